@@ -0,0 +1,39 @@
+use std::{fs, path::PathBuf};
+
+use crate::App;
+
+impl App<'_> {
+    /// Where backups of the grid go: `latest_file` with `.bak` appended.
+    pub(crate) fn backup_path(&self) -> PathBuf {
+        let mut path = self.latest_dir.to_path_buf();
+        path.push(format!("{}.bak", self.latest_file));
+        path
+    }
+
+    /// Counts a change to the grid, and writes a backup once `autosave_every`
+    /// changes have piled up. Does nothing while auto-save is off.
+    pub(crate) fn autosave_tick(&mut self) {
+        let Some(every) = self.autosave_every else {
+            return;
+        };
+        self.unsaved_changes += 1;
+        if self.unsaved_changes >= every {
+            self.unsaved_changes = 0;
+            if let Err(error) = self.save_grid(&self.backup_path(), false) {
+                self.log(&mut vec![format!("Auto-save failed: {error}")]);
+            }
+        }
+    }
+
+    /// The backup of `latest_file`, if it was written after the file itself.
+    pub(crate) fn newer_backup(&self) -> Option<PathBuf> {
+        let backup = self.backup_path();
+        let backup_time = fs::metadata(&backup).and_then(|m| m.modified()).ok()?;
+        let mut file = self.latest_dir.to_path_buf();
+        file.push(&self.latest_file);
+        match fs::metadata(&file).and_then(|m| m.modified()) {
+            Ok(file_time) if file_time >= backup_time => None,
+            _ => Some(backup),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use anyhow::{Context, Result, anyhow};
+use serde::Serialize;
+
+use crate::{NodeGrid, SelectedAlgorithm, nodegrid::TraceRecord};
+
+/// A single run without the interface, as asked for on the command line with
+/// `--run <algorithm> --grid <file> --out <file> [--frozen] [--seed <n>]`.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Headless {
+    algorithm: SelectedAlgorithm,
+    grid: PathBuf,
+    out: PathBuf,
+    /// Run without random processing.
+    frozen: bool,
+    /// Seed for the random choices of the run, so it can be repeated.
+    seed: Option<u64>,
+}
+
+/// What a headless run writes to its output file.
+#[derive(Debug, Serialize)]
+struct Report {
+    algorithm: SelectedAlgorithm,
+    completed: bool,
+    error: Option<String>,
+    log: Vec<String>,
+    /// Final state of every node, keyed by node name.
+    results: BTreeMap<String, String>,
+    trace: Vec<TraceRecord>,
+}
+
+impl Headless {
+    /// Returns `None` when `--run` isn't given, so the interface is started.
+    pub(crate) fn parse(args: &[String]) -> Result<Option<Headless>> {
+        if !args.iter().any(|a| a == "--run") {
+            return Ok(None);
+        }
+        let (mut algorithm, mut grid, mut out, mut frozen) = (None, None, None, false);
+        let mut seed = None;
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            if flag == "--frozen" {
+                frozen = true;
+                continue;
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| anyhow!("Missing value for {flag}."))?;
+            match flag.as_str() {
+                "--run" => {
+                    algorithm = Some(
+                        SelectedAlgorithm::from_str(value)
+                            .map_err(|_| anyhow!("Unknown algorithm {value}."))?,
+                    )
+                }
+                "--grid" => grid = Some(PathBuf::from(value)),
+                "--out" => out = Some(PathBuf::from(value)),
+                "--seed" => {
+                    seed = Some(
+                        value
+                            .parse()
+                            .map_err(|_| anyhow!("Bad seed {value}, expected a number."))?,
+                    )
+                }
+                _ => return Err(anyhow!("Unknown option {flag}.")),
+            }
+        }
+        Ok(Some(Headless {
+            algorithm: algorithm.ok_or_else(|| anyhow!("Missing value for --run."))?,
+            grid: grid.ok_or_else(|| anyhow!("--run needs a --grid to run on."))?,
+            out: out.ok_or_else(|| anyhow!("--run needs an --out file to write to."))?,
+            frozen,
+            seed,
+        }))
+    }
+
+    /// Runs the algorithm on the grid and writes the report. A run that does
+    /// not complete, or a grid the algorithm can't run on, is still reported,
+    /// only failing to read or write files is an error.
+    pub(crate) fn run(&self) -> Result<()> {
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .open(&self.grid)
+            .with_context(|| format!("Cannot open {}.", self.grid.display()))?;
+        let mut grid: NodeGrid = serde_json::from_reader(io::BufReader::new(file))
+            .with_context(|| format!("{} is not a valid grid.", self.grid.display()))?;
+        grid.frozen = self.frozen;
+        let mut log = vec![];
+        grid.drop_self_loops(&mut log);
+        let result = match (self.algorithm.preconditions(&grid), self.seed) {
+            (Err(violations), _) => {
+                log.push(violations.to_string());
+                Err(violations)
+            }
+            (Ok(()), Some(seed)) => grid.run_algorithm_seeded(self.algorithm, seed, &mut log),
+            (Ok(()), None) => grid.run_algorithm(self.algorithm, &mut log),
+        };
+        let report = Report {
+            algorithm: self.algorithm,
+            completed: result.is_ok(),
+            error: result.err().map(|e| e.to_string()),
+            log,
+            results: grid.results.into_iter().collect(),
+            trace: grid.trace,
+        };
+
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(&self.out)
+            .with_context(|| format!("Cannot write {}.", self.out.display()))?;
+        let mut writer = io::BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, &report)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
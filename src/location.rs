@@ -2,7 +2,7 @@ use std::cmp::Ordering;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, PartialOrd, Ord)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Location {
     pub(crate) x: u16,
     pub(crate) y: u16,
@@ -13,6 +13,13 @@ impl Location {
         Location { x, y }
     }
 
+    /// Squared euclidean distance to `other`.
+    pub fn distance_squared(&self, other: &Location) -> u32 {
+        let dx = self.x.abs_diff(other.x) as u32;
+        let dy = self.y.abs_diff(other.y) as u32;
+        dx * dx + dy * dy
+    }
+
     /// Returns the topleft most point of the bounding box drawn by `self` and `other`.
     pub fn lowest(&self, other: &Location) -> Self {
         Location {
@@ -22,18 +29,21 @@ impl Location {
     }
 }
 
-// impl PartialOrd for Location {
-//     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-//         match self.x.cmp(&other.x) {
-//             Ordering::Greater => match self.y.cmp(&other.y) {
-//                 Ordering::Less => None,
-//                 _ => Some(Ordering::Greater),
-//             },
-//             Ordering::Less => match self.y.cmp(&other.y) {
-//                 Ordering::Greater => None,
-//                 _ => Some(Ordering::Less),
-//             },
-//             Ordering::Equal => Some(self.y.cmp(&other.y)),
-//         }
-//     }
-// }
+/// Locations are ordered only when one lies both left of and above the other,
+/// or in line with it. Locations on a rising diagonal, like (0, 3) and (1, 2),
+/// can't be compared.
+impl PartialOrd for Location {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.x.cmp(&other.x) {
+            Ordering::Greater => match self.y.cmp(&other.y) {
+                Ordering::Less => None,
+                _ => Some(Ordering::Greater),
+            },
+            Ordering::Less => match self.y.cmp(&other.y) {
+                Ordering::Greater => None,
+                _ => Some(Ordering::Less),
+            },
+            Ordering::Equal => Some(self.y.cmp(&other.y)),
+        }
+    }
+}
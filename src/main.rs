@@ -1,12 +1,19 @@
 #![allow(unused_variables, unused_imports, dead_code)]
 
 use anyhow::{Context, Result, anyhow};
-use nodegrid::{NodeGrid, NodeGridDisplay, SelectedAlgorithm};
+use nodegrid::{
+    Delivery, Duplicates, Fix, MAX_GENERATED_NODES, MAX_TRAFFIC, NodeGrid, NodeGridDisplay,
+    SelectedAlgorithm, SteppedRun, Verbosity,
+};
+use rand::{SeedableRng, rngs::StdRng};
 use ratatui::{
     DefaultTerminal, Frame,
     buffer::Buffer,
-    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
-    layout::{Constraint, Flex, Layout, Rect},
+    crossterm::event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+        KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    },
+    layout::{Constraint, Flex, Layout, Position, Rect},
     style::{Style, Styled, Stylize},
     symbols::border,
     text::{Line, ToLine},
@@ -23,24 +30,52 @@ use std::{
 use strum::{Display, EnumIs, EnumIter, FromRepr, IntoEnumIterator};
 use tui_textarea::TextArea;
 use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
+use headless::Headless;
 use location::Location;
-use node::connection::Connection;
+use node::{
+    NodeBorder,
+    connection::{Connection, WEIGHT_STEP},
+};
+use script::Action;
+use session::GridFile;
+use theme::{THEME_FILE, Theme};
 
+mod backup;
+mod headless;
 mod location;
 mod node;
 mod nodegrid;
+mod script;
+mod session;
+mod theme;
 
 const NODE_HEIGHT: u16 = 3;
 const NODE_WIDTH: u16 = 6;
 const NODE_H_SPACING: u16 = 3;
 const NODE_V_SPACING: u16 = 3;
+const COMPACT_NODE_WIDTH: u16 = 4;
+const COMPACT_SPACING: u16 = 1;
+
+/// Popups never shrink below this, so their text area stays usable on tiny terminals.
+const POPUP_MIN_WIDTH: u16 = 24;
+const POPUP_MIN_HEIGHT: u16 = 8;
+const POPUP_MAX_LENGTH: u16 = 120;
+
+/// Sidebar width as a percentage of the screen.
+const SIDEBAR_DEFAULT_WIDTH: u16 = 50;
+const SIDEBAR_MIN_WIDTH: u16 = 20;
+const SIDEBAR_MAX_WIDTH: u16 = 80;
+const SIDEBAR_WIDTH_STEP: u16 = 5;
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 enum AppState {
     #[default]
     Default,
     Selection,
+    /// A run is advanced one step at a time.
+    Stepping,
     Popup(PopupState),
 }
 
@@ -52,9 +87,35 @@ enum PopupState {
     New,
     Pick,
     Connect,
+    Disconnect,
+    Generate,
+    Note,
+    Rename,
+    Script,
+    Diff,
+    Autosave,
+    MessageLimit,
+    Traffic,
+    RunSeed,
+    Restore,
+    GoTo,
+    States,
+    Partition,
+    Elections,
+    Distances,
+    Lint,
+    Collapse,
+    SaveSession,
+    LoadSession,
+    SpanningTree,
+    ImportAdjacency,
     #[default]
     Small,
     Edit,
+    Weights,
+    Inspect,
+    InspectStep,
+    Pseudocode,
     Large,
 }
 
@@ -74,8 +135,34 @@ impl PopupState {
             Self::New => PopupSize::Small,
             Self::Pick => PopupSize::Small,
             Self::Connect => PopupSize::Small,
+            Self::Disconnect => PopupSize::Small,
+            Self::Generate => PopupSize::Small,
+            Self::Note => PopupSize::Small,
+            Self::Rename => PopupSize::Small,
+            Self::Script => PopupSize::Small,
+            Self::Diff => PopupSize::Small,
+            Self::Autosave => PopupSize::Small,
+            Self::MessageLimit => PopupSize::Small,
+            Self::Traffic => PopupSize::Small,
+            Self::RunSeed => PopupSize::Small,
+            Self::Restore => PopupSize::Small,
+            Self::GoTo => PopupSize::Small,
+            Self::States => PopupSize::Small,
+            Self::Partition => PopupSize::Small,
+            Self::Elections => PopupSize::Small,
+            Self::Collapse => PopupSize::Small,
+            Self::SaveSession => PopupSize::Small,
+            Self::LoadSession => PopupSize::Small,
+            Self::SpanningTree => PopupSize::Small,
+            Self::ImportAdjacency => PopupSize::Small,
+            Self::InspectStep => PopupSize::Small,
             Self::Small => PopupSize::Small,
             Self::Edit => PopupSize::Large,
+            Self::Weights => PopupSize::Large,
+            Self::Inspect => PopupSize::Large,
+            Self::Pseudocode => PopupSize::Large,
+            Self::Distances => PopupSize::Large,
+            Self::Lint => PopupSize::Large,
             Self::Large => PopupSize::Large,
         }
     }
@@ -88,25 +175,110 @@ impl PopupState {
             Self::New => Line::from(" Unique node name ").left_aligned(),
             Self::Pick => Line::from(" Pick node with name ").left_aligned(),
             Self::Connect => Line::from(" Create weighted connection ").left_aligned(),
+            Self::Disconnect => Line::from(" Remove connection to ").left_aligned(),
+            Self::Generate => {
+                Line::from(" Generate example graph with N nodes, or an RxC lattice ")
+                    .left_aligned()
+            }
+            Self::Note => Line::from(" Note on node ").left_aligned(),
+            Self::Rename => Line::from(" Rename node ").left_aligned(),
+            Self::Script => Line::from(" Export recorded actions to... ").left_aligned(),
+            Self::Diff => Line::from(" Compare structure with... ").left_aligned(),
+            Self::Autosave => {
+                Line::from(" Back up after every N changes (0 is off) ").left_aligned()
+            }
+            Self::MessageLimit => {
+                Line::from(" Stop runs after delivering N messages (0 is off) ").left_aligned()
+            }
+            Self::Traffic => {
+                Line::from(" Random messages per node in snapshots (0 is a fixed few) ")
+                    .left_aligned()
+            }
+            Self::RunSeed => {
+                Line::from(" Seed the random choices of runs (empty is random) ").left_aligned()
+            }
+            Self::Restore => Line::from(" Newer backup found ").left_aligned(),
+            Self::GoTo => Line::from(" Go to coordinate x y ").left_aligned(),
+            Self::States => {
+                Line::from(" Random initial states up to N, optionally seeded ").left_aligned()
+            }
+            Self::Partition => {
+                Line::from(" Cut N% of links for runs, optionally seeded (0 lifts) ").left_aligned()
+            }
+            Self::Elections => {
+                Line::from(" Compare elections on a ring of N nodes, optionally seeded ")
+                    .left_aligned()
+            }
+            Self::Collapse => {
+                Line::from(" Collapse nodes a b..., or expand a super-node ").left_aligned()
+            }
+            Self::SaveSession => Line::from(" Save session to... ").left_aligned(),
+            Self::LoadSession => Line::from(" Resume session... ").left_aligned(),
+            Self::SpanningTree => {
+                Line::from(" Spanning tree from root (empty clears) ").left_aligned()
+            }
+            Self::ImportAdjacency => Line::from(" Import adjacency list... ").left_aligned(),
             Self::Small => Line::from(" Small Popup ").left_aligned(),
             Self::Edit => Line::from(" Edit node ").left_aligned(),
+            Self::Weights => Line::from(" Edit connection weights ").left_aligned(),
+            Self::Inspect => Line::from(" Inspect node ").left_aligned(),
+            Self::InspectStep => Line::from(" Inspect node named... ").left_aligned(),
+            Self::Pseudocode => Line::from(" Pseudocode ").left_aligned(),
+            Self::Distances => Line::from(" Shortest path distances ").left_aligned(),
+            Self::Lint => Line::from(" Check grid for algorithm ").left_aligned(),
             Self::Large => Line::from(" Large Popup ").left_aligned(),
         }
     }
 
     fn title_bottom<'a>(self) -> Line<'a> {
         match self {
-            Self::Save => Line::from(" <Esc> Cancel - <Enter> Save ").right_aligned(),
-            Self::Load => Line::from(" <Esc> Cancel - <Enter> Load ").right_aligned(),
-            Self::Dump => Line::from(" <Esc> Cancel - <Enter> Dump ").right_aligned(),
+            Self::Save => Line::from(" <Esc> Cancel - <Enter> Save <Alt+Enter> Save anonymized ")
+                .right_aligned(),
+            Self::Load => Line::from(" <Esc> Cancel - <Enter> Load <Alt+Enter> Replay script ")
+                .right_aligned(),
+            Self::Dump => {
+                Line::from(" <Esc> Cancel - <Enter> Dump <Alt+Enter> Dump message trace ")
+                    .right_aligned()
+            }
             Self::New => Line::from(" <Esc> Cancel - <Enter> Create ").right_aligned(),
             Self::Pick => Line::from(" <Esc> Cancel - <Enter> Pick ").right_aligned(),
             Self::Connect => {
                 Line::from(" <Esc> Cancel - <Enter> Create <Alt+Enter> Create undirected ")
                     .right_aligned()
             }
+            Self::Disconnect => {
+                Line::from(" <Esc> Cancel - <Enter> Remove <Alt+Enter> Remove both ways ")
+                    .right_aligned()
+            }
+            Self::Generate => Line::from(" <Esc> Cancel - <Enter> Generate ").right_aligned(),
+            Self::Note => Line::from(" <Esc> Cancel - <Enter> Apply ").right_aligned(),
+            Self::Rename => Line::from(" <Esc> Cancel - <Enter> Rename ").right_aligned(),
+            Self::Script => Line::from(" <Esc> Discard - <Enter> Export ").right_aligned(),
+            Self::Diff => Line::from(" <Esc> Cancel - <Enter> Compare ").right_aligned(),
+            Self::Autosave => Line::from(" <Esc> Cancel - <Enter> Apply ").right_aligned(),
+            Self::MessageLimit => Line::from(" <Esc> Cancel - <Enter> Apply ").right_aligned(),
+            Self::Traffic => Line::from(" <Esc> Cancel - <Enter> Apply ").right_aligned(),
+            Self::RunSeed => Line::from(" <Esc> Cancel - <Enter> Apply ").right_aligned(),
+            Self::Restore => Line::from(" <Esc> Ignore - <Enter> Restore ").right_aligned(),
+            Self::GoTo => Line::from(" <Esc> Cancel - <Enter> Go ").right_aligned(),
+            Self::States => Line::from(" <Esc> Cancel - <Enter> Assign ").right_aligned(),
+            Self::Partition => Line::from(" <Esc> Cancel - <Enter> Cut ").right_aligned(),
+            Self::Elections => Line::from(" <Esc> Cancel - <Enter> Compare ").right_aligned(),
+            Self::Collapse => Line::from(" <Esc> Cancel - <Enter> Apply ").right_aligned(),
+            Self::SaveSession => Line::from(" <Esc> Cancel - <Enter> Save ").right_aligned(),
+            Self::LoadSession => Line::from(" <Esc> Cancel - <Enter> Resume ").right_aligned(),
+            Self::SpanningTree => Line::from(" <Esc> Cancel - <Enter> Show ").right_aligned(),
+            Self::ImportAdjacency => Line::from(" <Esc> Cancel - <Enter> Import ").right_aligned(),
             Self::Small => Line::from(" Close with <Esc> - <Enter> Log ").right_aligned(),
             Self::Edit => Line::from(" <Esc> Cancel - <Ctrl+s> Apply ").right_aligned(),
+            Self::Weights => Line::from(" <Esc> Cancel - <Ctrl+s> Apply ").right_aligned(),
+            Self::Inspect => Line::from(" Close with <Esc> ").right_aligned(),
+            Self::InspectStep => Line::from(" <Esc> Cancel - <Enter> Inspect ").right_aligned(),
+            Self::Pseudocode => Line::from(" Close with <Esc> ").right_aligned(),
+            Self::Distances => Line::from(" Close with <Esc> - <Enter> Log ").right_aligned(),
+            Self::Lint => {
+                Line::from(" Close with <Esc> - <Enter> Log - <Key> Apply fix ").right_aligned()
+            }
             Self::Large => Line::from(" Close with <Esc> - <Alt+Enter> Log ").right_aligned(),
         }
     }
@@ -130,9 +302,54 @@ impl PopupState {
             }
             Self::New => String::from(""),
             Self::Pick => String::from(""),
-            Self::Connect => String::from("1.0 n"),
+            Self::Connect => format!("1.0 {}", app.drop_target.as_deref().unwrap_or("n")),
+            Self::Disconnect => app.first_neighbour().unwrap_or_default(),
+            Self::Generate => String::from("6"),
+            Self::Note => app.get_node_note(),
+            Self::Rename => app.selection_name().unwrap_or_default(),
+            Self::Script => {
+                let mut full_file = app.latest_dir.to_path_buf();
+                full_file.push("script.json");
+                full_file.display().to_string()
+            }
+            Self::Diff => {
+                let mut full_file = app.latest_dir.to_path_buf();
+                full_file.push(app.latest_file.clone());
+                full_file.display().to_string()
+            }
+            Self::Autosave => app.autosave_every.unwrap_or(0).to_string(),
+            Self::MessageLimit => app.message_limit.unwrap_or(0).to_string(),
+            Self::Traffic => app.traffic.unwrap_or(0).to_string(),
+            Self::RunSeed => app.run_seed.map_or(String::new(), |s| s.to_string()),
+            Self::Restore => app.backup_path().display().to_string(),
+            Self::GoTo => String::from("0 0"),
+            Self::States => String::from("10"),
+            Self::Partition => String::from("20"),
+            Self::Elections => String::from("8"),
+            Self::Collapse => String::from(""),
+            Self::SpanningTree => String::from(""),
+            Self::ImportAdjacency => {
+                let mut full_file = app.latest_dir.to_path_buf();
+                full_file.push("adjacency.txt");
+                full_file.display().to_string()
+            }
+            Self::SaveSession | Self::LoadSession => {
+                let mut full_file = app.latest_dir.to_path_buf();
+                full_file.push("session.json");
+                full_file.display().to_string()
+            }
             Self::Small => String::from(""),
-            Self::Edit => app.get_node_serialized(),
+            Self::Edit => app.get_node_serialized().unwrap_or_default(),
+            Self::Weights => app
+                .node_display
+                .grid
+                .connection_weights()
+                .unwrap_or_default(),
+            Self::Inspect => app.get_node_inspection(),
+            Self::InspectStep => String::from(""),
+            Self::Pseudocode => app.get_pseudocode(),
+            Self::Distances => app.get_distance_matrix(),
+            Self::Lint => app.get_lint_report(),
             Self::Large => String::from(""),
         }
     }
@@ -170,6 +387,7 @@ struct Sidebar<'a> {
     log: Vec<String>,
     log_scroll_state: usize,
     selector_scroll_state: usize,
+    theme: Theme,
 }
 
 #[derive(Debug, Default)]
@@ -182,19 +400,101 @@ pub struct App<'a> {
     textarea: TextArea<'a>,
     latest_dir: PathBuf,
     latest_file: String,
+    /// Draw connections as the channels of the algorithm highlighted in the selector.
+    show_channels: bool,
+    long_edge_labels: bool,
+    /// Show how many messages went over every connection in the last run.
+    message_counts: bool,
+    /// Write the weight of every connection next to it.
+    edge_weights: bool,
+    hide_connections: bool,
+    compact: bool,
+    show_degree: bool,
+    show_clocks: bool,
+    /// Draw nodes with a heavy total connection weight larger.
+    weighted_size: bool,
+    node_border: NodeBorder,
+    /// Colors of the grid and the sidebar, read from `THEME_FILE` at startup.
+    theme: Theme,
+    /// The zoom was chosen by hand, so it is kept when a grid is loaded.
+    zoom_pinned: bool,
+    /// Seed of the last seeded command.
+    seed: Option<u64>,
+    verbosity: Verbosity,
+    /// Run without random processing.
+    frozen: bool,
+    /// Delivery order of the messages in a run, instead of the channels of
+    /// the algorithm.
+    delivery: Option<Delivery>,
+    /// Runs stop after delivering this many messages, if set.
+    message_limit: Option<usize>,
+    /// Random basic messages per node the snapshots send, if set.
+    traffic: Option<usize>,
+    /// Runs draw their random choices from this seed, if set.
+    run_seed: Option<u64>,
+    /// Runs pick their initiator by the weight of its connections.
+    weighted_initiator: bool,
+    /// What loading a grid does with nodes sharing a name.
+    duplicates: Duplicates,
+    /// Index of the next special node of the last run to center on.
+    special_cursor: usize,
+    /// The run being stepped through, kept between steps.
+    stepping: Option<Box<dyn SteppedRun>>,
+    /// Lint fix waiting to be confirmed, as it would drop connections.
+    unconfirmed_fix: Option<Fix>,
+    /// Node inspected while stepping, when there is no picked node to inspect.
+    inspected: Option<String>,
+    /// Node a drag with the mouse started on.
+    drag_from: Option<String>,
+    /// Whether the mouse moved since the button went down on `drag_from`,
+    /// which tells a drag from a click.
+    dragged: bool,
+    /// Location under the mouse while dragging the selection around.
+    drag_location: Option<Location>,
+    /// Node a drag ended on, filled in as the other end of the connection.
+    drop_target: Option<String>,
+    /// Size of the terminal when it was last drawn.
+    screen: Rect,
+    last_algorithm: Option<SelectedAlgorithm>,
+    /// Nodes and channels the last run was on, to compare the messages it
+    /// sent with the bound of the algorithm.
+    last_run_size: (usize, usize),
+    /// Actions recorded so far, while recording.
+    recording: Option<Vec<Action>>,
+    /// The last finished recording.
+    script: Vec<Action>,
+    /// Write a backup after this many changes to the grid, if set.
+    autosave_every: Option<usize>,
+    unsaved_changes: usize,
 }
 
 fn main() -> Result<()> {
-    let mut terminal = ratatui::init();
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(headless) = Headless::parse(&args)? {
+        return headless.run();
+    }
     let grid = NodeGrid::default();
     let node_display = NodeGridDisplay::new(grid.clone());
+    let (width, height) = ratatui::crossterm::terminal::size()?;
     let mut app = App {
         node_display,
         latest_dir: env::current_dir()?,
         latest_file: String::from("grid.json"),
+        screen: Rect::new(0, 0, width, height),
         ..Default::default()
     };
+    app.theme = Theme::load(&app.latest_dir.join(THEME_FILE))
+        .with_context(|| format!("{THEME_FILE} is not a valid theme."))?;
+    if let Some(argument) = args.first() {
+        app.load_argument(argument)?;
+    }
+    if app.newer_backup().is_some() {
+        app.open_popup(PopupState::Restore);
+    }
+    let mut terminal = ratatui::init();
+    ratatui::crossterm::execute!(io::stdout(), EnableMouseCapture)?;
     let app_result = app.run(&mut terminal);
+    ratatui::crossterm::execute!(io::stdout(), DisableMouseCapture)?;
     ratatui::restore();
     app_result
 }
@@ -202,9 +502,10 @@ fn main() -> Result<()> {
 impl App<'_> {
     pub fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
         while !self.exit {
-            terminal
+            self.screen = terminal
                 .draw(|frame| self.draw(frame))
-                .context("Drawing to terminal failed.")?;
+                .context("Drawing to terminal failed.")?
+                .area;
             self.handle_events()?;
         }
         Ok(())
@@ -214,6 +515,10 @@ impl App<'_> {
         frame.render_widget(self, frame.area());
     }
 
+    fn highlighted_algorithm(&self) -> Option<SelectedAlgorithm> {
+        SelectedAlgorithm::from_repr(self.sidebar.selector_scroll_state)
+    }
+
     fn get_instructions(&self) -> Line<'_> {
         match self.state {
             AppState::Default => Line::from(vec![
@@ -233,9 +538,21 @@ impl App<'_> {
                 "<󰁍󰁅󰁝󰁔>".blue().bold(),
                 " Edit ".into(),
                 "<E>".blue().bold(),
+                " Inspect ".into(),
+                "<I>".blue().bold(),
                 " Place node ".into(),
                 "<Enter> ".blue().bold(),
             ]),
+            AppState::Stepping => Line::from(vec![
+                " Step ".into(),
+                "<Space>".blue().bold(),
+                " Finish run ".into(),
+                "<F>".blue().bold(),
+                " Inspect ".into(),
+                "<I>".blue().bold(),
+                " Stop ".into(),
+                "<Esc> ".blue().bold(),
+            ]),
             AppState::Popup(_) => Line::from(" Follow instructions in popup "),
         }
     }
@@ -247,6 +564,7 @@ impl App<'_> {
                     Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
                         self.handle_default_key_event(key_event)?
                     }
+                    Event::Mouse(mouse_event) => self.handle_mouse_event(mouse_event)?,
                     _ => {}
                 };
             }
@@ -255,6 +573,15 @@ impl App<'_> {
                     Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
                         self.handle_selection_key_event(key_event)?
                     }
+                    Event::Mouse(mouse_event) => self.handle_selection_mouse_event(mouse_event),
+                    _ => {}
+                };
+            }
+            AppState::Stepping => {
+                match event::read()? {
+                    Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                        self.handle_stepping_key_event(key_event)
+                    }
                     _ => {}
                 };
             }
@@ -265,6 +592,13 @@ impl App<'_> {
                 PopupState::New => self.new_textarea()?,
                 PopupState::Pick => self.pick_textarea()?,
                 PopupState::Edit => self.edit_textarea()?,
+                PopupState::Weights => self.weights_textarea()?,
+                PopupState::Inspect => self.inspect_textarea()?,
+                PopupState::InspectStep => self.inspect_step_textarea()?,
+                PopupState::Pseudocode => self.pseudocode_textarea()?,
+                PopupState::Distances => self.distances_textarea()?,
+                PopupState::Lint => self.lint_textarea()?,
+                PopupState::Elections => self.elections_textarea()?,
                 PopupState::Large => {
                     self.handle_large_textarea_key_event()?;
                 }
@@ -272,6 +606,25 @@ impl App<'_> {
                     self.handle_textarea_key_event()?;
                 }
                 PopupState::Connect => self.connect_textarea()?,
+                PopupState::Disconnect => self.disconnect_textarea()?,
+                PopupState::Generate => self.generate_textarea()?,
+                PopupState::Note => self.note_textarea()?,
+                PopupState::Rename => self.rename_textarea()?,
+                PopupState::Script => self.script_textarea()?,
+                PopupState::Diff => self.diff_textarea()?,
+                PopupState::GoTo => self.go_to_textarea()?,
+                PopupState::States => self.states_textarea()?,
+                PopupState::Partition => self.partition_textarea()?,
+                PopupState::Collapse => self.collapse_textarea()?,
+                PopupState::SaveSession => self.save_session_textarea()?,
+                PopupState::LoadSession => self.load_session_textarea()?,
+                PopupState::SpanningTree => self.spanning_tree_textarea()?,
+                PopupState::ImportAdjacency => self.import_adjacency_textarea()?,
+                PopupState::Autosave => self.autosave_textarea()?,
+                PopupState::MessageLimit => self.message_limit_textarea()?,
+                PopupState::Traffic => self.traffic_textarea()?,
+                PopupState::RunSeed => self.run_seed_textarea()?,
+                PopupState::Restore => self.restore_textarea()?,
             },
         }
         Ok(())
@@ -289,21 +642,108 @@ impl App<'_> {
             KeyCode::Char('d') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.open_popup(PopupState::Dump);
             }
+            KeyCode::Char('e') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.open_popup(PopupState::SaveSession);
+            }
+            KeyCode::Char('r') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.open_popup(PopupState::LoadSession);
+            }
+            KeyCode::Char('f') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.open_popup(PopupState::Diff);
+            }
+            KeyCode::Char('a') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.open_popup(PopupState::ImportAdjacency);
+            }
             KeyCode::Char('p') => self.open_popup(PopupState::Pick),
             KeyCode::Char('t') => self.open_popup(PopupState::Small),
             KeyCode::Char('y') => self.open_popup(PopupState::Large),
+            KeyCode::Char('C') => self.open_popup(PopupState::Pseudocode),
             KeyCode::Char('n') => self.open_popup(PopupState::New),
             KeyCode::Char('j') => self.sidebar_scroll_down(),
             KeyCode::Char('k') => self.sidebar_scroll_up(),
             KeyCode::Char('\\') => self.toggle_sidebar(),
+            KeyCode::Char('f') => self.show_channels = !self.show_channels,
+            KeyCode::Char('L') => self.long_edge_labels = !self.long_edge_labels,
+            KeyCode::Char('c') => self.message_counts = !self.message_counts,
+            KeyCode::Char('w') => self.edge_weights = !self.edge_weights,
+            KeyCode::Char('h') => self.hide_connections = !self.hide_connections,
+            KeyCode::Char('z') => {
+                self.compact = !self.compact;
+                self.zoom_pinned = true;
+            }
+            KeyCode::Char('D') => self.show_degree = !self.show_degree,
+            KeyCode::Char('Y') => self.show_clocks = !self.show_clocks,
+            KeyCode::Char('H') => self.weighted_size = !self.weighted_size,
+            KeyCode::Char('E') => self.cycle_node_border(),
+            KeyCode::Char('U') => self.restore_deleted(),
+            KeyCode::Char('K') => self.open_popup(PopupState::Partition),
+            KeyCode::Char('P') => self.open_popup(PopupState::Distances),
+            KeyCode::Char('J') => self.open_popup(PopupState::Collapse),
+            KeyCode::Char('N') => self.cycle_duplicates(),
+            KeyCode::Char('Z') => {
+                self.zoom_pinned = false;
+                self.zoom_to_fit();
+            }
+            KeyCode::Char('v') => self.toggle_verbosity(),
+            KeyCode::Char('F') => self.toggle_frozen(),
+            KeyCode::Char('u') => self.toggle_weighted_initiator(),
+            KeyCode::Char('O') => self.cycle_delivery(),
+            KeyCode::Char('G') => self.open_popup(PopupState::GoTo),
+            KeyCode::Char('V') => self.open_popup(PopupState::States),
+            KeyCode::Char('W') => self.open_popup(PopupState::SpanningTree),
+            KeyCode::Char('T') => self.log_tree_check(),
+            KeyCode::Char('m') => self.log_symmetry(),
+            KeyCode::Char('l') => self.open_popup(PopupState::Lint),
+            KeyCode::Char('x') => self.next_special_node(),
+            KeyCode::Char('b') => self.open_popup(PopupState::Elections),
+            KeyCode::Char('.') => {
+                // A failed run is already explained in the log.
+                let _ = self.repeat_algorithm();
+            }
+            KeyCode::Char('B') => self.log_message_bound(),
+            KeyCode::Char('I') => self.include_all(),
+            KeyCode::Char('a') => self.align_grid(),
+            KeyCode::Char('A') => self.open_popup(PopupState::Autosave),
+            KeyCode::Char('M') => self.open_popup(PopupState::MessageLimit),
+            KeyCode::Char('i') => self.open_popup(PopupState::Traffic),
+            KeyCode::Char('S') => self.compare_snapshots(),
+            KeyCode::Char('R') => match self.recording {
+                Some(_) => {
+                    self.stop_recording();
+                    self.open_popup(PopupState::Script);
+                }
+                None => self.start_recording(),
+            },
             KeyCode::Char('r') if self.sidebar_state.is_shown() => self.sidebar.selector(),
             KeyCode::Char('e') if self.sidebar_state.is_shown() => self.sidebar.log(),
             KeyCode::Enter
                 if self.sidebar_state.is_shown()
                     & (self.sidebar.shown_content == SidebarContent::Selector) =>
             {
-                self.select_algorithm()?
+                // A failed run is already explained in the log.
+                let _ = self.select_algorithm();
+            }
+            KeyCode::Char(' ')
+                if self.sidebar_state.is_shown()
+                    & (self.sidebar.shown_content == SidebarContent::Selector) =>
+            {
+                // A run which can't be stepped is already explained in the log.
+                let _ = self.start_stepping();
+            }
+            KeyCode::Char('g')
+                if self.sidebar_state.is_shown()
+                    & (self.sidebar.shown_content == SidebarContent::Selector) =>
+            {
+                self.open_popup(PopupState::Generate)
+            }
+            KeyCode::Char('s')
+                if self.sidebar_state.is_shown()
+                    & (self.sidebar.shown_content == SidebarContent::Selector) =>
+            {
+                self.open_popup(PopupState::RunSeed)
             }
+            KeyCode::Char('<') if self.sidebar_state.is_shown() => self.widen_sidebar(),
+            KeyCode::Char('>') if self.sidebar_state.is_shown() => self.narrow_sidebar(),
             KeyCode::Delete
                 if self.sidebar_state.is_shown()
                     & key_event.modifiers.contains(KeyModifiers::ALT) =>
@@ -318,13 +758,494 @@ impl App<'_> {
     fn select_algorithm(&mut self) -> Result<(), anyhow::Error> {
         let algorithm = SelectedAlgorithm::from_repr(self.sidebar.selector_scroll_state)
             .ok_or_else(|| anyhow!("Parsing scroll state {} to Algorithm failed.", 0))?;
+        if let Err(violations) = algorithm.preconditions(&self.node_display.grid) {
+            self.sidebar.log.push(violations.to_string());
+            return Err(violations);
+        }
+        let result = self.run_algorithm(algorithm);
+        if result.is_err() {
+            // Stay in the selector, to pick another algorithm or generate a
+            // fitting grid.
+            self.sidebar.selector();
+        }
+        result
+    }
+
+    /// Runs the most recently selected algorithm again, for a fresh trace.
+    fn repeat_algorithm(&mut self) -> Result<()> {
+        match self.last_algorithm {
+            Some(algorithm) => self.run_algorithm(algorithm),
+            None => {
+                self.log(&mut vec!["No algorithm has been run yet.".to_string()]);
+                Ok(())
+            }
+        }
+    }
+
+    /// Compares the message count of the last run with the algorithm's worst case.
+    fn log_message_bound(&mut self) {
+        let Some(algorithm) = self.last_algorithm else {
+            self.log(&mut vec!["No algorithm has been run yet.".to_string()]);
+            return;
+        };
+        let (nodes, channels) = self.last_run_size;
+        let line = match (
+            algorithm.complexity(),
+            algorithm.message_bound(nodes, channels),
+        ) {
+            (Some(complexity), Some(bound)) => format!(
+                "{algorithm} on {nodes} nodes: bound {complexity} = {bound} messages, last run sent {}.",
+                self.node_display.grid.messages_sent()
+            ),
+            _ => format!("{algorithm} has no known message bound."),
+        };
+        self.log(&mut vec![line]);
+    }
+
+    fn run_algorithm(&mut self, algorithm: SelectedAlgorithm) -> Result<()> {
+        self.last_algorithm = Some(algorithm);
+        self.last_run_size = self.node_display.grid.run_size();
+        self.sidebar.log();
+        if !self.sidebar.log.is_empty() {
+            self.sidebar.log.push(String::new());
+        }
+        let start = self.sidebar.log.len();
+        self.configure_run();
+        self.special_cursor = 0;
+        let result = self
+            .node_display
+            .grid
+            .run_algorithm(algorithm, &mut self.sidebar.log);
+        self.verbosity.apply(&mut self.sidebar.log, start);
+        self.record(Action::Run(algorithm));
+        result
+    }
+
+    /// Starts a run of the highlighted algorithm, to be advanced one step at
+    /// a time, and takes its first step.
+    fn start_stepping(&mut self) -> Result<()> {
+        let algorithm = self
+            .highlighted_algorithm()
+            .ok_or_else(|| anyhow!("No algorithm highlighted."))?;
+        if let Err(violations) = algorithm.preconditions(&self.node_display.grid) {
+            self.sidebar.log.push(violations.to_string());
+            return Err(violations);
+        }
         self.sidebar.log();
+        if !self.sidebar.log.is_empty() {
+            self.sidebar.log.push(String::new());
+        }
+        self.configure_run();
+        self.special_cursor = 0;
+        let run = self
+            .node_display
+            .grid
+            .start_stepping(algorithm, &mut self.sidebar.log);
+        let run = match run {
+            Ok(run) => run,
+            Err(error) => {
+                self.sidebar.log.push(error.to_string());
+                return Err(error);
+            }
+        };
+        self.last_algorithm = Some(algorithm);
+        self.last_run_size = self.node_display.grid.run_size();
+        self.stepping = Some(run);
+        self.state = AppState::Stepping;
+        self.step_run();
+        Ok(())
+    }
+
+    fn handle_stepping_key_event(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Char(' ') => self.step_run(),
+            KeyCode::Char('F') => self.finish_run(),
+            KeyCode::Char('i') => self.open_popup(PopupState::InspectStep),
+            KeyCode::Esc => self.stop_stepping(),
+            _ => {}
+        }
+    }
+
+    /// Advances the run by a single step, ending it when no steps are left.
+    fn step_run(&mut self) {
+        let Some(run) = self.stepping.as_mut() else {
+            return;
+        };
+        let start = self.sidebar.log.len();
+        let step = run.advance(&mut self.sidebar.log);
+        run.store(&mut self.node_display.grid);
+        self.verbosity.apply(&mut self.sidebar.log, start);
+        if step.is_last() {
+            self.conclude_run();
+        }
+    }
+
+    /// Takes every step left in the run.
+    fn finish_run(&mut self) {
+        while self.stepping.is_some() {
+            self.step_run();
+        }
+    }
+
+    fn conclude_run(&mut self) {
+        let Some(mut run) = self.stepping.take() else {
+            return;
+        };
+        if run.conclude(&mut self.sidebar.log).is_err()
+            && let Some(algorithm) = self.last_algorithm
+        {
+            self.sidebar
+                .log
+                .push(format!("{algorithm} did not complete."));
+        }
+        run.store(&mut self.node_display.grid);
+        self.state_default();
+    }
+
+    /// Drops the run being stepped through, keeping the state it reached.
+    fn stop_stepping(&mut self) {
+        self.stepping = None;
+        self.log(&mut vec![
+            "Stopped stepping, the run is unfinished.".to_string(),
+        ]);
+        self.state_default();
+    }
+
+    /// Area the grid is drawn in, next to the sidebar if it is shown.
+    fn grid_area(&self) -> Rect {
+        match self.sidebar_state {
+            SidebarState::Hidden => self.screen,
+            SidebarState::Shown => sidebar_split(self.screen, self.sidebar.width)[0],
+        }
+    }
+
+    /// Scrolls the grid so the coordinate given as `x y` is centered.
+    fn go_to(&mut self, input: &str) -> Result<()> {
+        let coordinates = input
+            .split_whitespace()
+            .map(str::parse::<u16>)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| anyhow!("Bad coordinate {input:?}, expected `x y`."))?;
+        let [x, y] = coordinates[..] else {
+            return Err(anyhow!("Bad coordinate {input:?}, expected `x y`."));
+        };
+        let area = self.grid_area();
+        let location = self
+            .node_display
+            .center_on(&Location::new(x, y), area, self.compact);
+        self.log(&mut vec![format!(
+            "Centered on ({}, {}).",
+            location.x, location.y
+        )]);
+        Ok(())
+    }
+
+    /// Scrolls the grid so the node named `name` is centered.
+    fn jump_to_node(&mut self, name: &str) -> Result<()> {
+        let grid = &self.node_display.grid;
+        let location = grid
+            .nodes
+            .iter()
+            .chain(grid.floating_nodes.iter())
+            .find(|n| n.name == name)
+            .ok_or(anyhow!("No node named {name:?}."))?
+            .location;
+        let area = self.grid_area();
+        self.node_display.center_on(&location, area, self.compact);
+        self.log(&mut vec![format!("Centered on {name}.")]);
+        Ok(())
+    }
+
+    /// Centers on the next special node of the last run, listing them all when
+    /// starting over at the first.
+    fn next_special_node(&mut self) {
+        let special = self.node_display.grid.special_nodes();
+        if special.is_empty() {
+            self.log(&mut vec!["No special nodes in the last run.".to_string()]);
+            return;
+        }
+        if self.special_cursor >= special.len() {
+            self.special_cursor = 0;
+        }
+        if self.special_cursor == 0 {
+            let listed: Vec<String> = special
+                .iter()
+                .map(|(name, role)| format!("{name} ({role})"))
+                .collect();
+            self.log(&mut vec![format!("Special nodes: {}.", listed.join(", "))]);
+        }
+        let (name, _) = &special[self.special_cursor];
+        // Every special node is placed, so this can't fail.
+        let _ = self.jump_to_node(name);
+        self.special_cursor += 1;
+    }
+
+    /// The word of the log drawn at `position`, if the log is shown there.
+    /// Words are made of the characters node names usually consist of.
+    fn log_word_at(&self, position: Position) -> Option<String> {
+        if self.sidebar_state.is_hidden() || self.sidebar.shown_content != SidebarContent::Log {
+            return None;
+        }
+        let sidebar_area = sidebar_split(self.screen, self.sidebar.width)[1];
+        let [_, content_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(sidebar_area);
+        let interior = Block::bordered().inner(content_area);
+        if !interior.contains(position) {
+            return None;
+        }
+        let lines = self.sidebar.clone().create_wrapped_lines(interior.width);
+        let overflow = lines.len().saturating_sub(interior.height.into());
+        let index =
+            self.sidebar.log_scroll_state.min(overflow) + (position.y - interior.y) as usize;
+        let line = lines.get(index)?.to_string();
+        let graphemes: Vec<&str> = line.graphemes(true).collect();
+        let is_word = |g: &&str| {
+            g.chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        };
+        let column = (position.x - interior.x) as usize;
+        if !graphemes.get(column).is_some_and(is_word) {
+            return Some(String::new());
+        }
+        let start = graphemes[..column]
+            .iter()
+            .rposition(|g| !is_word(g))
+            .map_or(0, |i| i + 1);
+        let end = graphemes[column..]
+            .iter()
+            .position(|g| !is_word(g))
+            .map_or(graphemes.len(), |i| column + i);
+        Some(graphemes[start..end].concat())
+    }
+
+    /// Assigns random initial states, given as `range` or `range seed`.
+    fn seed_states(&mut self, input: &str) -> Result<()> {
+        let bad_input = || anyhow!("Bad input {input:?}, expected `range` or `range seed`.");
+        let mut parts = input.split_whitespace();
+        let range = parts
+            .next()
+            .and_then(|range| range.parse::<u32>().ok())
+            .ok_or_else(bad_input)?;
+        let seed = parts
+            .next()
+            .map(|seed| seed.parse::<u64>().map_err(|_| bad_input()))
+            .transpose()?;
+        if parts.next().is_some() {
+            return Err(bad_input());
+        }
+        let mut rng = self.command_rng(seed);
+        self.node_display
+            .grid
+            .seed_states(range as isize, &mut rng, &mut self.sidebar.log);
+        self.autosave_tick();
+        Ok(())
+    }
+
+    /// Source of the random choices of a command: `seed` if one was given,
+    /// otherwise the run seed, otherwise a fresh one. The seed is kept, so the
+    /// command can be repeated.
+    fn command_rng(&mut self, seed: Option<u64>) -> StdRng {
+        let seed = seed.or(self.run_seed).unwrap_or_else(rand::random);
+        self.seed = Some(seed);
+        StdRng::seed_from_u64(seed)
+    }
+
+    /// Cuts random links for the runs to come, given as `percent` or
+    /// `percent seed`.
+    fn partition(&mut self, input: &str) -> Result<()> {
+        let numbers = input
+            .split_whitespace()
+            .map(str::parse::<u64>)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| anyhow!("Bad input {input:?}, expected `percent` or `percent seed`."))?;
+        let (percent, seed) = match numbers[..] {
+            [percent] => (percent, None),
+            [percent, seed] => (percent, Some(seed)),
+            _ => Err(anyhow!(
+                "Bad input {input:?}, expected `percent` or `percent seed`."
+            ))?,
+        };
+        let mut rng = self.command_rng(seed);
         self.node_display
             .grid
-            .run_algorithm(algorithm, &mut self.sidebar.log)?;
+            .partition(percent, &mut rng, &mut self.sidebar.log);
+        Ok(())
+    }
+
+    /// Runs every election algorithm on the same generated ring, given as
+    /// `size` or `size seed`, and logs how many messages each one sent. The
+    /// grid itself is left alone.
+    fn compare_elections(&mut self, input: &str) -> Result<()> {
+        let bad_input = || anyhow!("Bad input {input:?}, expected `size` or `size seed`.");
+        let mut parts = input.split_whitespace();
+        let size = parts
+            .next()
+            .and_then(|size| size.parse::<usize>().ok())
+            .ok_or_else(bad_input)?;
+        let seed = parts
+            .next()
+            .map(|seed| seed.parse::<u64>().map_err(|_| bad_input()))
+            .transpose()?;
+        if parts.next().is_some() {
+            return Err(bad_input());
+        }
+        if !(2..=MAX_GENERATED_NODES).contains(&size) {
+            return Err(anyhow!(
+                "A ring to compare elections on needs between 2 and {MAX_GENERATED_NODES} nodes."
+            ));
+        }
+        let mut rng = self.command_rng(seed);
+        let mut ring = NodeGrid::generate_ring(size, &mut rng);
+        ring.frozen = self.frozen;
+        ring.delivery = self.delivery;
+        ring.message_limit = self.message_limit;
+        ring.seed = self.seed;
+        let mut lines = vec![format!(
+            "Elections on a ring of {size} nodes with seed {}:",
+            self.seed.unwrap_or_default()
+        )];
+        lines.extend(ring.compare_elections());
+        self.sidebar.log();
+        self.log(&mut lines);
+        Ok(())
+    }
+
+    /// Collapses the nodes named in `input` into a super-node named after all
+    /// of them, or expands the super-node if only one is named.
+    fn collapse_nodes(&mut self, input: &str) -> Result<()> {
+        let names: Vec<String> = input.split_whitespace().map(String::from).collect();
+        let grid = &mut self.node_display.grid;
+        let line = match &names[..] {
+            [name] => format!("Expanded {name} into {}.", grid.expand(name)?.join(", ")),
+            _ => {
+                let name = names.join("+");
+                grid.collapse(&names, name.clone())?;
+                format!("Collapsed {} into {name}.", names.join(", "))
+            }
+        };
+        self.log(&mut vec![line]);
+        self.autosave_tick();
+        Ok(())
+    }
+
+    /// Highlights and logs a spanning tree from `root`, or clears the highlight
+    /// if `root` is empty.
+    fn show_spanning_tree(&mut self, root: &str) -> Result<()> {
+        let grid = &mut self.node_display.grid;
+        if root.is_empty() {
+            grid.highlighted.clear();
+            return Ok(());
+        }
+        if !grid.node_exists(root) {
+            Err(anyhow!("No node named {root:?}."))?;
+        }
+        let edges = grid.spanning_tree(root);
+        let mut lines = vec![format!(
+            "Spanning tree from {root} reaches {} of {} nodes:",
+            edges.len() + 1,
+            grid.nodes.len()
+        )];
+        lines.extend(edges.iter().map(|(from, to)| format!("    {from} -> {to}")));
+        grid.highlighted = edges.into_iter().collect();
+        self.sidebar.log();
+        self.log(&mut lines);
+        Ok(())
+    }
+
+    fn generate_example(&mut self, size: usize) -> Result<()> {
+        let algorithm = SelectedAlgorithm::from_repr(self.sidebar.selector_scroll_state)
+            .ok_or_else(|| anyhow!("Parsing scroll state {} to Algorithm failed.", 0))?;
+        let mut rng = self.command_rng(None);
+        self.node_display.grid = NodeGrid::generate_for(algorithm, size, &mut rng)?;
+        self.log(&mut vec![format!(
+            "Generated a {} of {size} nodes for {algorithm} with seed {}.",
+            algorithm.example_topology(),
+            self.seed.unwrap_or_default()
+        )]);
+        self.autosave_tick();
+        Ok(())
+    }
+
+    fn generate_lattice(&mut self, rows: usize, cols: usize) -> Result<()> {
+        self.node_display.grid = NodeGrid::make_lattice(rows, cols)?;
+        self.log(&mut vec![format!("Generated a {rows}x{cols} lattice.")]);
+        self.autosave_tick();
+        Ok(())
+    }
+
+    /// Clicking a node picks it. Dragging from one node to another picks the
+    /// first, and opens the connect popup towards the second.
+    fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> Result<()> {
+        let position = Position::new(mouse_event.column, mouse_event.row);
+        if mouse_event.kind == MouseEventKind::Down(MouseButton::Left) {
+            // Clicking a node name in the log jumps to the node.
+            if let Some(word) = self.log_word_at(position) {
+                if self.node_display.grid.node_exists(&word) {
+                    return self.jump_to_node(&word);
+                }
+                return Ok(());
+            }
+        }
+        let node = self
+            .node_display
+            .node_at(position, self.grid_area(), self.compact);
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.drag_from = node;
+                self.dragged = false;
+            }
+            MouseEventKind::Drag(MouseButton::Left) => self.dragged = true,
+            MouseEventKind::Up(MouseButton::Left) => {
+                let Some(from) = self.drag_from.take() else {
+                    return Ok(());
+                };
+                match node {
+                    Some(to) if to == from && !self.dragged => {
+                        self.pick_node(from)?;
+                        self.state = AppState::Selection;
+                    }
+                    None => self.log(&mut vec![format!(
+                        "Released on empty space, {from} is not connected."
+                    )]),
+                    Some(to) if to == from => self.log(&mut vec![format!(
+                        "Dragged {from} onto itself, nodes can't connect to themselves."
+                    )]),
+                    Some(to) => {
+                        self.pick_node(from)?;
+                        self.drop_target = Some(to);
+                        self.open_popup(PopupState::Connect);
+                        self.drop_target = None;
+                    }
+                }
+            }
+            _ => {}
+        }
         Ok(())
     }
 
+    /// Dragging moves the selection along with the mouse, a cell at a time.
+    fn handle_selection_mouse_event(&mut self, mouse_event: MouseEvent) {
+        let position = Position::new(mouse_event.column, mouse_event.row);
+        let location = self
+            .node_display
+            .location_at(position, self.grid_area(), self.compact);
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => self.drag_location = location,
+            MouseEventKind::Drag(MouseButton::Left) => {
+                let (Some(from), Some(to)) = (self.drag_location, location) else {
+                    return;
+                };
+                if from != to {
+                    let step = |from: u16, to: u16| (to as i32 - from as i32).clamp(-128, 127);
+                    self.move_node(step(from.x, to.x) as i8, step(from.y, to.y) as i8);
+                    self.drag_location = Some(to);
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => self.drag_location = None,
+            _ => {}
+        }
+    }
+
     fn handle_selection_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
         match key_event.code {
             // In ratatui, down is positive
@@ -333,13 +1254,42 @@ impl App<'_> {
             // and right is positive
             KeyCode::Right => self.move_node(1, 0),
             KeyCode::Left => self.move_node(-1, 0),
+            // and the diagonals as laid out on a numpad.
+            KeyCode::Home => self.move_node(-1, -1),
+            KeyCode::PageUp => self.move_node(1, -1),
+            KeyCode::End => self.move_node(-1, 1),
+            KeyCode::PageDown => self.move_node(1, 1),
 
             KeyCode::Char('e') => {
                 self.open_popup(PopupState::Edit);
             }
+            KeyCode::Char('W') => {
+                self.open_popup(PopupState::Weights);
+            }
+            KeyCode::Char('i') => {
+                self.open_popup(PopupState::Inspect);
+            }
             KeyCode::Char('c') => {
                 self.open_popup(PopupState::Connect);
             }
+            KeyCode::Char('d') => {
+                self.open_popup(PopupState::Disconnect);
+            }
+            KeyCode::Char('a') => {
+                self.open_popup(PopupState::Note);
+            }
+            KeyCode::Char('n') => {
+                self.open_popup(PopupState::Rename);
+            }
+            KeyCode::Char('I') => self.toggle_included(),
+            KeyCode::Char('r') => self.toggle_reachable(),
+            KeyCode::Char('p') => self.toggle_pinned(),
+            KeyCode::Char('C') => self.connect_nearest(),
+            KeyCode::Char('w') => self.cycle_connection(),
+            KeyCode::Char('+') => self.adjust_weight(WEIGHT_STEP),
+            KeyCode::Char('-') => self.adjust_weight(-WEIGHT_STEP),
+            KeyCode::Char('x') => self.clear_connections(false),
+            KeyCode::Char('X') => self.clear_connections(true),
             KeyCode::Backspace | KeyCode::Delete => {
                 self.delete_selection();
                 self.state_default()
@@ -388,69 +1338,427 @@ impl App<'_> {
     }
 
     fn save_textarea(&mut self) -> Result<()> {
-        let mut enter_func = |app: &mut App| {
-            let path: PathBuf = app.textarea.lines()[0].parse()?;
-            app.save_grid(&path)?;
-            app.set_latest_location(path);
-            app.state_default();
-            Ok(())
+        match event::read()? {
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                match key_event.code {
+                    KeyCode::Esc => self.state_default(),
+                    KeyCode::Enter => {
+                        self.confirm_save(key_event.modifiers.contains(KeyModifiers::ALT))
+                    }
+                    _ => {
+                        self.textarea.input(key_event);
+                    }
+                }
+            }
+            _ => {}
         };
-        self.confirm_cancel_textarea(&mut enter_func)
+        Ok(())
+    }
+
+    /// Saves the grid to the path in the popup, or logs why it could not and
+    /// keeps the popup open to correct the path.
+    fn confirm_save(&mut self, anonymize: bool) {
+        let path = PathBuf::from(&self.textarea.lines()[0]);
+        match self.save_grid(&path, anonymize) {
+            Ok(()) => {
+                self.set_latest_location(path);
+                self.state_default();
+            }
+            Err(error) => self.log(&mut vec![format!(
+                "Could not save {}: {error}",
+                path.display()
+            )]),
+        }
     }
 
-    fn save_grid(&self, path: &PathBuf) -> Result<()> {
+    /// Writes the grid and the log to `path`, or only the grid with every
+    /// node name replaced by `n0..nk` when anonymizing.
+    fn save_grid(&self, path: &PathBuf, anonymize: bool) -> Result<()> {
         let file = fs::OpenOptions::new()
             .create(true)
             .truncate(true)
             .write(true)
             .open(path)?;
         let mut writer = io::BufWriter::new(file);
-        serde_json::to_writer_pretty(&mut writer, &self.node_display.grid)?;
+        let saved = match anonymize {
+            true => GridFile {
+                grid: self.node_display.grid.anonymized(),
+                log: vec![],
+            },
+            false => GridFile {
+                grid: self.node_display.grid.clone(),
+                log: self.sidebar.log.clone(),
+            },
+        };
+        serde_json::to_writer_pretty(&mut writer, &saved)?;
         writer.flush()?;
         Ok(())
     }
 
     fn load_textarea(&mut self) -> Result<()> {
+        match event::read()? {
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                match key_event.code {
+                    KeyCode::Esc => self.state_default(),
+                    KeyCode::Enter => {
+                        self.confirm_load(key_event.modifiers.contains(KeyModifiers::ALT))
+                    }
+                    _ => {
+                        self.textarea.input(key_event);
+                    }
+                }
+            }
+            _ => {}
+        };
+        Ok(())
+    }
+
+    /// Loads the grid at the path in the popup, or replays the script there,
+    /// logging why it could not and keeping the popup open to correct the
+    /// path.
+    fn confirm_load(&mut self, replay: bool) {
+        let path = PathBuf::from(&self.textarea.lines()[0]);
+        let loaded = match replay {
+            true => self.replay_script(&path),
+            false => self.load_grid(&path),
+        };
+        match loaded {
+            Ok(()) => {
+                // Loading a grid goes on to placing its floating nodes.
+                if replay {
+                    self.state_default();
+                }
+                self.set_latest_location(path);
+            }
+            Err(error) => self.log(&mut vec![format!(
+                "Could not load {}: {error}",
+                path.display()
+            )]),
+        }
+    }
+
+    fn autosave_textarea(&mut self) -> Result<()> {
         let mut enter_func = |app: &mut App| {
-            let path: PathBuf = app.textarea.lines()[0].parse()?;
-            app.load_grid(&path)?;
-            app.set_latest_location(path);
-            app.state_default();
+            if let Ok(every) = app.textarea.lines()[0].trim().parse::<usize>() {
+                app.autosave_every = (every > 0).then_some(every);
+                app.unsaved_changes = 0;
+                app.state_default();
+            }
             Ok(())
         };
         self.confirm_cancel_textarea(&mut enter_func)
     }
 
-    fn dump_textarea(&mut self) -> Result<()> {
+    fn message_limit_textarea(&mut self) -> Result<()> {
         let mut enter_func = |app: &mut App| {
-            let path: PathBuf = app.textarea.lines()[0].parse()?;
-            let file = fs::OpenOptions::new()
-                .create(true)
-                .truncate(true)
-                .write(true)
-                .open(path)?;
-            let mut writer = io::BufWriter::new(file);
-            writer.write_all(
-                &app.sidebar
-                    .log
-                    .clone()
-                    .join("\n")
-                    .bytes()
-                    .collect::<Vec<u8>>(),
-            )?;
-            writer.flush()?;
-            app.state_default();
+            if let Ok(limit) = app.textarea.lines()[0].trim().parse::<usize>() {
+                app.message_limit = (limit > 0).then_some(limit);
+                app.state_default();
+            }
             Ok(())
         };
         self.confirm_cancel_textarea(&mut enter_func)
     }
 
-    fn load_grid(&mut self, path: &PathBuf) -> Result<()> {
-        let file = fs::OpenOptions::new().read(true).open(path)?;
-        let reader = io::BufReader::new(file);
-        self.node_display.grid = serde_json::from_reader(reader)?;
-
-        Ok(())
+    fn traffic_textarea(&mut self) -> Result<()> {
+        let mut enter_func = |app: &mut App| {
+            match app.textarea.lines()[0].trim().parse::<usize>() {
+                Ok(intensity) if intensity <= MAX_TRAFFIC => {
+                    app.traffic = (intensity > 0).then_some(intensity);
+                    app.state_default();
+                }
+                Ok(_) => app.log(&mut vec![format!(
+                    "Traffic is at most {MAX_TRAFFIC} messages per node."
+                )]),
+                Err(_) => (),
+            }
+            Ok(())
+        };
+        self.confirm_cancel_textarea(&mut enter_func)
+    }
+
+    fn run_seed_textarea(&mut self) -> Result<()> {
+        let mut enter_func = |app: &mut App| {
+            let input = app.textarea.lines()[0].clone();
+            match app.set_run_seed(&input) {
+                Ok(()) => app.state_default(),
+                Err(error) => app.log(&mut vec![error.to_string()]),
+            }
+            Ok(())
+        };
+        self.confirm_cancel_textarea(&mut enter_func)
+    }
+
+    fn restore_textarea(&mut self) -> Result<()> {
+        let mut enter_func = |app: &mut App| {
+            app.confirm_restore();
+            Ok(())
+        };
+        self.confirm_cancel_textarea(&mut enter_func)
+    }
+
+    /// Loads the newer backup, or logs why it could not, leaving the popup
+    /// open to ignore it instead.
+    fn confirm_restore(&mut self) {
+        let path = self.backup_path();
+        let line = match self.load_grid(&path) {
+            Ok(()) => format!("Restored backup {}.", path.display()),
+            Err(error) => format!("Could not restore backup {}: {error}", path.display()),
+        };
+        self.log(&mut vec![line]);
+    }
+
+    fn diff_textarea(&mut self) -> Result<()> {
+        let mut enter_func = |app: &mut App| {
+            app.confirm_diff();
+            Ok(())
+        };
+        self.confirm_cancel_textarea(&mut enter_func)
+    }
+
+    /// Logs the changes to the grid at the path in the popup, or why it could
+    /// not be read and keeps the popup open to correct the path.
+    fn confirm_diff(&mut self) {
+        let path = PathBuf::from(&self.textarea.lines()[0]);
+        match self.diff_grid(&path) {
+            Ok(()) => self.state_default(),
+            Err(error) => self.log(&mut vec![format!(
+                "Could not compare with {}: {error}",
+                path.display()
+            )]),
+        }
+    }
+
+    fn import_adjacency_textarea(&mut self) -> Result<()> {
+        let mut enter_func = |app: &mut App| {
+            app.confirm_import_adjacency();
+            Ok(())
+        };
+        self.confirm_cancel_textarea(&mut enter_func)
+    }
+
+    /// Imports the adjacency list at the path in the popup, or logs why it
+    /// could not and keeps the popup open to correct the path.
+    fn confirm_import_adjacency(&mut self) {
+        let path = PathBuf::from(&self.textarea.lines()[0]);
+        match self.import_adjacency(&path) {
+            Ok(()) => self.state_default(),
+            Err(error) => self.log(&mut vec![format!(
+                "Could not import {}: {error}",
+                path.display()
+            )]),
+        }
+    }
+
+    fn save_session_textarea(&mut self) -> Result<()> {
+        let mut enter_func = |app: &mut App| {
+            app.confirm_save_session();
+            Ok(())
+        };
+        self.confirm_cancel_textarea(&mut enter_func)
+    }
+
+    /// Saves the session to the path in the popup, or logs why it could not
+    /// and keeps the popup open to correct the path.
+    fn confirm_save_session(&mut self) {
+        let path = PathBuf::from(&self.textarea.lines()[0]);
+        match self.save_session(&path) {
+            Ok(()) => {
+                self.set_latest_location(path);
+                self.state_default();
+            }
+            Err(error) => self.log(&mut vec![format!("Could not save session: {error}")]),
+        }
+    }
+
+    fn load_session_textarea(&mut self) -> Result<()> {
+        let mut enter_func = |app: &mut App| {
+            let path: PathBuf = app.textarea.lines()[0].parse()?;
+            match app.load_session(&path) {
+                Ok(()) => {
+                    app.set_latest_location(path);
+                    app.resume_placement();
+                }
+                Err(error) => app.log(&mut vec![format!("Could not resume session: {error}")]),
+            }
+            Ok(())
+        };
+        self.confirm_cancel_textarea(&mut enter_func)
+    }
+
+    fn script_textarea(&mut self) -> Result<()> {
+        let mut enter_func = |app: &mut App| {
+            app.confirm_export_script();
+            Ok(())
+        };
+        self.confirm_cancel_textarea(&mut enter_func)
+    }
+
+    /// Exports the recorded script to the path in the popup, or logs why it
+    /// could not and keeps the popup open to correct the path.
+    fn confirm_export_script(&mut self) {
+        let path = PathBuf::from(&self.textarea.lines()[0]);
+        match self.export_script(&path) {
+            Ok(()) => {
+                self.set_latest_location(path);
+                self.state_default();
+            }
+            Err(error) => self.log(&mut vec![format!("Could not export script: {error}")]),
+        }
+    }
+
+    fn dump_textarea(&mut self) -> Result<()> {
+        match event::read()? {
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                match key_event.code {
+                    KeyCode::Esc => self.state_default(),
+                    KeyCode::Enter => {
+                        self.confirm_dump(key_event.modifiers.contains(KeyModifiers::ALT))
+                    }
+                    _ => {
+                        self.textarea.input(key_event);
+                    }
+                }
+            }
+            _ => {}
+        };
+        Ok(())
+    }
+
+    /// Writes the trace, or else the log, to the path in the popup, or logs
+    /// why it could not and keeps the popup open to correct the path.
+    fn confirm_dump(&mut self, trace: bool) {
+        let path = PathBuf::from(&self.textarea.lines()[0]);
+        let dumped = match trace {
+            true => self.dump_trace(&path),
+            false => self.dump_log(&path),
+        };
+        match dumped {
+            Ok(()) => self.state_default(),
+            Err(error) => self.log(&mut vec![format!(
+                "Could not write {}: {error}",
+                path.display()
+            )]),
+        }
+    }
+
+    fn dump_log(&self, path: &PathBuf) -> Result<()> {
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(path)?;
+        let mut writer = io::BufWriter::new(file);
+        writer.write_all(
+            &self
+                .sidebar
+                .log
+                .clone()
+                .join("\n")
+                .bytes()
+                .collect::<Vec<u8>>(),
+        )?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes the message trace of the last run as JSON.
+    fn dump_trace(&self, path: &PathBuf) -> Result<()> {
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(path)?;
+        let mut writer = io::BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, &self.node_display.grid.trace)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn load_grid(&mut self, path: &PathBuf) -> Result<()> {
+        let file = fs::OpenOptions::new().read(true).open(path)?;
+        let reader = io::BufReader::new(file);
+        let GridFile { mut grid, log } = serde_json::from_reader(reader)?;
+        if !log.is_empty() {
+            self.sidebar.log = log;
+        }
+        grid.resolve_duplicates(self.duplicates, &mut self.sidebar.log)?;
+        grid.drop_self_loops(&mut self.sidebar.log);
+        self.node_display.grid = grid;
+        if !self.zoom_pinned {
+            self.zoom_to_fit();
+        }
+        self.resume_placement();
+        Ok(())
+    }
+
+    /// Replaces the grid with the one listed in the adjacency list at `path`.
+    fn import_adjacency(&mut self, path: &PathBuf) -> Result<()> {
+        let text = fs::read_to_string(path)?;
+        let grid = NodeGrid::from_adjacency(&text)?;
+        let line = format!(
+            "Imported {} nodes from {}.",
+            grid.nodes.len(),
+            path.display()
+        );
+        self.node_display.grid = grid;
+        if !self.zoom_pinned {
+            self.zoom_to_fit();
+        }
+        self.log(&mut vec![line]);
+        self.autosave_tick();
+        Ok(())
+    }
+
+    /// Goes back to placing the floating nodes of a loaded grid, if it has
+    /// any, and otherwise to the default state.
+    fn resume_placement(&mut self) {
+        let floating: Vec<&str> = self
+            .node_display
+            .grid
+            .floating_nodes
+            .iter()
+            .map(|n| n.name.as_str())
+            .collect();
+        if floating.is_empty() {
+            self.state_default();
+            return;
+        }
+        let line = format!("Resuming placement of {}.", floating.join(", "));
+        self.log(&mut vec![line]);
+        self.state = AppState::Selection;
+    }
+
+    /// Starts on the grid at the path given on the command line.
+    fn load_argument(&mut self, argument: &str) -> Result<()> {
+        let path =
+            fs::canonicalize(argument).with_context(|| format!("Cannot open {argument}."))?;
+        self.load_grid(&path)
+            .with_context(|| format!("{} is not a valid grid.", path.display()))?;
+        self.set_latest_location(path);
+        Ok(())
+    }
+
+    /// Picks the zoom at which the whole grid fits, and scrolls back to the
+    /// top left.
+    fn zoom_to_fit(&mut self) {
+        self.compact = self.node_display.grid.needs_compact(self.grid_area());
+        self.node_display.offset = (0, 0);
+    }
+
+    /// Logs what changed going from the current grid to the one saved at `path`.
+    fn diff_grid(&mut self, path: &PathBuf) -> Result<()> {
+        let file = fs::OpenOptions::new().read(true).open(path)?;
+        let reader = io::BufReader::new(file);
+        let other: NodeGrid = serde_json::from_reader(reader)?;
+        let mut lines = vec![format!(
+            "Changes from the current grid to {}:",
+            path.display()
+        )];
+        lines.extend(self.node_display.grid.diff(&other).lines());
+        self.sidebar.log();
+        self.log(&mut lines);
+        Ok(())
     }
 
     fn set_latest_location(&mut self, mut path: PathBuf) {
@@ -475,6 +1783,101 @@ impl App<'_> {
         self.confirm_cancel_textarea(&mut enter_func)
     }
 
+    fn generate_textarea(&mut self) -> Result<()> {
+        let mut enter_func = |app: &mut App| {
+            let input = app.textarea.lines()[0].trim().to_string();
+            let bad_input = || anyhow!("Bad input {input:?}, expected `size` or `rows x cols`.");
+            let generated = match input.split_once('x') {
+                Some((rows, cols)) => match (rows.trim().parse(), cols.trim().parse()) {
+                    (Ok(rows), Ok(cols)) => app.generate_lattice(rows, cols),
+                    _ => Err(bad_input()),
+                },
+                None => input
+                    .parse::<usize>()
+                    .map_err(|_| bad_input())
+                    .and_then(|size| app.generate_example(size)),
+            };
+            match generated {
+                Ok(()) => app.state_default(),
+                Err(error) => app.log(&mut vec![error.to_string()]),
+            }
+            Ok(())
+        };
+        self.confirm_cancel_textarea(&mut enter_func)
+    }
+
+    fn go_to_textarea(&mut self) -> Result<()> {
+        let mut enter_func = |app: &mut App| {
+            let input = app.textarea.lines()[0].clone();
+            match app.go_to(&input) {
+                Ok(()) => app.state_default(),
+                Err(error) => app.log(&mut vec![error.to_string()]),
+            }
+            Ok(())
+        };
+        self.confirm_cancel_textarea(&mut enter_func)
+    }
+
+    fn states_textarea(&mut self) -> Result<()> {
+        let mut enter_func = |app: &mut App| {
+            let input = app.textarea.lines()[0].clone();
+            match app.seed_states(&input) {
+                Ok(()) => app.state_default(),
+                Err(error) => app.log(&mut vec![error.to_string()]),
+            }
+            Ok(())
+        };
+        self.confirm_cancel_textarea(&mut enter_func)
+    }
+
+    fn partition_textarea(&mut self) -> Result<()> {
+        let mut enter_func = |app: &mut App| {
+            let input = app.textarea.lines()[0].clone();
+            match app.partition(&input) {
+                Ok(()) => app.state_default(),
+                Err(error) => app.log(&mut vec![error.to_string()]),
+            }
+            Ok(())
+        };
+        self.confirm_cancel_textarea(&mut enter_func)
+    }
+
+    fn elections_textarea(&mut self) -> Result<()> {
+        let mut enter_func = |app: &mut App| {
+            let input = app.textarea.lines()[0].clone();
+            match app.compare_elections(&input) {
+                Ok(()) => app.state_default(),
+                Err(error) => app.log(&mut vec![error.to_string()]),
+            }
+            Ok(())
+        };
+        self.confirm_cancel_textarea(&mut enter_func)
+    }
+
+    fn collapse_textarea(&mut self) -> Result<()> {
+        let mut enter_func = |app: &mut App| {
+            let input = app.textarea.lines()[0].clone();
+            match app.collapse_nodes(&input) {
+                Ok(()) => app.state_default(),
+                Err(error) => app.log(&mut vec![error.to_string()]),
+            }
+            Ok(())
+        };
+        self.confirm_cancel_textarea(&mut enter_func)
+    }
+
+    fn spanning_tree_textarea(&mut self) -> Result<()> {
+        let mut enter_func = |app: &mut App| {
+            let root = app.textarea.lines()[0].trim().to_string();
+            match app.show_spanning_tree(&root) {
+                Ok(()) => app.state_default(),
+                Err(error) => app.log(&mut vec![error.to_string()]),
+            }
+            Ok(())
+        };
+        self.confirm_cancel_textarea(&mut enter_func)
+    }
+
     fn pick_textarea(&mut self) -> Result<()> {
         let mut enter_func = |app: &mut App| {
             let name = app.textarea.lines()[0].clone();
@@ -529,26 +1932,202 @@ impl App<'_> {
         Ok(())
     }
 
+    fn weights_textarea(&mut self) -> Result<()> {
+        match event::read()? {
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                match key_event.code {
+                    KeyCode::Esc => self.state = AppState::Selection,
+                    KeyCode::Char('s') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                        let lines = self.textarea.lines().to_vec();
+                        match self.apply_weights(&lines) {
+                            Ok(()) => self.state = AppState::Selection,
+                            Err(error) => self.log(&mut vec![error.to_string()]),
+                        }
+                    }
+                    _ => {
+                        self.textarea.input(key_event);
+                    }
+                }
+            }
+            _ => {}
+        };
+        Ok(())
+    }
+
+    fn inspect_textarea(&mut self) -> Result<()> {
+        match event::read()? {
+            Event::Key(key_event)
+                if key_event.kind == KeyEventKind::Press && key_event.code == KeyCode::Esc =>
+            {
+                self.close_inspection()
+            }
+            _ => {}
+        };
+        Ok(())
+    }
+
+    fn inspect_step_textarea(&mut self) -> Result<()> {
+        match event::read()? {
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                match key_event.code {
+                    KeyCode::Esc => self.state = AppState::Stepping,
+                    KeyCode::Enter => {
+                        let name = self.textarea.lines()[0].trim().to_string();
+                        if let Err(error) = self.inspect_while_stepping(&name) {
+                            self.log(&mut vec![error.to_string()]);
+                        }
+                    }
+                    _ => {
+                        self.textarea.input(key_event);
+                    }
+                }
+            }
+            _ => {}
+        };
+        Ok(())
+    }
+
+    /// Opens the inspection of the node `name` in the middle of a run, where
+    /// no node is picked.
+    fn inspect_while_stepping(&mut self, name: &str) -> Result<()> {
+        self.node_display.grid.inspect_node(name)?;
+        self.inspected = Some(name.to_string());
+        self.open_popup(PopupState::Inspect);
+        Ok(())
+    }
+
+    /// Goes back to the run being stepped through, or else to the selection.
+    fn close_inspection(&mut self) {
+        self.state = match self.inspected.take() {
+            Some(_) => AppState::Stepping,
+            None => AppState::Selection,
+        };
+    }
+
+    fn pseudocode_textarea(&mut self) -> Result<()> {
+        match event::read()? {
+            Event::Key(key_event)
+                if key_event.kind == KeyEventKind::Press && key_event.code == KeyCode::Esc =>
+            {
+                self.state_default()
+            }
+            _ => {}
+        };
+        Ok(())
+    }
+
+    fn distances_textarea(&mut self) -> Result<()> {
+        match event::read()? {
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                match key_event.code {
+                    KeyCode::Esc => self.state_default(),
+                    KeyCode::Enter => self.log_textarea(),
+                    _ => {}
+                }
+            }
+            _ => {}
+        };
+        Ok(())
+    }
+
+    fn lint_textarea(&mut self) -> Result<()> {
+        match event::read()? {
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                match key_event.code {
+                    KeyCode::Esc => self.state_default(),
+                    KeyCode::Enter => self.log_textarea(),
+                    KeyCode::Char(key) => {
+                        if let Some(fix) = Fix::from_key(key) {
+                            self.apply_fix(fix);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        };
+        Ok(())
+    }
+
     fn connect_textarea(&mut self) -> Result<()> {
         match event::read()? {
             Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
                 match key_event.code {
                     KeyCode::Esc => self.state = AppState::Selection,
                     KeyCode::Enter => {
-                        let input = self.textarea.lines()[0].split_once(" ").ok_or(anyhow!(
-                            "Bad connection for connection: {:?}",
-                            self.textarea.lines()[0]
-                        ))?;
-                        let connection =
-                            Connection::new(input.1.to_string(), input.0.parse::<f64>()?);
-                        if self.connect_selection(&connection).is_ok() {
-                            if key_event.modifiers.contains(KeyModifiers::ALT) {
-                                if self.connect_other(&connection).is_ok() {
-                                    self.state = AppState::Selection;
-                                }
-                            } else {
-                                self.state = AppState::Selection;
-                            }
+                        let undirected = key_event.modifiers.contains(KeyModifiers::ALT);
+                        let result = parse_connect_input(&self.textarea.lines()[0])
+                            .and_then(|c| self.connect_selection(&c, undirected));
+                        match result {
+                            Ok(()) => self.state = AppState::Selection,
+                            Err(error) => self.log(&mut vec![error.to_string()]),
+                        }
+                    }
+                    _ => {
+                        self.textarea.input(key_event);
+                    }
+                }
+            }
+            _ => {}
+        };
+        Ok(())
+    }
+
+    fn disconnect_textarea(&mut self) -> Result<()> {
+        match event::read()? {
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                match key_event.code {
+                    KeyCode::Esc => self.state = AppState::Selection,
+                    KeyCode::Enter => {
+                        let other = self.textarea.lines()[0].trim().to_string();
+                        let undirected = key_event.modifiers.contains(KeyModifiers::ALT);
+                        match self.disconnect_selection(&other, undirected) {
+                            Ok(()) => self.state = AppState::Selection,
+                            Err(error) => self.log(&mut vec![error.to_string()]),
+                        }
+                    }
+                    _ => {
+                        self.textarea.input(key_event);
+                    }
+                }
+            }
+            _ => {}
+        };
+        Ok(())
+    }
+
+    fn rename_textarea(&mut self) -> Result<()> {
+        match event::read()? {
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                match key_event.code {
+                    KeyCode::Esc => self.state = AppState::Selection,
+                    KeyCode::Enter => {
+                        let name = self.textarea.lines()[0].clone();
+                        match self.rename_selection(&name) {
+                            Ok(()) => self.state = AppState::Selection,
+                            Err(error) => self.log(&mut vec![error.to_string()]),
+                        }
+                    }
+                    _ => {
+                        self.textarea.input(key_event);
+                    }
+                }
+            }
+            _ => {}
+        };
+        Ok(())
+    }
+
+    fn note_textarea(&mut self) -> Result<()> {
+        match event::read()? {
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                match key_event.code {
+                    KeyCode::Esc => self.state = AppState::Selection,
+                    KeyCode::Enter => {
+                        let note = self.textarea.lines()[0].trim().to_string();
+                        if self.node_display.grid.annotate(note).is_ok() {
+                            self.autosave_tick();
+                            self.state = AppState::Selection;
                         }
                     }
                     _ => {
@@ -566,6 +2145,18 @@ impl App<'_> {
     }
 
     fn open_popup(&mut self, state: PopupState) {
+        if state == PopupState::Edit
+            && let Err(error) = self.get_node_serialized()
+        {
+            self.log(&mut vec![error.to_string()]);
+            return;
+        }
+        if state == PopupState::Weights
+            && let Err(error) = self.node_display.grid.connection_weights()
+        {
+            self.log(&mut vec![error.to_string()]);
+            return;
+        }
         self.textarea = TextArea::from(state.content_default(self).split('\n'));
         self.textarea.set_block(
             Block::bordered()
@@ -586,39 +2177,286 @@ impl App<'_> {
     }
 
     fn add_node(&mut self, name: String) -> Result<()> {
-        self.node_display.grid.new_node(name)
+        self.node_display.grid.new_node(name.clone())?;
+        self.record(Action::NewNode(name));
+        Ok(())
     }
 
     fn move_node(&mut self, x: i8, y: i8) {
-        self.node_display.grid.move_node(x, y)
+        self.node_display.grid.move_node(x, y);
+        self.record(Action::Move { x, y });
     }
 
     fn pick_node(&mut self, name: String) -> Result<()> {
-        self.node_display.grid.pick(name)
+        self.node_display.grid.pick(name.clone())?;
+        self.record(Action::Pick(name));
+        Ok(())
     }
 
     fn commit_selection(&mut self) -> Result<()> {
-        self.node_display.grid.commit()
+        self.node_display.grid.commit()?;
+        self.record(Action::Commit);
+        Ok(())
     }
 
     fn delete_selection(&mut self) {
-        self.node_display.grid.delete()
+        self.node_display.grid.delete();
+        self.record(Action::Delete);
+    }
+
+    /// Brings back what the last delete removed, selected so it can be moved
+    /// out of the way if its spot was taken since.
+    fn restore_deleted(&mut self) {
+        match self.node_display.grid.restore_deleted() {
+            Ok(names) => {
+                self.log(&mut vec![format!("Restored {}.", names.join(", "))]);
+                self.autosave_tick();
+                self.state = AppState::Selection;
+            }
+            Err(error) => self.log(&mut vec![error.to_string()]),
+        }
     }
 
     fn overwrite_selection(&mut self, new_node: String) -> Result<()> {
-        self.node_display.grid.overwrite(new_node)
+        self.node_display.grid.overwrite(new_node)?;
+        self.autosave_tick();
+        Ok(())
+    }
+
+    fn get_node_serialized(&self) -> Result<String> {
+        self.node_display.grid.get_floating_serialized()
+    }
+
+    /// Name of the selected node, if exactly one is selected.
+    fn selection_name(&self) -> Option<String> {
+        match &self.node_display.grid.floating_nodes[..] {
+            [node] => Some(node.name.clone()),
+            _ => None,
+        }
+    }
+
+    fn rename_selection(&mut self, name: &str) -> Result<()> {
+        let old = self
+            .selection_name()
+            .ok_or_else(|| anyhow!("Select a single node to rename."))?;
+        self.node_display.grid.rename_node(&old, name)?;
+        self.log(&mut vec![format!("Renamed {old} to {}.", name.trim())]);
+        self.record(Action::Rename(name.trim().to_string()));
+        Ok(())
+    }
+
+    fn get_node_note(&self) -> String {
+        self.node_display
+            .grid
+            .get_floating_note()
+            .unwrap_or_default()
+    }
+
+    fn get_node_inspection(&self) -> String {
+        let grid = &self.node_display.grid;
+        match &self.inspected {
+            Some(name) => grid.inspect_node(name),
+            None => grid.inspect_floating(),
+        }
+        .unwrap_or_else(|e| e.to_string())
+    }
+
+    /// Pseudocode of the algorithm highlighted in the selector, noting when
+    /// it can't be run step by step.
+    fn get_pseudocode(&self) -> String {
+        let algorithm = self
+            .highlighted_algorithm()
+            .unwrap_or(SelectedAlgorithm::ChandyLamport);
+        let mut text = format!("{algorithm}\n\n{}", algorithm.pseudocode());
+        if !algorithm.steppable() {
+            text.push_str("\n\n<Space> can't run it step by step.");
+        }
+        text
+    }
+
+    /// Issues with the grid as input for the highlighted algorithm.
+    fn get_lint_report(&self) -> String {
+        let algorithm = self
+            .highlighted_algorithm()
+            .unwrap_or(SelectedAlgorithm::ChandyLamport);
+        self.node_display.grid.lint_report(algorithm).join("\n")
+    }
+
+    /// Applies a fix from the lint popup and logs it, then checks the grid
+    /// again. Making a ring which drops connections waits for the key to be
+    /// pressed a second time, as there is no undo.
+    fn apply_fix(&mut self, fix: Fix) {
+        let dropped = match fix {
+            Fix::MakeRing => self.node_display.grid.ring_fix_drops(),
+            _ => 0,
+        };
+        if dropped > 0 && self.unconfirmed_fix != Some(fix) {
+            self.unconfirmed_fix = Some(fix);
+            self.log(&mut vec![format!(
+                "Making a ring drops {dropped} other connections, press <{}> again to go ahead.",
+                fix.key()
+            )]);
+            return;
+        }
+        self.unconfirmed_fix = None;
+        let line = match self.node_display.grid.apply_fix(fix) {
+            Ok(changed) => {
+                self.autosave_tick();
+                changed
+            }
+            Err(error) => error.to_string(),
+        };
+        self.log(&mut vec![line]);
+        self.open_popup(PopupState::Lint);
+    }
+
+    /// Shortest path distances between all nodes, or why there are none.
+    fn get_distance_matrix(&self) -> String {
+        match self.node_display.grid.distance_matrix() {
+            Ok(lines) => lines.join("\n"),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    fn connect_selection(&mut self, connection: &Connection, undirected: bool) -> Result<()> {
+        let changes = match undirected {
+            true => self.node_display.grid.connect_undirected(connection)?,
+            false => self.node_display.grid.connect(connection)?,
+        };
+        self.log(&mut changes.iter().map(|c| c.to_string()).collect());
+        self.record(Action::Connect {
+            connection: connection.clone(),
+            undirected,
+        });
+        Ok(())
+    }
+
+    /// Updates the connections of the picked node to the weights edited in the
+    /// weights popup. Nothing changes unless every line is valid.
+    fn apply_weights(&mut self, lines: &[String]) -> Result<()> {
+        for connection in self.node_display.grid.changed_weights(lines)? {
+            self.connect_selection(&connection, false)?;
+        }
+        Ok(())
+    }
+
+    fn disconnect_selection(&mut self, other: &str, undirected: bool) -> Result<()> {
+        let changes = match undirected {
+            true => self.node_display.grid.disconnect_undirected(other)?,
+            false => self.node_display.grid.disconnect(other)?,
+        };
+        self.log(&mut changes.iter().map(|c| c.to_string()).collect());
+        self.record(Action::Disconnect {
+            other: other.to_string(),
+            undirected,
+        });
+        Ok(())
+    }
+
+    /// The node the first picked node connects to first, if any.
+    fn first_neighbour(&self) -> Option<String> {
+        let node = self.node_display.grid.floating_nodes.first()?;
+        node.connections.first().map(|c| c.other.clone())
+    }
+
+    fn cycle_connection(&mut self) {
+        match self.node_display.grid.cycle_connection() {
+            Ok(other) => self.log(&mut vec![format!("Adjusting the connection to {other}.")]),
+            Err(error) => self.log(&mut vec![error.to_string()]),
+        }
+    }
+
+    /// Changes the weight of the current connection of the picked node by
+    /// `step`.
+    fn adjust_weight(&mut self, step: f64) {
+        let result = self
+            .node_display
+            .grid
+            .adjusted_connection(step)
+            .and_then(|connection| self.connect_selection(&connection, false));
+        if let Err(error) = result {
+            self.log(&mut vec![error.to_string()]);
+        }
     }
 
-    fn get_node_serialized(&self) -> String {
-        self.node_display.grid.get_floating_serialized().unwrap()
+    fn connect_nearest(&mut self) {
+        match self.node_display.grid.connect_nearest() {
+            Ok(changes) => {
+                self.log(&mut changes.iter().map(|c| c.to_string()).collect());
+                self.autosave_tick();
+            }
+            Err(error) => self.log(&mut vec![error.to_string()]),
+        }
     }
 
-    fn connect_selection(&mut self, connection: &Connection) -> Result<()> {
-        self.node_display.grid.connect(connection)
+    fn toggle_included(&mut self) {
+        let line = match self.node_display.grid.toggle_included() {
+            Ok(true) => "Selection included in algorithm runs.".to_string(),
+            Ok(false) => "Selection excluded from algorithm runs.".to_string(),
+            Err(error) => error.to_string(),
+        };
+        self.log(&mut vec![line]);
     }
 
-    fn connect_other(&mut self, connection: &Connection) -> Result<()> {
-        self.node_display.grid.connect_reverse(connection)
+    fn align_grid(&mut self) {
+        let line = match self.node_display.grid.align() {
+            Ok(moved) => {
+                self.autosave_tick();
+                format!("Aligned the grid, moving {moved} nodes.")
+            }
+            Err(error) => error.to_string(),
+        };
+        self.log(&mut vec![line]);
+    }
+
+    fn toggle_pinned(&mut self) {
+        let line = match self.node_display.grid.toggle_pinned() {
+            Ok(true) => {
+                self.autosave_tick();
+                "Selection pinned.".to_string()
+            }
+            Ok(false) => {
+                self.autosave_tick();
+                "Selection unpinned.".to_string()
+            }
+            Err(error) => error.to_string(),
+        };
+        self.log(&mut vec![line]);
+    }
+
+    /// Marks the nodes reachable from the selection, or clears the marks.
+    fn toggle_reachable(&mut self) {
+        let grid = &mut self.node_display.grid;
+        let line = if !grid.marked.is_empty() {
+            grid.marked.clear();
+            "Cleared reachable nodes.".to_string()
+        } else if let Some(root) = grid.floating_nodes.first().map(|n| n.name.clone()) {
+            grid.marked = grid.reachable(&root);
+            let total = grid.nodes.len() + grid.floating_nodes.len();
+            format!(
+                "{} of {total} nodes are reachable from {root}.",
+                grid.marked.len()
+            )
+        } else {
+            "Nothing selected.".to_string()
+        };
+        self.log(&mut vec![line]);
+    }
+
+    fn include_all(&mut self) {
+        self.node_display.grid.included.clear();
+        self.log(&mut vec!["Algorithms run on the whole grid.".to_string()]);
+    }
+
+    fn clear_connections(&mut self, reciprocal: bool) {
+        let line = match self.node_display.grid.clear_connections(reciprocal) {
+            Ok(removed) => {
+                self.autosave_tick();
+                format!("Removed {removed} connections.")
+            }
+            Err(error) => error.to_string(),
+        };
+        self.log(&mut vec![line]);
     }
 
     fn log_textarea(&mut self) {
@@ -630,9 +2468,151 @@ impl App<'_> {
         self.sidebar.log.append(input);
     }
 
+    /// Runs both snapshot algorithms and logs whether they agree.
+    fn compare_snapshots(&mut self) {
+        self.sidebar.log();
+        if !self.sidebar.log.is_empty() {
+            self.sidebar.log.push(String::new());
+        }
+        let start = self.sidebar.log.len();
+        self.configure_run();
+        let _ = self
+            .node_display
+            .grid
+            .compare_snapshots(&mut self.sidebar.log);
+        self.verbosity.apply(&mut self.sidebar.log, start);
+    }
+
+    fn toggle_verbosity(&mut self) {
+        self.verbosity = match self.verbosity {
+            Verbosity::Full => Verbosity::Milestones,
+            Verbosity::Milestones => Verbosity::Full,
+        };
+        let line = match self.verbosity {
+            Verbosity::Full => "Logging every message of a run.",
+            Verbosity::Milestones => "Logging only the milestones of a run.",
+        };
+        self.log(&mut vec![line.to_string()]);
+    }
+
+    /// Hands the settings for runs to the grid.
+    fn configure_run(&mut self) {
+        self.node_display.grid.frozen = self.frozen;
+        self.node_display.grid.delivery = self.delivery;
+        self.node_display.grid.message_limit = self.message_limit;
+        self.node_display.grid.traffic = self.traffic;
+        self.node_display.grid.seed = self.run_seed;
+        self.node_display.grid.weighted_initiator = self.weighted_initiator;
+    }
+
+    /// Seeds the runs to come with the number in `input`, or makes them random
+    /// again when it is empty.
+    fn set_run_seed(&mut self, input: &str) -> Result<()> {
+        let input = input.trim();
+        self.run_seed = match input.is_empty() {
+            true => None,
+            false => Some(
+                input
+                    .parse()
+                    .map_err(|_| anyhow!("Bad seed {input:?}, expected a number."))?,
+            ),
+        };
+        let line = match self.run_seed {
+            Some(seed) => {
+                self.seed = Some(seed);
+                format!("Seeding runs with {seed}, so they repeat exactly.")
+            }
+            None => "Runs make random choices again.".to_string(),
+        };
+        self.log(&mut vec![line]);
+        Ok(())
+    }
+
+    fn cycle_node_border(&mut self) {
+        self.node_border = match self.node_border {
+            NodeBorder::Solid => NodeBorder::Rounded,
+            NodeBorder::Rounded => NodeBorder::Thick,
+            NodeBorder::Thick => NodeBorder::Solid,
+        };
+    }
+
+    fn cycle_delivery(&mut self) {
+        self.delivery = match self.delivery {
+            None => Some(Delivery::FrontFifo),
+            Some(Delivery::FrontFifo) => Some(Delivery::RandomInsert),
+            Some(Delivery::RandomInsert) => Some(Delivery::LifoStack),
+            Some(Delivery::LifoStack) => Some(Delivery::PriorityByTimestamp),
+            Some(Delivery::PriorityByTimestamp) => None,
+        };
+        let line = match self.delivery {
+            Some(delivery) => format!("Runs deliver messages {delivery}."),
+            None => "Runs deliver messages through the channels of the algorithm.".to_string(),
+        };
+        self.log(&mut vec![line]);
+    }
+
+    fn cycle_duplicates(&mut self) {
+        self.duplicates = match self.duplicates {
+            Duplicates::Error => Duplicates::Rename,
+            Duplicates::Rename => Duplicates::Merge,
+            Duplicates::Merge => Duplicates::Error,
+        };
+        let line = match self.duplicates {
+            Duplicates::Error => "Grids with duplicate node names are refused.",
+            Duplicates::Rename => "Duplicate node names are given a suffix on load.",
+            Duplicates::Merge => "Nodes with duplicate names are merged on load.",
+        };
+        self.log(&mut vec![line.to_string()]);
+    }
+
+    fn toggle_frozen(&mut self) {
+        self.frozen = !self.frozen;
+        let line = match self.frozen {
+            true => "Snapshots run without random processing.",
+            false => "Snapshots run with random processing.",
+        };
+        self.log(&mut vec![line.to_string()]);
+    }
+
+    fn toggle_weighted_initiator(&mut self) {
+        self.weighted_initiator = !self.weighted_initiator;
+        let line = match self.weighted_initiator {
+            true => "Runs pick heavily connected initiators more often.",
+            false => "Runs pick any initiator as often.",
+        };
+        self.log(&mut vec![line.to_string()]);
+    }
+
+    fn log_tree_check(&mut self) {
+        let line = match self.node_display.grid.tree_check() {
+            Ok(()) => "Grid is a tree.".to_string(),
+            Err(reason) => format!("Grid is not a tree: {reason}."),
+        };
+        self.log(&mut vec![line]);
+    }
+
+    /// Logs whether the grid maps onto itself other than trivially, in which
+    /// case deterministic algorithms can't break the symmetry to pick a leader.
+    fn log_symmetry(&mut self) {
+        let line = match self.node_display.grid.nontrivial_automorphism() {
+            Ok(Some(mapping)) => format!(
+                "Grid is symmetric, mapping {} keeps every connection. Deterministic \
+                 algorithms can't tell these nodes apart.",
+                mapping
+                    .iter()
+                    .map(|(node, image)| format!("{node}->{image}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Ok(None) => "Grid has no symmetry, every node is set apart by structure.".to_string(),
+            Err(error) => error.to_string(),
+        };
+        self.log(&mut vec![line]);
+    }
+
     fn toggle_sidebar(&mut self) {
         if self.sidebar.width == 0 {
-            self.sidebar.width = 50
+            self.sidebar.width = SIDEBAR_DEFAULT_WIDTH
         }
         self.sidebar_state = match self.sidebar_state {
             SidebarState::Hidden => SidebarState::Shown,
@@ -640,6 +2620,18 @@ impl App<'_> {
         }
     }
 
+    fn widen_sidebar(&mut self) {
+        self.sidebar.width = (self.sidebar.width + SIDEBAR_WIDTH_STEP).min(SIDEBAR_MAX_WIDTH);
+    }
+
+    fn narrow_sidebar(&mut self) {
+        self.sidebar.width = self
+            .sidebar
+            .width
+            .saturating_sub(SIDEBAR_WIDTH_STEP)
+            .max(SIDEBAR_MIN_WIDTH);
+    }
+
     fn sidebar_scroll_down(&mut self) {
         match self.sidebar.shown_content {
             SidebarContent::Log => {
@@ -667,16 +2659,25 @@ impl App<'_> {
 
 impl Widget for &App<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let title = Line::from(vec![
+        let mut title = Line::from(vec![
             " Current state: ".into(),
             format!("{:?}", self.state).into(),
             " Sidebar: ".into(),
             format!("{:?}", self.sidebar_state).into(),
             " ".into(),
         ]);
+        if let Some(run) = &self.stepping {
+            title.push_span(format!("In transit: {} ", run.in_transit()));
+        }
         let instructions = self.get_instructions();
 
+        let channels = self
+            .highlighted_algorithm()
+            .filter(|_| self.show_channels)
+            .map(SelectedAlgorithm::channels);
+
         let block_style = Style::default();
+        buf.set_style(area, Style::default().bg(self.theme.background));
         let node_block = Block::bordered()
             .title(title.centered())
             .title_bottom(instructions.centered())
@@ -688,25 +2689,44 @@ impl Widget for &App<'_> {
                 self.node_display
                     .clone()
                     .block(node_block)
+                    .channels(channels)
+                    .long_edge_labels(self.long_edge_labels)
+                    .message_counts(self.message_counts)
+                    .edge_weights(self.edge_weights)
+                    .hide_connections(self.hide_connections)
+                    .compact(self.compact)
+                    .show_degree(self.show_degree)
+                    .show_clocks(self.show_clocks)
+                    .weighted_size(self.weighted_size)
+                    .node_border(self.node_border)
+                    .theme(self.theme)
                     .render(area, buf);
             }
             SidebarState::Shown => {
                 let sidebar_block = Block::bordered()
                     .border_style(block_style)
                     .border_set(border::THICK);
-                let layout = Layout::horizontal([
-                    Constraint::Min(0),
-                    Constraint::Percentage(self.sidebar.width),
-                ]);
-                let [node_area, sidebar_area] = layout.areas(area);
+                let [node_area, sidebar_area] = sidebar_split(area, self.sidebar.width);
 
                 self.node_display
                     .clone()
                     .block(node_block)
+                    .channels(channels)
+                    .long_edge_labels(self.long_edge_labels)
+                    .message_counts(self.message_counts)
+                    .edge_weights(self.edge_weights)
+                    .hide_connections(self.hide_connections)
+                    .compact(self.compact)
+                    .show_degree(self.show_degree)
+                    .show_clocks(self.show_clocks)
+                    .weighted_size(self.weighted_size)
+                    .node_border(self.node_border)
+                    .theme(self.theme)
                     .render(node_area, buf);
                 self.sidebar
                     .clone()
                     .block(sidebar_block)
+                    .theme(self.theme)
                     .render(sidebar_area, buf);
             }
         };
@@ -714,6 +2734,7 @@ impl Widget for &App<'_> {
         match self.state {
             AppState::Default => {}
             AppState::Selection => {}
+            AppState::Stepping => {}
             AppState::Popup(popup) => match popup.size() {
                 PopupSize::Small => {
                     let area = popup_area_small(area, 60, 3);
@@ -752,7 +2773,7 @@ impl Sidebar<'_> {
         let titles = SidebarContent::iter().map(SidebarContent::title);
         let highlight_style = Style::default()
             .reversed()
-            .fg(ratatui::style::Color::Cyan)
+            .fg(self.theme.sidebar_highlight)
             .bold();
         let selected_tab_index = self.shown_content as usize;
         Tabs::new(titles)
@@ -797,20 +2818,27 @@ impl<'a> Sidebar<'a> {
         self
     }
 
+    fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
     fn create_wrapped_lines(&mut self, max_width: u16) -> Vec<Line<'a>> {
         let mut output = vec![];
         for line in self.log.iter().flat_map(|s| s.split("\\n")) {
             let mut next_line = String::new();
             let mut length = 0;
             for grapheme in line.graphemes(true) {
-                if length == max_width {
+                // Wide glyphs take two columns, and move to the next line
+                // whole when only one is left.
+                let width = grapheme.width() as u16;
+                if length + width > max_width && !next_line.is_empty() {
                     output.push(Line::from(next_line));
                     next_line = String::new();
-                    next_line.clear();
                     length = 0
                 }
                 next_line.push_str(grapheme);
-                length += 1;
+                length += width;
             }
             output.push(Line::from(next_line));
         }
@@ -833,17 +2861,67 @@ impl Widget for Sidebar<'_> {
     }
 }
 
+/// Reads the `weight name` typed in the connect popup, naming the part which
+/// is wrong if it can't be read.
+fn parse_connect_input(line: &str) -> Result<Connection> {
+    let mut parts = line.split_whitespace();
+    let weight = parts.next().unwrap_or_default();
+    let weight = weight
+        .parse::<f64>()
+        .ok()
+        .filter(|w| w.is_finite())
+        .ok_or_else(|| anyhow!("Weight must be a number, got {weight:?}."))?;
+    let other = parts
+        .next()
+        .ok_or_else(|| anyhow!("Destination name required."))?;
+    if let Some(extra) = parts.next() {
+        Err(anyhow!("Unexpected {extra:?} after the destination name."))?;
+    }
+    Ok(Connection::new(other.to_string(), weight))
+}
+
 fn popup_area_small(area: Rect, percent_x: u16, length_y: u16) -> Rect {
-    let vertical = Layout::vertical([Constraint::Length(length_y)]).flex(Flex::Center);
-    let horizontal = Layout::horizontal([Constraint::Percentage(percent_x)]).flex(Flex::Center);
-    let [area] = vertical.areas(area);
-    let [area] = horizontal.areas(area);
-    area
+    let width = clamp_popup_length(
+        percentage(area.width, percent_x),
+        POPUP_MIN_WIDTH,
+        area.width,
+    );
+    let height = clamp_popup_length(length_y, length_y, area.height);
+    centered_area(area, width, height)
 }
 
 fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
-    let vertical = Layout::vertical([Constraint::Percentage(percent_y)]).flex(Flex::Center);
-    let horizontal = Layout::horizontal([Constraint::Percentage(percent_x)]).flex(Flex::Center);
+    let width = clamp_popup_length(
+        percentage(area.width, percent_x),
+        POPUP_MIN_WIDTH,
+        area.width,
+    );
+    let height = clamp_popup_length(
+        percentage(area.height, percent_y),
+        POPUP_MIN_HEIGHT,
+        area.height,
+    );
+    centered_area(area, width, height)
+}
+
+/// Splits `area` into the grid and a sidebar of `width` percent on the right.
+fn sidebar_split(area: Rect, width: u16) -> [Rect; 2] {
+    Layout::horizontal([Constraint::Min(0), Constraint::Percentage(width)]).areas(area)
+}
+
+/// Keeps a popup dimension between `min` and `POPUP_MAX_LENGTH`, but never larger
+/// than the `available` space.
+fn clamp_popup_length(length: u16, min: u16, available: u16) -> u16 {
+    length.clamp(min, POPUP_MAX_LENGTH.max(min)).min(available)
+}
+
+fn percentage(length: u16, percent: u16) -> u16 {
+    (length as u32 * percent as u32 / 100) as u16
+}
+
+fn centered_area(area: Rect, width: u16, height: u16) -> Rect {
+    let vertical = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center);
+    let horizontal = Layout::horizontal([Constraint::Length(width)]).flex(Flex::Center);
     let [area] = vertical.areas(area);
     let [area] = horizontal.areas(area);
     area
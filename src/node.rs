@@ -1,5 +1,11 @@
 use anyhow::Result;
-use ratatui::{buffer::Buffer, layout::Rect, prelude::Stylize, style::Style, widgets::Widget};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    prelude::Stylize,
+    style::Style,
+    widgets::{Block, BorderType, Widget},
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -14,20 +20,49 @@ pub(crate) struct Node {
     pub(crate) id: usize,
     pub(crate) connections: Vec<Connection>,
     pub(crate) location: Location,
+    /// Free-text annotation, e.g. the role the node plays in an example.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) note: String,
+    /// Initial state for the snapshot algorithms.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub(crate) state: isize,
+    /// Aligning the grid leaves pinned nodes where they are.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) pinned: bool,
+    /// The nodes this super-node was collapsed from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) collapsed: Option<Box<Collapsed>>,
+}
+
+/// What a super-node replaced, so it can be expanded again.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub(crate) struct Collapsed {
+    pub(crate) members: Vec<Node>,
+    /// Connections other nodes had to the members, by the name of the node
+    /// they start at.
+    pub(crate) incoming: Vec<(String, Connection)>,
+}
+
+const PIN_MARKER: &str = "◆";
+
+fn is_zero(state: &isize) -> bool {
+    *state == 0
 }
 
 impl Node {
-    pub(crate) fn add_connection(&mut self, connection: &Connection) {
-        match self
-            .connections
-            .iter()
-            .position(|n| n.other == connection.other)
-        {
-            Some(index) => self.connections[index] = connection.clone(),
+    /// Adds `connection`, replacing any existing connection to the same node.
+    /// Returns the weight of the replaced connection, if there was one.
+    pub(crate) fn add_connection(&mut self, connection: &Connection) -> Option<f64> {
+        match self.index_connection(&connection.other) {
+            Some(index) => {
+                let old = std::mem::replace(&mut self.connections[index], connection.clone());
+                Some(*old.weight)
+            }
             None => {
                 self.connections.push(connection.clone());
+                None
             }
-        };
+        }
     }
 
     pub fn index_connection(&self, other: &String) -> Option<usize> {
@@ -48,10 +83,29 @@ pub(crate) fn pad(width: u16, output: &mut String) {
     }
 }
 
+/// How the box of a node is drawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum NodeBorder {
+    /// Solid bars above and below an inverted name row.
+    #[default]
+    Solid,
+    /// A rounded border around the name.
+    Rounded,
+    /// A thick border around the name.
+    Thick,
+}
+
 #[derive(Debug, Default, Clone)]
 pub(crate) struct NodeWidget {
     node: Node,
     style: Style,
+    /// Draw only the id on a single row.
+    compact: bool,
+    /// In and out degree, shown on the bottom edge.
+    degree: Option<(usize, usize)>,
+    /// Lamport clock, shown on the top edge.
+    clock: Option<usize>,
+    border: NodeBorder,
 }
 
 impl NodeWidget {
@@ -59,8 +113,49 @@ impl NodeWidget {
         NodeWidget {
             node: node.clone(),
             style,
+            compact: false,
+            degree: None,
+            clock: None,
+            border: NodeBorder::Solid,
         }
     }
+
+    pub fn compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    pub fn degree(mut self, degree: Option<(usize, usize)>) -> Self {
+        self.degree = degree;
+        self
+    }
+
+    pub fn clock(mut self, clock: Option<usize>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn border(mut self, border: NodeBorder) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Draws a border around the area, with the name inside.
+    fn render_bordered(&self, border_type: BorderType, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .border_type(border_type)
+            .border_style(self.style);
+        let inner = block.inner(area);
+        block.render(area, buf);
+        buf.set_stringn(
+            inner.left(),
+            inner.top() + inner.height / 2,
+            self.display_name(inner.width),
+            inner.width as usize,
+            self.style.bold(),
+        );
+    }
+
     pub(crate) fn display_id(&self, width: u16) -> String {
         let mut output = format!("{}", self.node.id);
         pad(width, &mut output);
@@ -79,13 +174,94 @@ impl Widget for NodeWidget {
     where
         Self: Sized,
     {
-        buf.set_string(area.left(), area.top(), "████", self.style);
+        if self.compact {
+            buf.set_stringn(
+                area.left(),
+                area.top(),
+                self.display_id(area.width),
+                area.width as usize,
+                self.style.reversed().bold(),
+            );
+            if self.node.pinned {
+                buf.set_string(area.left(), area.top(), PIN_MARKER, self.style.reversed());
+            }
+            return;
+        }
+        let border_type = match self.border {
+            NodeBorder::Solid => None,
+            NodeBorder::Rounded => Some(BorderType::Rounded),
+            NodeBorder::Thick => Some(BorderType::Thick),
+        };
+        if let Some(border_type) = border_type {
+            self.render_bordered(border_type, area, buf);
+        } else {
+            self.render_solid(area, buf);
+        }
+        let bottom = area.bottom() - 1;
+        let edge_style = match self.border {
+            NodeBorder::Solid => self.style.reversed(),
+            _ => self.style,
+        };
+        // Badges go between the corners of the top and bottom edge.
+        let width = area.width.saturating_sub(2);
+        if let Some(clock) = self.clock {
+            let mut badge = clock.to_string();
+            pad(width, &mut badge);
+            buf.set_stringn(
+                area.left() + 1,
+                area.top(),
+                badge,
+                width as usize,
+                edge_style,
+            );
+        }
+        if let Some((incoming, outgoing)) = self.degree {
+            let mut badge = format!("{incoming}/{outgoing}");
+            pad(width, &mut badge);
+            buf.set_stringn(area.left() + 1, bottom, badge, width as usize, edge_style);
+        }
+        if self.node.pinned {
+            buf.set_string(
+                area.left() + area.width / 2,
+                area.top(),
+                PIN_MARKER,
+                self.style.reversed(),
+            );
+        }
+    }
+}
+
+impl NodeWidget {
+    /// Rows between the top and bottom edge are filled, with the name in the
+    /// middle one.
+    fn render_solid(&self, area: Rect, buf: &mut Buffer) {
+        let fill = "█".repeat(area.width.saturating_sub(2) as usize);
+        let bottom = area.bottom() - 1;
+        buf.set_string(
+            area.left(),
+            area.top(),
+            format!("\u{e0ba}{fill}\u{e0b8}"),
+            self.style,
+        );
+        for y in area.top() + 1..bottom {
+            buf.set_string(
+                area.left(),
+                y,
+                " ".repeat(area.width as usize),
+                self.style.reversed(),
+            );
+        }
         buf.set_string(
             area.left(),
-            area.top() + 1,
+            area.top() + area.height / 2,
             self.display_name(area.width),
             self.style.reversed().bold(),
         );
-        buf.set_string(area.left(), area.top() + 2, "████", self.style);
+        buf.set_string(
+            area.left(),
+            bottom,
+            format!("\u{e0be}{fill}\u{e0bc}"),
+            self.style,
+        );
     }
 }
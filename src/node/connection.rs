@@ -5,15 +5,31 @@ use serde::{Deserialize, Serialize};
 use super::super::Location;
 use crate::{NODE_H_SPACING, NODE_HEIGHT, NODE_V_SPACING, NODE_WIDTH};
 
+/// Weight used for connections created without asking the user for one.
+pub const DEFAULT_WEIGHT: f64 = 1.0;
+/// Amount a weight changes by when adjusted from the keyboard.
+pub const WEIGHT_STEP: f64 = 0.5;
+
 #[derive(Debug, Clone)]
 pub(crate) struct ConnectionWidget {
     pub sprite: ConnectionSprite,
     pub style: Style,
+    pub solid: bool,
 }
 
 impl ConnectionWidget {
     pub fn new(sprite: ConnectionSprite, style: Style) -> Self {
-        ConnectionWidget { sprite, style }
+        ConnectionWidget {
+            sprite,
+            style,
+            solid: false,
+        }
+    }
+
+    /// Draw straight connections with solid instead of dashed lines.
+    pub fn solid(mut self, solid: bool) -> Self {
+        self.solid = solid;
+        self
     }
 
     pub fn set_style(&mut self, style: Style) {
@@ -36,6 +52,8 @@ pub enum ConnectionSprite {
     DiagLLUR,
     DiagURLL,
     Other(String),
+    /// Receiving end of an `Other` connection, naming the sender.
+    Incoming(String),
 }
 
 impl ConnectionSprite {
@@ -48,6 +66,7 @@ impl ConnectionSprite {
             ConnectionSprite::UndirDiagULLR => vec!["𜹙𜹠".into(), " 𜹒𜹍𜹠".into(), "   𜹒𜹴".into()],
             ConnectionSprite::UndirDiagLLUR => vec!["   𜹰𜹖".into(), " 𜹰𜹍𜹑".into(), "𜹨𜹑".into()],
             ConnectionSprite::Other(string) => vec![format!("&{}", string)],
+            ConnectionSprite::Incoming(string) => vec![format!("{}&", string)],
             ConnectionSprite::Downwards => {
                 vec!["┇".into(), "┇".into(), "𜸊".into()]
             }
@@ -63,6 +82,14 @@ impl ConnectionSprite {
         }
     }
 
+    /// Like `get`, but with the dashed straight lines replaced by solid ones.
+    pub fn get_solid(self) -> Vec<String> {
+        self.get()
+            .into_iter()
+            .map(|line| line.replace('𜹜', "━").replace('┇', "┃"))
+            .collect()
+    }
+
     pub fn get_area(&self) -> Rect {
         match self {
             ConnectionSprite::UndirHorizontal => {
@@ -84,6 +111,8 @@ impl ConnectionSprite {
             ConnectionSprite::DiagULLR => Rect::new(NODE_WIDTH - 1, NODE_HEIGHT, 5, 3),
             ConnectionSprite::DiagLRUL => Rect::new(NODE_WIDTH - 1, NODE_HEIGHT, 5, 3),
             ConnectionSprite::Other(_) => Rect::new(NODE_WIDTH / 2 - 1, NODE_HEIGHT, 1, 1),
+            // Relative to the row above the receiving node.
+            ConnectionSprite::Incoming(_) => Rect::new(NODE_WIDTH / 2 - 1, 0, 1, 1),
         }
     }
 }
@@ -153,7 +182,11 @@ impl Widget for ConnectionWidget {
     where
         Self: Sized,
     {
-        for (content, line) in self.sprite.get().into_iter().zip(0u16..) {
+        let sprite = match self.solid {
+            true => self.sprite.get_solid(),
+            false => self.sprite.get(),
+        };
+        for (content, line) in sprite.into_iter().zip(0u16..) {
             let white_space = content.chars().filter(|c| c.is_whitespace()).count();
             buf.set_string(
                 area.left() + white_space as u16,
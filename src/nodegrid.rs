@@ -1,32 +1,81 @@
-use std::cmp;
+use std::{
+    cmp,
+    collections::{BTreeMap, BTreeSet, HashMap},
+};
 
 use anyhow::{Ok, Result, anyhow};
 use ratatui::{
     buffer::Buffer,
-    layout::{Offset, Rect},
-    style::Style,
+    layout::{Offset, Position, Rect},
+    style::{Style, Stylize},
     text::ToText,
     widgets::{Block, ListItem, Widget},
 };
 use serde::{Deserialize, Serialize};
-use strum::{Display, EnumIter, FromRepr};
+use strum::{Display, EnumIter, EnumString, FromRepr};
 
 use crate::{
     NODE_H_SPACING, NODE_HEIGHT, NODE_V_SPACING, NODE_WIDTH,
     location::Location,
     node::{
-        Node, NodeWidget,
-        connection::{Connection, ConnectionSprite, ConnectionWidget},
+        Node, NodeBorder, NodeWidget,
+        connection::{Connection, ConnectionSprite, ConnectionWidget, DEFAULT_WEIGHT},
     },
+    theme::Theme,
 };
 
 mod algorithms;
+pub(crate) use algorithms::{
+    Channels, Delivery, MAX_TRAFFIC, NodeDisplayState, Step, SteppedRun, TraceEvent, TraceRecord,
+    Verbosity,
+};
+mod adjacency;
+mod collapse;
+mod compact;
+mod diff;
+pub(crate) use diff::GridDiff;
+mod duplicates;
+pub(crate) use duplicates::Duplicates;
+mod generators;
+pub(crate) use generators::MAX_GENERATED_NODES;
+mod lint;
+pub(crate) use lint::Fix;
+mod partition;
+mod paths;
+mod symmetry;
+#[cfg(test)]
+mod tests;
+mod topology;
+pub(crate) use topology::NotATree;
+mod viewport;
 
-#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, EnumIter, FromRepr)]
+#[derive(
+    Debug,
+    Display,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumIter,
+    EnumString,
+    FromRepr,
+    Serialize,
+    Deserialize,
+)]
+#[strum(ascii_case_insensitive)]
 pub enum SelectedAlgorithm {
     ChandyLamport,
     LaiYang,
     ChangRoberts,
+    Franklin,
+    Peterson,
+    Luby,
+    Echo,
+    Tarry,
+    RicartAgrawala,
+    DijkstraScholten,
+    ChandyMisra,
+    CausalOrder,
 }
 
 impl From<SelectedAlgorithm> for ListItem<'_> {
@@ -35,18 +84,157 @@ impl From<SelectedAlgorithm> for ListItem<'_> {
     }
 }
 
+/// What happened to an edge, when connecting a floating node or comparing grids.
+#[derive(Debug, Clone, PartialEq, displaydoc::Display)]
+pub(crate) enum EdgeChange {
+    /// Created connection {from}->{to} with weight {weight}.
+    Created {
+        from: String,
+        to: String,
+        weight: f64,
+    },
+    /// Updated connection {from}->{to} weight {old} -> {new}.
+    Updated {
+        from: String,
+        to: String,
+        old: f64,
+        new: f64,
+    },
+    /// Removed connection {from}->{to} with weight {weight}.
+    Removed {
+        from: String,
+        to: String,
+        weight: f64,
+    },
+}
+
+/// Nodes removed by the last delete, kept so they can be restored.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Deleted {
+    nodes: Vec<Node>,
+    /// Connections other nodes had to the deleted ones, by the name of the
+    /// node they start at.
+    incoming: Vec<(String, Connection)>,
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct NodeGrid {
     pub(crate) nodes: Vec<Node>,
 
-    #[serde(skip)]
+    /// Nodes picked or created but not yet placed. Saved under their own key
+    /// only while there are any, so placement can be resumed after loading.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub(crate) floating_nodes: Vec<Node>,
+
+    /// Final node states of the last algorithm run, keyed by node name.
+    #[serde(skip)]
+    pub(crate) results: HashMap<String, String>,
+
+    /// States the last algorithm run tagged nodes with, to color them by.
+    #[serde(skip)]
+    pub(crate) display_states: HashMap<String, NodeDisplayState>,
+
+    /// Message trace of the last algorithm run.
+    #[serde(skip)]
+    pub(crate) trace: Vec<TraceRecord>,
+
+    /// Messages still in transit to every node when the last run ended, per
+    /// incoming channel.
+    #[serde(skip)]
+    pub(crate) inboxes: HashMap<String, BTreeMap<String, Vec<String>>>,
+
+    /// Connections cut for runs, as (from, to) pairs. Messages sent over them
+    /// are dropped.
+    #[serde(skip)]
+    pub(crate) partition: BTreeSet<(String, String)>,
+
+    /// Index of the connection of the picked node which the weight keys adjust.
+    #[serde(skip)]
+    pub(crate) current_connection: usize,
+
+    /// Lamport clock of every node when the last run ended, for algorithms
+    /// which keep them.
+    #[serde(skip)]
+    pub(crate) clocks: HashMap<String, usize>,
+
+    /// Node in the critical section when the last mutual exclusion run ended.
+    #[serde(skip)]
+    pub(crate) critical: Option<String>,
+
+    /// Names of the nodes algorithms are run on. Empty means the whole grid.
+    #[serde(skip)]
+    pub(crate) included: BTreeSet<String>,
+
+    /// Connections drawn highlighted, as (from, to) pairs.
+    #[serde(skip)]
+    pub(crate) highlighted: BTreeSet<(String, String)>,
+
+    /// Names of the nodes drawn highlighted.
+    #[serde(skip)]
+    pub(crate) marked: BTreeSet<String>,
+
+    /// Run without random processing, and pick initiators and delivery order
+    /// deterministically.
+    #[serde(skip)]
+    pub(crate) frozen: bool,
+
+    /// Delivery order of the messages in a run, instead of the channels of
+    /// the algorithm.
+    #[serde(skip)]
+    pub(crate) delivery: Option<Delivery>,
+
+    /// Runs stop after delivering this many messages, if set.
+    #[serde(skip)]
+    pub(crate) message_limit: Option<usize>,
+
+    /// Random basic messages per node the snapshots send, instead of a fixed
+    /// number, if set.
+    #[serde(skip)]
+    pub(crate) traffic: Option<usize>,
+
+    /// Runs draw every random choice from this seed, if set, so they can be
+    /// repeated exactly.
+    #[serde(skip)]
+    pub(crate) seed: Option<u64>,
+
+    /// Runs pick their initiator with a chance proportional to the weight of
+    /// its connections, instead of uniformly.
+    #[serde(skip)]
+    pub(crate) weighted_initiator: bool,
+
+    /// What the last delete removed.
+    #[serde(skip)]
+    pub(crate) deleted: Option<Deleted>,
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct NodeGridDisplay<'a> {
     pub(crate) grid: NodeGrid,
     pub(crate) block: Option<Block<'a>>,
+    /// Draw connections according to this channel assumption.
+    pub(crate) channels: Option<Channels>,
+    /// Also name the sender at the receiving end of long connections.
+    pub(crate) long_edge_labels: bool,
+    /// Show how many messages went over every connection in the last run.
+    pub(crate) message_counts: bool,
+    /// Write the weight of every connection next to it.
+    pub(crate) edge_weights: bool,
+    /// Draw only the nodes.
+    pub(crate) hide_connections: bool,
+    /// Draw every node as a single row showing its id, so more of them fit.
+    pub(crate) compact: bool,
+    /// Number of cells the grid is scrolled to the right and down.
+    pub(crate) offset: (u16, u16),
+    /// Show the in and out degree on every node.
+    pub(crate) show_degree: bool,
+    /// Show the Lamport clock of every node, after a run of an algorithm which
+    /// keeps them.
+    pub(crate) show_clocks: bool,
+    /// Draw nodes with a heavy total connection weight larger.
+    pub(crate) weighted_size: bool,
+    /// How the box of every node is drawn.
+    pub(crate) node_border: NodeBorder,
+    pub(crate) theme: Theme,
 }
 
 impl NodeGrid {
@@ -79,9 +267,15 @@ impl NodeGrid {
         if name.is_empty() {
             return Err(anyhow!("Name not unique."));
         }
-        if self.nodes.iter().any(|n| n.name == name) {
+        if self
+            .nodes
+            .iter()
+            .chain(self.floating_nodes.iter())
+            .any(|n| n.name == name)
+        {
             return Err(anyhow!("Name not unique."));
         }
+        self.clear_results();
         self.floating_nodes.push(Node {
             name,
             id: self.next_id(),
@@ -90,8 +284,16 @@ impl NodeGrid {
         anyhow::Ok(())
     }
 
+    /// An id above that of every placed, floating or restorable deleted node.
     fn next_id(&self) -> usize {
-        self.nodes.iter().max_by_key(|&n| n.id).map_or(0, |n| n.id) + 1
+        self.nodes
+            .iter()
+            .chain(self.floating_nodes.iter())
+            .chain(self.deleted.iter().flat_map(|d| d.nodes.iter()))
+            .map(|n| n.id)
+            .max()
+            .unwrap_or(0)
+            + 1
     }
 
     fn check_not_empty(&self, logger: &mut Vec<String>) -> Result<()> {
@@ -102,6 +304,15 @@ impl NodeGrid {
         Ok(())
     }
 
+    /// The successor of every node for algorithms on a ring, which would
+    /// otherwise pass their messages along arbitrary connections.
+    fn check_ring(&self, logger: &mut Vec<String>) -> Result<HashMap<String, String>> {
+        self.ring_successors().ok_or_else(|| {
+            logger.push("Graph is not a ring.".to_string());
+            anyhow!("Graph is not a ring.")
+        })
+    }
+
     pub(crate) fn move_node(&mut self, x: i8, y: i8) {
         for node in self.floating_nodes.iter_mut() {
             let mut location = node.location;
@@ -138,16 +349,237 @@ impl NodeGrid {
         Ok(())
     }
 
+    /// Deletes the floating nodes and the connections to them, keeping both
+    /// so the last delete can be restored.
     pub(crate) fn delete(&mut self) {
-        self.floating_nodes.clear();
+        let nodes: Vec<Node> = self.floating_nodes.drain(..).collect();
+        self.delete_nodes(nodes);
+    }
+
+    /// Deletes the node named `name`, placed or floating, and every connection
+    /// to it, keeping both so the delete can be restored.
+    pub(crate) fn delete_node(&mut self, name: &str) -> Result<()> {
+        let node = if let Some(index) = self.nodes.iter().position(|n| n.name == name) {
+            self.nodes.remove(index)
+        } else if let Some(index) = self.floating_nodes.iter().position(|n| n.name == name) {
+            self.floating_nodes.remove(index)
+        } else {
+            return Err(anyhow!("No node named {name:?}."));
+        };
+        self.delete_nodes(vec![node]);
+        Ok(())
+    }
+
+    /// Removes the connections to the already taken out `nodes` from every
+    /// node left, and keeps both for restoring.
+    fn delete_nodes(&mut self, nodes: Vec<Node>) {
+        self.clear_results();
+        let mut incoming = vec![];
+        for node in self.nodes.iter_mut().chain(self.floating_nodes.iter_mut()) {
+            let (removed, kept) = std::mem::take(&mut node.connections)
+                .into_iter()
+                .partition(|c| nodes.iter().any(|n| n.name == c.other));
+            node.connections = kept;
+            incoming.extend(
+                removed
+                    .into_iter()
+                    .map(|c: Connection| (node.name.clone(), c)),
+            );
+        }
+        for node in nodes.iter() {
+            self.included.remove(&node.name);
+        }
+        self.deleted = Some(Deleted { nodes, incoming });
+    }
+
+    /// Brings back the nodes removed by the last delete as floating nodes, with
+    /// the connections to them. Returns their names.
+    pub(crate) fn restore_deleted(&mut self) -> Result<Vec<String>> {
+        let deleted = self
+            .deleted
+            .take()
+            .ok_or_else(|| anyhow!("Nothing was deleted."))?;
+        let taken = deleted.nodes.iter().any(|d| {
+            self.nodes
+                .iter()
+                .chain(self.floating_nodes.iter())
+                .any(|n| n.name == d.name)
+        });
+        if taken {
+            let names = deleted
+                .nodes
+                .iter()
+                .map(|n| n.name.clone())
+                .collect::<Vec<_>>();
+            self.deleted = Some(deleted);
+            return Err(anyhow!("Name of {} is taken again.", names.join(", ")));
+        }
+        self.clear_results();
+        for (from, connection) in deleted.incoming.iter() {
+            let mut nodes = self.nodes.iter_mut().chain(self.floating_nodes.iter_mut());
+            if let Some(node) = nodes.find(|n| &n.name == from) {
+                node.add_connection(connection);
+            }
+        }
+        let names = deleted.nodes.iter().map(|n| n.name.clone()).collect();
+        self.floating_nodes.extend(deleted.nodes);
+        Ok(names)
     }
 
     pub(crate) fn overwrite(&mut self, new_node: String) -> Result<()> {
         let new_node: Node = serde_json::from_str(&new_node)?;
+        if new_node.index_connection(&new_node.name).is_some() {
+            Err(anyhow!("Can't connect {} to itself.", new_node.name))?;
+        }
+        self.clear_results();
         self.floating_nodes[0] = new_node;
         Ok(())
     }
 
+    /// A copy of the grid with every node renamed to `n0..nk`, in the order the
+    /// nodes are stored, floating nodes last. Connections are renamed along, so the structure is kept.
+    pub(crate) fn anonymized(&self) -> NodeGrid {
+        let mut names: HashMap<String, String> = HashMap::new();
+        for node in self.nodes.iter().chain(self.floating_nodes.iter()) {
+            names.insert(node.name.clone(), format!("n{}", names.len()));
+        }
+        let mut grid = self.clone();
+        for node in grid.nodes.iter_mut().chain(grid.floating_nodes.iter_mut()) {
+            node.name = names[&node.name].clone();
+            for connection in node.connections.iter_mut() {
+                let next = format!("n{}", names.len());
+                connection.other = names
+                    .entry(connection.other.clone())
+                    .or_insert(next)
+                    .clone();
+            }
+        }
+        grid
+    }
+
+    pub(crate) fn get_floating_note(&self) -> Result<String> {
+        match self.floating_nodes.len() {
+            1 => Ok(self.floating_nodes[0].note.clone()),
+            0 => Err(anyhow!("Tried to read a note with empty floating_nodes.")),
+            _ => Err(anyhow!("Tried to read a note of multiple floating nodes.")),
+        }
+    }
+
+    /// Sets the note of every floating node.
+    pub(crate) fn annotate(&mut self, note: String) -> Result<()> {
+        if self.floating_nodes.is_empty() {
+            return Err(anyhow!("Tried to annotate with empty floating_nodes."));
+        }
+        for node in self.floating_nodes.iter_mut() {
+            node.note = note.clone();
+        }
+        Ok(())
+    }
+
+    /// Pins every floating node, or unpins them if they all are pinned. Returns
+    /// whether they are pinned now.
+    pub(crate) fn toggle_pinned(&mut self) -> Result<bool> {
+        if self.floating_nodes.is_empty() {
+            return Err(anyhow!("Tried to pin with empty floating_nodes."));
+        }
+        let pin = !self.floating_nodes.iter().all(|n| n.pinned);
+        for node in self.floating_nodes.iter_mut() {
+            node.pinned = pin;
+        }
+        Ok(pin)
+    }
+
+    /// Lays the placed nodes out in a square, in the order of their ids. Pinned
+    /// nodes stay where they are and the rest fill the cells around them.
+    /// Returns the number of nodes moved.
+    pub(crate) fn align(&mut self) -> Result<usize> {
+        if self.nodes.is_empty() {
+            return Err(anyhow!("No nodes in grid."));
+        }
+        let pinned: Vec<Location> = self
+            .nodes
+            .iter()
+            .filter(|n| n.pinned)
+            .map(|n| n.location)
+            .collect();
+        let columns = (self.nodes.len() as f64).sqrt().ceil() as usize;
+        let mut cells = (0..)
+            .map(|i| Location::new((i % columns) as u16, (i / columns) as u16))
+            .filter(|l| !pinned.contains(l));
+        let mut unpinned: Vec<&mut Node> = self.nodes.iter_mut().filter(|n| !n.pinned).collect();
+        unpinned.sort_by_key(|n| n.id);
+        let mut moved = 0;
+        for node in unpinned {
+            let cell = cells.next().unwrap();
+            if node.location != cell {
+                node.location = cell;
+                moved += 1;
+            }
+        }
+        Ok(moved)
+    }
+
+    /// Renames the node `old` to `new`, and points every connection to `old`
+    /// at `new` instead, including those cut by the partition or kept to
+    /// restore a delete.
+    pub(crate) fn rename_node(&mut self, old: &str, new: &str) -> Result<()> {
+        let new = new.trim();
+        let mut nodes = self.nodes.iter().chain(self.floating_nodes.iter());
+        if new.is_empty() || nodes.any(|n| n.name == new) {
+            return Err(anyhow!("Name not unique."));
+        }
+        let mut nodes = self.nodes.iter_mut().chain(self.floating_nodes.iter_mut());
+        let node = nodes
+            .find(|n| n.name == old)
+            .ok_or_else(|| anyhow!("No node named {old}."))?;
+        if node.connections.iter().any(|c| c.other == new) {
+            return Err(anyhow!("Can't connect {new} to itself."));
+        }
+        node.name = new.to_string();
+        for connection in self
+            .nodes
+            .iter_mut()
+            .chain(self.floating_nodes.iter_mut())
+            .flat_map(|n| n.connections.iter_mut())
+            .filter(|c| c.other == old)
+        {
+            connection.other = new.to_string();
+        }
+
+        let rename = |name: String| if name == old { new.to_string() } else { name };
+        self.included = std::mem::take(&mut self.included)
+            .into_iter()
+            .map(rename)
+            .collect();
+        self.marked = std::mem::take(&mut self.marked)
+            .into_iter()
+            .map(rename)
+            .collect();
+        self.highlighted = std::mem::take(&mut self.highlighted)
+            .into_iter()
+            .map(|(from, to)| (rename(from), rename(to)))
+            .collect();
+        self.partition = std::mem::take(&mut self.partition)
+            .into_iter()
+            .map(|(from, to)| (rename(from), rename(to)))
+            .collect();
+        if let Some(deleted) = &mut self.deleted {
+            for (from, connection) in deleted.incoming.iter_mut() {
+                *from = rename(std::mem::take(from));
+                connection.other = rename(std::mem::take(&mut connection.other));
+            }
+            for connection in deleted
+                .nodes
+                .iter_mut()
+                .flat_map(|n| n.connections.iter_mut())
+            {
+                connection.other = rename(std::mem::take(&mut connection.other));
+            }
+        }
+        self.clear_results();
+        Ok(())
+    }
+
     pub(crate) fn get_floating_serialized(&self) -> Result<String> {
         match self.floating_nodes.len() {
             1 => Ok(serde_json::to_string_pretty(&self.floating_nodes[0])?),
@@ -156,26 +588,328 @@ impl NodeGrid {
         }
     }
 
-    pub(crate) fn connect(&mut self, connection: &Connection) -> Result<()> {
+    /// The single picked node, whose connection weights are edited.
+    fn weighted_node(&self) -> Result<&Node> {
+        match &self.floating_nodes[..] {
+            [node] => Ok(node),
+            _ => Err(anyhow!(
+                "Pick a single node to edit its connection weights."
+            )),
+        }
+    }
+
+    /// The connections of the single picked node, one `other weight` per line.
+    pub(crate) fn connection_weights(&self) -> Result<String> {
+        let node = self.weighted_node()?;
+        if node.connections.is_empty() {
+            Err(anyhow!("{} has no connections.", node.name))?;
+        }
+        Ok(node
+            .connections
+            .iter()
+            .map(|c| format!("{} {}", c.other, c.weight))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    /// Reads lines of `other weight`, as written by `connection_weights`, into
+    /// the connections of the single picked node whose weight changed.
+    pub(crate) fn changed_weights(&self, lines: &[String]) -> Result<Vec<Connection>> {
+        let node = self.weighted_node()?;
+        let mut changed = vec![];
+        for line in lines.iter().filter(|l| !l.trim().is_empty()) {
+            let mut parts = line.split_whitespace();
+            let other = parts.next().unwrap_or_default();
+            let weight = parts
+                .next()
+                .ok_or_else(|| anyhow!("No weight for the connection to {other}."))?;
+            let weight = weight
+                .parse::<f64>()
+                .ok()
+                .filter(|w| w.is_finite())
+                .ok_or_else(|| {
+                    anyhow!("Malformed weight {weight:?} for the connection to {other}.")
+                })?;
+            if let Some(extra) = parts.next() {
+                Err(anyhow!(
+                    "Unexpected {extra:?} after the weight of the connection to {other}."
+                ))?;
+            }
+            let index = node
+                .index_connection(&other.to_string())
+                .ok_or_else(|| anyhow!("{} has no connection to {other}.", node.name))?;
+            if *node.connections[index].weight != weight {
+                changed.push(Connection::new(other.to_string(), weight));
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Connects every floating node to `connection.other`, reporting for each
+    /// whether a new edge was created or the weight of an existing one updated.
+    pub(crate) fn connect(&mut self, connection: &Connection) -> Result<Vec<EdgeChange>> {
+        let mut changes = vec![];
         match self.floating_nodes.len() {
             0 => Err(anyhow!("Tried to connect with empty floating_nodes."))?,
             _ => {
-                if !self.nodes.iter().any(|n| n.name == connection.other) {
-                    Err(anyhow!("Other `{:?}` does not exist.", connection.other))?
-                };
+                self.check_other_exists(connection)?;
+                self.clear_results();
                 for node in self.floating_nodes.iter_mut() {
-                    node.add_connection(connection);
+                    let from = node.name.clone();
+                    let to = connection.other.clone();
+                    let change = match node.add_connection(connection) {
+                        Some(old) => EdgeChange::Updated {
+                            from,
+                            to,
+                            old,
+                            new: *connection.weight,
+                        },
+                        None => EdgeChange::Created {
+                            from,
+                            to,
+                            weight: *connection.weight,
+                        },
+                    };
+                    changes.push(change);
                 }
             }
         };
 
-        Ok(())
+        Ok(changes)
+    }
+
+    /// The connection of the single picked node which the weight keys adjust.
+    fn current_connection(&self) -> Result<(&Node, &Connection)> {
+        let node = match self.floating_nodes.len() {
+            1 => &self.floating_nodes[0],
+            _ => Err(anyhow!("Pick a single node to adjust its connections."))?,
+        };
+        match node.connections.len() {
+            0 => Err(anyhow!("{} has no connections.", node.name))?,
+            len => Ok((node, &node.connections[self.current_connection % len])),
+        }
+    }
+
+    /// Moves on to the next connection of the picked node, and highlights it.
+    pub(crate) fn cycle_connection(&mut self) -> Result<String> {
+        self.current_connection =
+            (self.current_connection + 1) % self.current_connection()?.0.connections.len();
+        let (node, connection) = self.current_connection()?;
+        let pair = (node.name.clone(), connection.other.clone());
+        self.highlighted = BTreeSet::from([pair.clone()]);
+        Ok(pair.1)
+    }
+
+    /// The current connection of the picked node with `step` added to its
+    /// weight, to be connected again. Like the other ways of setting a weight,
+    /// this may take it to zero or below.
+    pub(crate) fn adjusted_connection(&self, step: f64) -> Result<Connection> {
+        let (_, connection) = self.current_connection()?;
+        Ok(Connection::new(
+            connection.other.clone(),
+            *connection.weight + step,
+        ))
+    }
+
+    /// Connects the floating nodes and `connection.other` in both directions. Nothing
+    /// is changed if `connection.other` does not exist.
+    pub(crate) fn connect_undirected(
+        &mut self,
+        connection: &Connection,
+    ) -> Result<Vec<EdgeChange>> {
+        self.check_other_exists(connection)?;
+        let changes = self.connect(connection)?;
+        self.connect_reverse(connection)?;
+        Ok(changes)
+    }
+
+    /// Removes the connection of every floating node to `other`. Fails if none
+    /// of them has one.
+    pub(crate) fn disconnect(&mut self, other: &str) -> Result<Vec<EdgeChange>> {
+        if self.floating_nodes.is_empty() {
+            Err(anyhow!("Tried to disconnect with empty floating_nodes."))?;
+        }
+        let mut changes = vec![];
+        for node in self.floating_nodes.iter_mut() {
+            if let Some(index) = node.index_connection(&other.to_string()) {
+                let removed = node.connections.remove(index);
+                changes.push(EdgeChange::Removed {
+                    from: node.name.clone(),
+                    to: removed.other,
+                    weight: *removed.weight,
+                });
+            }
+        }
+        if changes.is_empty() {
+            Err(anyhow!("No connection to {other:?}."))?;
+        }
+        self.clear_results();
+        Ok(changes)
+    }
+
+    /// Removes the connections of the floating nodes to `other`, and those of
+    /// `other` back to them.
+    pub(crate) fn disconnect_undirected(&mut self, other: &str) -> Result<Vec<EdgeChange>> {
+        let mut changes = self.disconnect(other)?;
+        changes.append(&mut self.disconnect_reverse(other));
+        Ok(changes)
+    }
+
+    /// Removes the connections of `other` to the floating nodes.
+    pub(crate) fn disconnect_reverse(&mut self, other: &str) -> Vec<EdgeChange> {
+        let names: Vec<String> = self.floating_nodes.iter().map(|n| n.name.clone()).collect();
+        let mut changes = vec![];
+        for node in self.nodes.iter_mut().filter(|n| n.name == other) {
+            node.connections.retain(|c| {
+                let kept = !names.contains(&c.other);
+                if !kept {
+                    changes.push(EdgeChange::Removed {
+                        from: other.to_string(),
+                        to: c.other.clone(),
+                        weight: *c.weight,
+                    });
+                }
+                kept
+            });
+        }
+        self.clear_results();
+        changes
+    }
+
+    pub(crate) fn node_exists(&self, name: &str) -> bool {
+        self.nodes.iter().any(|n| n.name == name)
+    }
+
+    /// Checks `connection` leads to a placed node, and so not back to one of
+    /// the picked nodes it would be added to.
+    fn check_other_exists(&self, connection: &Connection) -> Result<()> {
+        if self
+            .floating_nodes
+            .iter()
+            .any(|n| n.name == connection.other)
+        {
+            Err(anyhow!("Can't connect {} to itself.", connection.other))?;
+        }
+        match self.node_exists(&connection.other) {
+            true => Ok(()),
+            false => Err(anyhow!("Other `{:?}` does not exist.", connection.other)),
+        }
+    }
+
+    /// Removes the connections of nodes to themselves, which can't be made in
+    /// the grid but may be in a file, and logs them. Returns the nodes which
+    /// had one.
+    pub(crate) fn drop_self_loops(&mut self, logger: &mut Vec<String>) -> Vec<String> {
+        let mut looped = vec![];
+        for node in self.nodes.iter_mut().chain(self.floating_nodes.iter_mut()) {
+            let before = node.connections.len();
+            node.connections.retain(|c| c.other != node.name);
+            if node.connections.len() < before {
+                looped.push(node.name.clone());
+            }
+        }
+        if !looped.is_empty() {
+            logger.push(format!(
+                "Dropped the connections of {} to themselves.",
+                looped.join(", ")
+            ));
+        }
+        looped
+    }
+
+    /// Removes every connection of the floating node, and optionally every
+    /// connection pointing back at it. Returns how many connections were removed.
+    pub(crate) fn clear_connections(&mut self, reciprocal: bool) -> Result<usize> {
+        let node = self.floating_nodes.first_mut().ok_or(anyhow!(
+            "Tried to clear connections with empty floating_nodes."
+        ))?;
+        let name = node.name.clone();
+        let mut removed = node.connections.len();
+        node.connections.clear();
+        if reciprocal {
+            for other in self.nodes.iter_mut() {
+                let before = other.connections.len();
+                other.connections.retain(|c| c.other != name);
+                removed += before - other.connections.len();
+            }
+        }
+        self.clear_results();
+        Ok(removed)
+    }
+
+    /// Adds the floating nodes to the set algorithms are run on, or removes them if
+    /// they are all in it already. Returns whether the nodes are now included.
+    pub(crate) fn toggle_included(&mut self) -> Result<bool> {
+        if self.floating_nodes.is_empty() {
+            return Err(anyhow!("Tried to include with empty floating_nodes."));
+        }
+        let include = !self
+            .floating_nodes
+            .iter()
+            .all(|n| self.included.contains(&n.name));
+        for node in self.floating_nodes.iter() {
+            match include {
+                true => self.included.insert(node.name.clone()),
+                false => self.included.remove(&node.name),
+            };
+        }
+        Ok(include)
+    }
+
+    /// The included nodes, keeping only the connections among them, with the
+    /// run settings of this grid.
+    pub(crate) fn included_subgrid(&self) -> NodeGrid {
+        let nodes = self
+            .nodes
+            .iter()
+            .filter(|n| self.included.contains(&n.name))
+            .map(|n| {
+                let mut node = n.clone();
+                node.connections
+                    .retain(|c| self.included.contains(&c.other));
+                node
+            })
+            .collect();
+        NodeGrid {
+            nodes,
+            frozen: self.frozen,
+            delivery: self.delivery,
+            message_limit: self.message_limit,
+            traffic: self.traffic,
+            partition: self.partition.clone(),
+            seed: self.seed,
+            weighted_initiator: self.weighted_initiator,
+            ..Default::default()
+        }
+    }
+
+    /// The placed node closest to `location`.
+    pub(crate) fn nearest_node(&self, location: &Location) -> Option<&Node> {
+        self.nodes
+            .iter()
+            .min_by_key(|n| n.location.distance_squared(location))
+    }
+
+    /// Connects the floating node to the nearest placed node with the default weight.
+    pub(crate) fn connect_nearest(&mut self) -> Result<Vec<EdgeChange>> {
+        let location = match self.floating_nodes.first() {
+            Some(node) => node.location,
+            None => Err(anyhow!("Tried to connect with empty floating_nodes."))?,
+        };
+        let other = self
+            .nearest_node(&location)
+            .ok_or(anyhow!("No other nodes to connect to."))?
+            .name
+            .clone();
+        self.connect(&Connection::new(other, DEFAULT_WEIGHT))
     }
 
     pub(crate) fn connect_reverse(&mut self, connection: &Connection) -> Result<()> {
         match self.floating_nodes.len() {
             0 => Err(anyhow!("Tried to connect with empty floating_nodes."))?,
             _ => {
+                self.check_other_exists(connection)?;
+                self.clear_results();
                 for node in self.floating_nodes.iter() {
                     let other_connection = Connection::new(node.name.clone(), *connection.weight);
                     for node in self.nodes.iter_mut().filter(|n| n.name == connection.other) {
@@ -188,38 +922,302 @@ impl NodeGrid {
         Ok(())
     }
 
-    fn render_nodes(&self, buf: &mut Buffer) {
+    /// Nodes which stood out in the last run, in grid order, with what set
+    /// them apart: elected leaders, members of the computed set, and the node
+    /// in the critical section.
+    pub(crate) fn special_nodes(&self) -> Vec<(String, &'static str)> {
+        self.nodes
+            .iter()
+            .filter_map(|n| {
+                let role = match self.result(&n.name).map(String::as_str) {
+                    _ if self.critical.as_ref() == Some(&n.name) => "critical section",
+                    _ if self.display_states.get(&n.name) == Some(&NodeDisplayState::Leader) => {
+                        "leader"
+                    }
+                    Some("in set") => "in set",
+                    _ => return None,
+                };
+                Some((n.name.clone(), role))
+            })
+            .collect()
+    }
+
+    /// The final state `name` ended up in during the last algorithm run.
+    pub(crate) fn result(&self, name: &str) -> Option<&String> {
+        self.results.get(name)
+    }
+
+    /// Results of the last run no longer describe the grid once it is edited.
+    fn clear_results(&mut self) {
+        self.results.clear();
+        self.display_states.clear();
+        self.trace.clear();
+        self.inboxes.clear();
+        self.critical = None;
+        self.clocks.clear();
+    }
+
+    pub(crate) fn inspect_floating(&self) -> Result<String> {
+        match self.floating_nodes.len() {
+            1 => Ok(self.inspect(&self.floating_nodes[0])),
+            0 => Err(anyhow!("Tried to inspect with empty floating_nodes.")),
+            _ => Err(anyhow!("Tried to inspect multiple floating nodes.")),
+        }
+    }
+
+    /// Inspects the placed node `name`, as while stepping through a run.
+    pub(crate) fn inspect_node(&self, name: &str) -> Result<String> {
+        self.nodes
+            .iter()
+            .find(|n| n.name == name)
+            .map(|node| self.inspect(node))
+            .ok_or_else(|| anyhow!("No node named {name:?}."))
+    }
+
+    /// Details of `node`, with what it holds from the last run.
+    fn inspect(&self, node: &Node) -> String {
+        let mut output = vec![
+            format!("Name: {}", node.name),
+            format!("Id: {}", node.id),
+            format!("Location: ({}, {})", node.location.x, node.location.y),
+        ];
+        if !node.note.is_empty() {
+            output.push(format!("Note: {}", node.note));
+        }
+        if node.state != 0 {
+            output.push(format!("Initial state: {}", node.state));
+        }
+        output.push("Connections:".to_string());
+        output.extend(self.connection_table(node));
+        output.push(format!(
+            "Last run: {}",
+            self.result(&node.name).map_or("-", |r| r.as_str())
+        ));
+        if let Some(inbox) = self.inboxes.get(&node.name) {
+            output.push("Inbox:".to_string());
+            for (sender, messages) in inbox.iter() {
+                output.push(format!("    from {sender}: {}", messages.join(", ")));
+            }
+        }
+        output.join("\n")
+    }
+
+    /// Connections of `node` as aligned `other | weight | direction` rows,
+    /// where connections going both ways are listed once as `both`. Incoming
+    /// connections show the weight of the other node's connection.
+    fn connection_table(&self, node: &Node) -> Vec<String> {
+        let mut rows: Vec<(String, String, &str)> = node
+            .connections
+            .iter()
+            .map(|c| {
+                let reciprocal = self
+                    .nodes
+                    .iter()
+                    .chain(self.floating_nodes.iter())
+                    .find(|n| n.name == c.other)
+                    .is_some_and(|n| n.index_connection(&node.name).is_some());
+                let direction = if reciprocal { "both" } else { "out" };
+                (c.other.clone(), c.weight.to_string(), direction)
+            })
+            .collect();
+        for other in self.nodes.iter().chain(self.floating_nodes.iter()) {
+            if node.index_connection(&other.name).is_some() {
+                continue;
+            }
+            if let Some(index) = other.index_connection(&node.name) {
+                let weight = other.connections[index].weight.to_string();
+                rows.push((other.name.clone(), weight, "in"));
+            }
+        }
+        let name_width = rows.iter().map(|r| r.0.len()).max().unwrap_or(0).max(4);
+        let weight_width = rows.iter().map(|r| r.1.len()).max().unwrap_or(0).max(6);
+        std::iter::once(("Node".to_string(), "Weight".to_string(), "Direction"))
+            .chain(rows)
+            .map(|(other, weight, direction)| {
+                format!("    {other:<name_width$} | {weight:>weight_width$} | {direction}")
+            })
+            .collect()
+    }
+
+    /// Number of connections into and out of the node named `name`.
+    pub(crate) fn degree(&self, name: &str) -> (usize, usize) {
+        let nodes = || self.nodes.iter().chain(self.floating_nodes.iter());
+        let incoming = nodes()
+            .filter(|n| n.connections.iter().any(|c| c.other == name))
+            .count();
+        let outgoing = nodes()
+            .find(|n| n.name == name)
+            .map_or(0, |n| n.connections.len());
+        (incoming, outgoing)
+    }
+
+    /// Sum of the weights of the connections into and out of the node named
+    /// `name`.
+    pub(crate) fn incident_weight(&self, name: &str) -> f64 {
+        self.nodes
+            .iter()
+            .chain(self.floating_nodes.iter())
+            .flat_map(|n| n.connections.iter().map(move |c| (n, c)))
+            .filter(|(n, c)| n.name == name || c.other == name)
+            .map(|(_, c)| *c.weight)
+            .sum()
+    }
+
+    /// Incident weight of every node, as by `incident_weight`, along with
+    /// their average. Computed in a single pass over the connections.
+    fn incident_weights(&self) -> (HashMap<String, f64>, f64) {
+        let nodes = || self.nodes.iter().chain(self.floating_nodes.iter());
+        let mut weights: HashMap<String, f64> = nodes().map(|n| (n.name.clone(), 0.0)).collect();
+        for node in nodes() {
+            for connection in node.connections.iter() {
+                *weights.entry(node.name.clone()).or_default() += *connection.weight;
+                if connection.other != node.name {
+                    *weights.entry(connection.other.clone()).or_default() += *connection.weight;
+                }
+            }
+        }
+        let average = nodes().map(|n| weights[&n.name]).sum::<f64>() / nodes().count() as f64;
+        (weights, average)
+    }
+
+    /// Area `node` is drawn in. When sized by the `incident_weights`, nodes
+    /// heavier than the average grow a column to each side, and nodes twice as
+    /// heavy a row up and down as well. That stays within the spacing, so
+    /// nodes never overlap.
+    fn node_area(&self, node: &Node, weights: Option<&(HashMap<String, f64>, f64)>) -> Rect {
+        let (x, y) = self.place(node);
+        let area = Rect::new(x, y, NODE_WIDTH, NODE_HEIGHT);
+        let Some((weights, average)) = weights else {
+            return area;
+        };
+        let average = *average;
+        let weight = weights.get(&node.name).copied().unwrap_or_default();
+        let (dx, dy) = match weight {
+            w if average > 0.0 && w >= 2.0 * average => (1, 1),
+            w if w > average => (1, 0),
+            _ => (0, 0),
+        };
+        Rect::new(x - dx, y - dy, NODE_WIDTH + 2 * dx, NODE_HEIGHT + 2 * dy)
+    }
+
+    fn render_nodes(
+        &self,
+        buf: &mut Buffer,
+        show_degree: bool,
+        show_clocks: bool,
+        weighted: bool,
+        border: NodeBorder,
+        theme: &Theme,
+    ) {
+        let weights = weighted.then(|| self.incident_weights());
         for node in self.nodes.iter() {
-            let style = Style::default().fg(ratatui::style::Color::Green);
-            let (x, y) = self.place(node);
-            let node_widget = NodeWidget::from(node, style);
-            let area = Rect::new(x, y, NODE_WIDTH, NODE_HEIGHT);
-            node_widget.render(area, buf);
+            let style = Style::default().fg(self.node_color(node, theme));
+            let node_widget = NodeWidget::from(node, style)
+                .degree(show_degree.then(|| self.degree(&node.name)))
+                .clock(
+                    show_clocks
+                        .then(|| self.clocks.get(&node.name).copied())
+                        .flatten(),
+                )
+                .border(border);
+            node_widget.render(self.node_area(node, weights.as_ref()), buf);
+        }
+    }
+
+    /// Highlighted connections stand out in either direction.
+    fn connection_style(&self, from: &str, to: &str, theme: &Theme) -> Style {
+        let pair = |a: &str, b: &str| (a.to_string(), b.to_string());
+        match self.highlighted.contains(&pair(from, to))
+            || self.highlighted.contains(&pair(to, from))
+        {
+            true => Style::default().fg(theme.marked).bold(),
+            false => Style::default().fg(theme.connection),
         }
     }
 
-    fn render_floating_nodes(&self, buf: &mut Buffer) {
+    /// The node in the critical section stands out most, then marked nodes,
+    /// the states nodes ended the last run in, and nodes left out of runs.
+    fn node_color(&self, node: &Node, theme: &Theme) -> ratatui::style::Color {
+        if self.critical.as_ref() == Some(&node.name) {
+            return theme.critical;
+        }
+        if self.marked.contains(&node.name) {
+            return theme.marked;
+        }
+        match self.display_states.get(&node.name) {
+            Some(NodeDisplayState::Leader) => return theme.leader,
+            Some(NodeDisplayState::Passive) => return theme.passive,
+            Some(NodeDisplayState::Recorded) => return theme.recorded,
+            None => (),
+        }
+        match self.included.is_empty() || self.included.contains(&node.name) {
+            true => theme.node,
+            false => theme.excluded,
+        }
+    }
+
+    fn render_floating_nodes(
+        &self,
+        buf: &mut Buffer,
+        show_degree: bool,
+        weighted: bool,
+        border: NodeBorder,
+        theme: &Theme,
+    ) {
+        let weights = weighted.then(|| self.incident_weights());
         for node in self.floating_nodes.iter() {
-            let style = Style::default().fg(ratatui::style::Color::Cyan);
-            let (x, y) = self.place(node);
-            let node_widget = NodeWidget::from(node, style);
-            let area = Rect::new(x, y, NODE_WIDTH, NODE_HEIGHT);
-            node_widget.render(area, buf);
+            let style = Style::default().fg(theme.floating);
+            let node_widget = NodeWidget::from(node, style)
+                .degree(show_degree.then(|| self.degree(&node.name)))
+                .border(border);
+            node_widget.render(self.node_area(node, weights.as_ref()), buf);
+        }
+    }
+
+    /// Number of messages sent over every connection in the last run, keyed
+    /// by (sender, destination).
+    pub(crate) fn message_counts(&self) -> HashMap<(String, String), usize> {
+        let mut counts = HashMap::new();
+        for record in self.trace.iter().filter(|r| r.event == TraceEvent::Sent) {
+            *counts
+                .entry((record.sender.clone(), record.destination.clone()))
+                .or_default() += 1;
         }
+        counts
     }
 
-    fn render_connections(&self, buf: &mut Buffer) {
+    fn render_connections(
+        &self,
+        buf: &mut Buffer,
+        channels: Option<Channels>,
+        long_edge_labels: bool,
+        message_counts: bool,
+        edge_weights: bool,
+        theme: &Theme,
+    ) {
         let mut longer_connections = vec![];
+        // Where the weight of every connection is written, with the weights
+        // in either direction.
+        let mut weights: BTreeMap<(String, String), (Position, Vec<f64>)> = BTreeMap::new();
+        // Where the number of messages sent over every connection is written.
+        // Connections drawn next to their nodes count both directions.
+        let mut counted: BTreeMap<(String, String), (Rect, usize)> = BTreeMap::new();
+        let counts = self.message_counts();
+        let count = |from: &str, to: &str| {
+            counts
+                .get(&(from.to_string(), to.to_string()))
+                .copied()
+                .unwrap_or_default()
+        };
 
         for node in self.nodes.iter() {
-            let style = Style::default();
-
             for (origin, connection) in self.nodes.iter().filter_map(|n| {
                 n.connections
                     .iter()
                     .find(|c| c.other == node.name)
                     .map(|c| (n, c))
             }) {
+                let style = self.connection_style(&origin.name, &node.name, theme);
                 let con_widget = if node.connections.iter().any(|c| c.other == origin.name) {
                     ConnectionWidget::new(
                         connection.undirected_sprite(&origin.location, &node.location),
@@ -230,7 +1228,8 @@ impl NodeGrid {
                         connection.directed_sprite(&origin.location, &node.location),
                         style,
                     )
-                };
+                }
+                .solid(channels == Some(Channels::Fifo));
 
                 let area = {
                     let coords = match con_widget.sprite {
@@ -244,8 +1243,58 @@ impl NodeGrid {
                 };
 
                 match con_widget.sprite {
-                    ConnectionSprite::Other(_) => longer_connections.push((area, con_widget)),
-                    _ => con_widget.render(area, buf),
+                    ConnectionSprite::Other(_) => {
+                        // After the name of the other node.
+                        let x = area.x + node.name.chars().count() as u16 + 2;
+                        weights.insert(
+                            (origin.name.clone(), node.name.clone()),
+                            (Position::new(x, area.y), vec![*connection.weight]),
+                        );
+                        // After the weight, when it is written.
+                        let x = match edge_weights {
+                            true => x + connection.weight.to_string().chars().count() as u16 + 1,
+                            false => x,
+                        };
+                        counted.insert(
+                            (origin.name.clone(), node.name.clone()),
+                            (Rect::new(x, area.y, 1, 1), count(&origin.name, &node.name)),
+                        );
+                        if long_edge_labels {
+                            let label = ConnectionWidget::new(
+                                ConnectionSprite::Incoming(origin.name.clone()),
+                                style,
+                            );
+                            let (x, y) = self.place(node);
+                            let label_area = label.sprite.get_area().offset(Offset {
+                                x: x as i32,
+                                y: y as i32 - 1,
+                            });
+                            longer_connections.push((label_area, label));
+                        }
+                        longer_connections.push((area, con_widget))
+                    }
+                    _ => {
+                        let pair = match origin.name < node.name {
+                            true => (origin.name.clone(), node.name.clone()),
+                            false => (node.name.clone(), origin.name.clone()),
+                        };
+                        let sent = counted.get(&pair).map_or(0, |c| c.1);
+                        let sent = sent + count(&origin.name, &node.name);
+                        counted.insert(pair.clone(), (area, sent));
+                        // Under the nodes for horizontal connections, right of
+                        // the middle for the others.
+                        let position = match area.height {
+                            1 => Position::new(area.x, area.y + NODE_HEIGHT - NODE_HEIGHT / 2),
+                            _ => Position::new(area.right(), area.y + area.height / 2),
+                        };
+                        let entry = weights.entry(pair).or_insert((position, vec![]));
+                        // The weight from the first node of the pair goes first.
+                        match origin.name < node.name {
+                            true => entry.1.insert(0, *connection.weight),
+                            false => entry.1.push(*connection.weight),
+                        }
+                        con_widget.render(area, buf)
+                    }
                 }
             }
         }
@@ -253,6 +1302,40 @@ impl NodeGrid {
         for (area, connection) in longer_connections {
             connection.render(area, buf);
         }
+        if message_counts {
+            self.render_message_counts(buf, counted.into_values(), theme);
+        }
+        if edge_weights {
+            let style = Style::default().fg(theme.connection).italic();
+            for (position, mut weights) in weights.into_values() {
+                weights.dedup();
+                let label: Vec<String> = weights.iter().map(|w| w.to_string()).collect();
+                buf.set_string(position.x, position.y, label.join("/"), style);
+            }
+        }
+    }
+
+    /// Writes the number of messages in the middle of every connection which
+    /// carried any, or after the name of the other node for long connections.
+    /// Connections carrying at least half as many as the busiest
+    /// one are drawn in the busy color of the theme.
+    fn render_message_counts(
+        &self,
+        buf: &mut Buffer,
+        counted: impl Iterator<Item = (Rect, usize)>,
+        theme: &Theme,
+    ) {
+        let counted: Vec<(Rect, usize)> = counted.filter(|(_, sent)| *sent > 0).collect();
+        let busiest = counted.iter().map(|c| c.1).max().unwrap_or_default();
+        for (area, sent) in counted {
+            let style = match sent * 2 >= busiest {
+                true => Style::default().fg(theme.busy).bold(),
+                false => Style::default().bold(),
+            };
+            let x = area.x + area.width / 2;
+            let y = area.y + area.height / 2;
+            buf.set_string(x, y, sent.to_string(), style);
+        }
     }
 }
 
@@ -261,15 +1344,19 @@ impl Widget for NodeGrid {
     where
         Self: Sized,
     {
-        self.render_connections(buf);
-        self.render_nodes(buf);
-        self.render_floating_nodes(buf);
+        let theme = Theme::default();
+        self.render_connections(buf, None, false, false, false, &theme);
+        self.render_nodes(buf, false, false, false, NodeBorder::Solid, &theme);
+        self.render_floating_nodes(buf, false, false, NodeBorder::Solid, &theme);
     }
 }
 
 impl<'a> NodeGridDisplay<'a> {
     pub fn new(grid: NodeGrid) -> Self {
-        Self { grid, block: None }
+        Self {
+            grid,
+            ..Default::default()
+        }
     }
 
     /// Surrounds the `NodeGrid` with a `Block`.
@@ -277,6 +1364,62 @@ impl<'a> NodeGridDisplay<'a> {
         self.block = Some(block);
         self
     }
+
+    /// Draws connections as the channels of the given kind.
+    pub fn channels(mut self, channels: Option<Channels>) -> Self {
+        self.channels = channels;
+        self
+    }
+
+    pub fn long_edge_labels(mut self, long_edge_labels: bool) -> Self {
+        self.long_edge_labels = long_edge_labels;
+        self
+    }
+
+    pub fn message_counts(mut self, message_counts: bool) -> Self {
+        self.message_counts = message_counts;
+        self
+    }
+
+    pub fn edge_weights(mut self, edge_weights: bool) -> Self {
+        self.edge_weights = edge_weights;
+        self
+    }
+
+    pub fn hide_connections(mut self, hide_connections: bool) -> Self {
+        self.hide_connections = hide_connections;
+        self
+    }
+
+    pub fn compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    pub fn show_degree(mut self, show_degree: bool) -> Self {
+        self.show_degree = show_degree;
+        self
+    }
+
+    pub fn show_clocks(mut self, show_clocks: bool) -> Self {
+        self.show_clocks = show_clocks;
+        self
+    }
+
+    pub fn weighted_size(mut self, weighted_size: bool) -> Self {
+        self.weighted_size = weighted_size;
+        self
+    }
+
+    pub fn node_border(mut self, node_border: NodeBorder) -> Self {
+        self.node_border = node_border;
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
 }
 
 impl Widget for NodeGridDisplay<'_> {
@@ -284,7 +1427,7 @@ impl Widget for NodeGridDisplay<'_> {
     where
         Self: Sized,
     {
-        self.grid.render(area, buf);
+        self.render_scrolled(area, buf);
         self.block.render(area, buf);
     }
 }
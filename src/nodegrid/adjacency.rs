@@ -0,0 +1,95 @@
+use anyhow::{Result, anyhow};
+
+use crate::{
+    location::Location,
+    node::{
+        Node,
+        connection::{Connection, DEFAULT_WEIGHT},
+    },
+    nodegrid::NodeGrid,
+};
+
+impl NodeGrid {
+    /// Reads a grid from lines like `a: b 2.0, c 1.5`, each naming a node and
+    /// its outgoing connections. A connection without a weight gets the
+    /// default one, and nodes only named as a neighbour are created as well.
+    /// Nodes are laid out in a square, in the order they were first named.
+    pub(crate) fn from_adjacency(text: &str) -> Result<NodeGrid> {
+        let mut nodes: Vec<Node> = vec![];
+        let mut declared: Vec<String> = vec![];
+        for (number, line) in text.lines().enumerate().map(|(i, l)| (i + 1, l)) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (name, neighbours) = line
+                .split_once(':')
+                .ok_or_else(|| anyhow!("No ':' after the node name on line {number}."))?;
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(anyhow!("No node name on line {number}."));
+            }
+            if declared.iter().any(|d| d == name) {
+                return Err(anyhow!("Node {name} is declared again on line {number}."));
+            }
+            declared.push(name.to_string());
+            let mut connections = vec![];
+            for neighbour in neighbours.split(',').filter(|n| !n.trim().is_empty()) {
+                let connection = parse_connection(neighbour, number)?;
+                if connection.other == name {
+                    return Err(anyhow!("Node {name} connects to itself on line {number}."));
+                }
+                connections.push(connection);
+            }
+            named(&mut nodes, name);
+            for connection in connections {
+                named(&mut nodes, &connection.other);
+                named(&mut nodes, name).add_connection(&connection);
+            }
+        }
+
+        let columns = (nodes.len() as f64).sqrt().ceil().max(1.0) as usize;
+        for (index, node) in nodes.iter_mut().enumerate() {
+            node.id = index + 1;
+            node.location = Location::new((index % columns) as u16, (index / columns) as u16);
+        }
+        Ok(NodeGrid {
+            nodes,
+            ..Default::default()
+        })
+    }
+}
+
+/// Parses a neighbour like `b 2.0`, or just `b` for the default weight.
+fn parse_connection(neighbour: &str, number: usize) -> Result<Connection> {
+    let mut parts = neighbour.split_whitespace();
+    let other = parts.next().unwrap_or_default();
+    let weight = match parts.next() {
+        Some(weight) => weight
+            .parse::<f64>()
+            .ok()
+            .filter(|w| w.is_finite())
+            .ok_or_else(|| anyhow!("Malformed weight {weight:?} on line {number}."))?,
+        None => DEFAULT_WEIGHT,
+    };
+    if let Some(extra) = parts.next() {
+        return Err(anyhow!(
+            "Unexpected {extra:?} after the weight on line {number}."
+        ));
+    }
+    Ok(Connection::new(other.to_string(), weight))
+}
+
+/// The node called `name`, added at the end if there is none yet.
+fn named<'a>(nodes: &'a mut Vec<Node>, name: &str) -> &'a mut Node {
+    let index = match nodes.iter().position(|n| n.name == name) {
+        Some(index) => index,
+        None => {
+            nodes.push(Node {
+                name: name.to_string(),
+                ..Default::default()
+            });
+            nodes.len() - 1
+        }
+    };
+    &mut nodes[index]
+}
@@ -1,28 +1,202 @@
 use anyhow::{Ok, Result, anyhow};
 use displaydoc::Display;
 use rand::{
-    random_range,
+    Rng, SeedableRng, random_range,
+    rngs::StdRng,
     seq::{IndexedRandom, IteratorRandom},
 };
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::VecDeque,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
     fmt::{Display, format},
     ops::{Deref, DerefMut},
 };
+use strum::EnumIs;
 
 use crate::node::{Node, connection};
 use crate::{NodeGrid, SelectedAlgorithm};
 
+mod causality;
 mod elections;
+mod graphs;
+mod mutex;
+mod routing;
 mod snapshots;
+mod termination;
+#[cfg(test)]
+mod tests;
+mod waves;
+
+/// Routine lines, about single messages being sent or received, are indented
+/// under the milestones of a run.
+const ROUTINE_INDENT: &str = "  ";
+
+fn log_routine(logger: &mut Vec<String>, line: String) {
+    logger.push(format!("{ROUTINE_INDENT}{line}"));
+}
 
 fn log_sent_messages<T: Display>(messages: &VecDeque<T>, logger: &mut Vec<String>) {
     for mesg in messages.iter() {
-        logger.push(format!("Sent {mesg}."));
+        log_routine(logger, format!("Sent {mesg}."));
+    }
+}
+
+/// How much of a run is logged.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIs)]
+pub(crate) enum Verbosity {
+    /// Every message sent and received.
+    #[default]
+    Full,
+    /// Only milestones, like snapshots taken or leaders declared.
+    Milestones,
+}
+
+impl Verbosity {
+    /// Drops the routine lines logged from `start` on, unless everything is
+    /// logged.
+    pub(crate) fn apply(self, logger: &mut Vec<String>, start: usize) {
+        if self.is_milestones() {
+            let mut index = 0;
+            logger.retain(|line| {
+                index += 1;
+                index <= start || !line.starts_with(ROUTINE_INDENT)
+            });
+        }
     }
 }
 
-#[derive(Debug, Default)]
+/// Where a sent message is put in the queue, which is delivered from the front.
+/// Chosen for a run to override the channels of the algorithm.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum Delivery {
+    /// in sending order
+    FrontFifo,
+    /// in random order
+    RandomInsert,
+    /// last sent first
+    LifoStack,
+    /// by Lamport timestamp
+    PriorityByTimestamp,
+}
+
+/// What happened to a message, as recorded in the message trace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TraceEvent {
+    Sent,
+    Delivered,
+    Dropped,
+}
+
+/// What a single step of a run did.
+#[derive(Debug, Clone, PartialEq, Eq, EnumIs)]
+pub(crate) enum Step {
+    /// The named node initiated the run.
+    Initiated(String),
+    /// The message was delivered and handled.
+    Delivered(String),
+    /// The named node left the critical section, with no message to deliver.
+    Left(String),
+    /// The run stopped at the message limit.
+    Truncated,
+    /// Nothing is left to do.
+    Finished,
+}
+
+impl Step {
+    /// Whether the run has no steps left after this one.
+    pub(crate) fn is_last(&self) -> bool {
+        self.is_truncated() || self.is_finished()
+    }
+}
+
+/// State a node ended a run in which is worth telling apart at a glance, so
+/// it is drawn in its own color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NodeDisplayState {
+    /// Elected leader.
+    Leader,
+    /// Knocked out of an election.
+    Passive,
+    /// Took part in a snapshot.
+    Recorded,
+}
+
+/// A run kept between steps, so it can be advanced one event at a time.
+pub(crate) trait SteppedRun: std::fmt::Debug {
+    fn advance(&mut self, logger: &mut Vec<String>) -> Step;
+    /// Logs the outcome of a run with no steps left.
+    fn conclude(&mut self, logger: &mut Vec<String>) -> Result<()>;
+    /// Keeps the state of the run so far on `grid`, to draw it.
+    fn store(&self, grid: &mut NodeGrid);
+    /// Number of messages sent but not yet delivered.
+    fn in_transit(&self) -> usize;
+
+    /// Runs every step left, then logs the outcome.
+    fn run(&mut self, logger: &mut Vec<String>) -> Result<()> {
+        while !self.advance(logger).is_last() {}
+        self.conclude(logger)
+    }
+}
+
+/// A single message event of a run, independent of the human readable log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct TraceRecord {
+    pub(crate) event: TraceEvent,
+    pub(crate) sender: String,
+    pub(crate) destination: String,
+    pub(crate) kind: String,
+    /// Number of messages delivered before this event.
+    pub(crate) step: usize,
+}
+
+/// Counts kept during a run, to compare algorithms by more than their log.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Metrics {
+    /// Messages sent, including those dropped by the partition.
+    sent: usize,
+    /// Most messages in transit at once.
+    max_queue: usize,
+}
+
+/// Most random basic messages per node the snapshot traffic can be set to.
+pub(crate) const MAX_TRAFFIC: usize = 100;
+
+/// Random basic messages the snapshot algorithms send, so there is something
+/// in transit to record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Traffic {
+    /// Sent before the initiator starts the snapshot, and again right after.
+    around_start: usize,
+    /// Sent whenever a delivered message is answered.
+    per_response: usize,
+}
+
+impl Default for Traffic {
+    fn default() -> Self {
+        Traffic {
+            around_start: 5,
+            per_response: 3,
+        }
+    }
+}
+
+impl Traffic {
+    /// `intensity` messages for every node around the start, and `intensity`
+    /// after every answer, so larger grids get as busy as small ones. None if
+    /// the intensity is over [`MAX_TRAFFIC`].
+    fn scaled(intensity: usize, nodes: usize) -> Option<Self> {
+        if intensity > MAX_TRAFFIC {
+            return None;
+        }
+        Some(Traffic {
+            around_start: intensity.checked_mul(nodes)?,
+            per_response: intensity,
+        })
+    }
+}
+
+#[derive(Debug)]
 struct Algorithm<N, M>
 where
     N: NodeLike,
@@ -30,6 +204,27 @@ where
 {
     nodes: Vec<N>,
     messages: VecDeque<M>,
+    trace: Vec<TraceRecord>,
+    step: usize,
+    metrics: Metrics,
+    critical_section: mutex::CriticalSection,
+    /// No random processing, and initiators and delivery order are picked
+    /// deterministically, so the run is the same every time.
+    frozen: bool,
+    /// Overrides the delivery order of the channels, if set.
+    delivery: Option<Delivery>,
+    /// The run stops after delivering this many messages, if set.
+    message_limit: Option<usize>,
+    traffic: Traffic,
+    /// Connections cut for the run, as (from, to) pairs.
+    partition: BTreeSet<(String, String)>,
+    /// Nodes yet to initiate the run, in order.
+    initiators: VecDeque<String>,
+    /// Weight of every node, if the initiator is picked by weight.
+    initiator_weights: Option<HashMap<String, f64>>,
+    /// Source of every random choice in the run, so a seeded run can be
+    /// repeated exactly.
+    rng: StdRng,
 }
 
 impl<N, M> Algorithm<N, M>
@@ -42,54 +237,310 @@ where
         nodes
     }
 
+    /// Connections to nodes outside of `nodes`, or back to the node itself, are
+    /// dropped.
     fn new(nodes: &[Node]) -> Algorithm<N, M> {
+        let names: HashSet<&str> = nodes.iter().map(|n| n.name.as_str()).collect();
+        let nodes: Vec<Node> = nodes
+            .iter()
+            .map(|n| {
+                let mut node = n.clone();
+                node.connections
+                    .retain(|c| c.other != n.name && names.contains(c.other.as_str()));
+                node
+            })
+            .collect();
         Self {
-            nodes: Self::wrap_nodes(nodes),
-            ..Default::default()
+            nodes: Self::wrap_nodes(&nodes),
+            messages: VecDeque::new(),
+            trace: vec![],
+            step: 0,
+            metrics: Metrics::default(),
+            critical_section: mutex::CriticalSection::default(),
+            frozen: false,
+            delivery: None,
+            message_limit: None,
+            traffic: Traffic::default(),
+            partition: BTreeSet::new(),
+            initiators: VecDeque::new(),
+            initiator_weights: None,
+            rng: StdRng::from_os_rng(),
         }
     }
 
-    fn choose_initiator(&self, logger: &mut Vec<String>) -> String {
-        let initiator = self
-            .nodes
-            .iter()
-            .choose(&mut rand::rng())
-            .unwrap()
-            .name_clone();
+    fn choose_initiator(&mut self, logger: &mut Vec<String>) -> String {
+        if let Some(weights) = self.initiator_weights.clone() {
+            return self.choose_initiator_weighted(
+                |name| weights.get(name).copied().unwrap_or_default(),
+                logger,
+            );
+        }
+        let initiator = match self.frozen {
+            true => self.nodes.first(),
+            false => self.nodes.iter().choose(&mut self.rng),
+        }
+        .unwrap()
+        .name_clone();
         logger.push(format!("Choose {} as initator.", initiator));
         initiator
     }
 
-    fn choose_initiator_multiple(&self, amount: usize, logger: &mut Vec<String>) -> Vec<String> {
-        let initiators: Vec<String> = self
-            .nodes
-            .iter()
-            .choose_multiple(&mut rand::rng(), amount)
-            .iter()
-            .map(|&n| n.name_clone())
-            .collect();
+    fn choose_initiator_multiple(
+        &mut self,
+        amount: usize,
+        logger: &mut Vec<String>,
+    ) -> Vec<String> {
+        let initiators: Vec<String> = match self.frozen {
+            true => self.nodes.iter().take(amount).collect(),
+            false => self.nodes.iter().choose_multiple(&mut self.rng, amount),
+        }
+        .iter()
+        .map(|&n| n.name_clone())
+        .collect();
         logger.push(format!("Choose {:?} as initator.", initiators));
         initiators
     }
 
-    fn random_node(&mut self) -> &mut N {
-        self.nodes.iter_mut().choose(&mut rand::rng()).unwrap()
+    /// Picks the initiator with a chance proportional to its `weight`, such as
+    /// `NodeGrid::incident_weight`. Frozen runs take the heaviest node, and
+    /// without any weight every node is as likely.
+    fn choose_initiator_weighted(
+        &mut self,
+        weight: impl Fn(&str) -> f64,
+        logger: &mut Vec<String>,
+    ) -> String {
+        let weights: Vec<(&N, f64)> = self.nodes.iter().map(|n| (n, weight(n.name()))).collect();
+        logger.push(format!(
+            "Initiator weights {}.",
+            weights
+                .iter()
+                .map(|(n, w)| format!("{}={w}", n.name()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        let initiator = match self.frozen {
+            true => weights
+                .iter()
+                .reduce(|heaviest, n| if n.1 > heaviest.1 { n } else { heaviest }),
+            false => weights
+                .choose_weighted(&mut self.rng, |(_, w)| *w)
+                .ok()
+                .or_else(|| weights.choose(&mut self.rng)),
+        }
+        .unwrap()
+        .0
+        .name_clone();
+        logger.push(format!("Choose {} as initator.", initiator));
+        initiator
+    }
+
+    /// A random node, along with the rng of the run for the random choices the
+    /// node makes itself.
+    fn random_node(&mut self) -> (&mut N, &mut StdRng) {
+        let node = self.nodes.iter_mut().choose(&mut self.rng).unwrap();
+        (node, &mut self.rng)
     }
 
     fn node_by_name(&mut self, name: String) -> &mut N {
         self.nodes.iter_mut().find(|n| n.name() == name).unwrap()
     }
 
+    /// Queues a sent message according to the delivery chosen for the run, or
+    /// `channels` if none was.
+    /// Messages over a connection cut by the partition are dropped instead.
+    fn enqueue(&mut self, mesg: M, channels: Delivery) {
+        self.record(TraceEvent::Sent, &mesg);
+        self.metrics.sent += 1;
+        let channel = (mesg.sender().to_string(), mesg.destination().to_string());
+        if self.partition.contains(&channel) {
+            self.record(TraceEvent::Dropped, &mesg);
+            return;
+        }
+        match self.delivery.unwrap_or(channels) {
+            Delivery::RandomInsert if self.has_messages() && !self.frozen => {
+                let index = self.rng.random_range(0..self.messages.len());
+                self.messages.insert(index, mesg);
+            }
+            Delivery::FrontFifo | Delivery::RandomInsert => self.messages.push_back(mesg),
+            Delivery::LifoStack => self.messages.push_front(mesg),
+            Delivery::PriorityByTimestamp => {
+                let index = self
+                    .messages
+                    .partition_point(|m| m.timestamp() <= mesg.timestamp());
+                self.messages.insert(index, mesg);
+            }
+        }
+        self.metrics.max_queue = self.metrics.max_queue.max(self.messages.len());
+    }
+
+    /// Logs the summary of the run so far, ahead of its outcome.
+    fn log_metrics(&self, logger: &mut Vec<String>) {
+        logger.push(format!(
+            "Run summary: {} messages sent, at most {} in transit, {} steps taken.",
+            self.metrics.sent, self.metrics.max_queue, self.step
+        ));
+    }
+
+    /// Whether the run has delivered as many messages as it may, in which case
+    /// it is logged that the run stops early.
+    fn limit_reached(&self, logger: &mut Vec<String>) -> bool {
+        match self.message_limit {
+            Some(limit) if self.step >= limit => {
+                logger.push(format!(
+                    "Stopped after delivering {limit} messages, the run is truncated."
+                ));
+                true
+            }
+            _ => false,
+        }
+    }
+
     fn pop_mesg(&mut self) -> Option<M> {
-        self.messages.pop_front()
+        let mesg = self.messages.pop_front()?;
+        self.record(TraceEvent::Delivered, &mesg);
+        self.step += 1;
+        Some(mesg)
+    }
+
+    fn record(&mut self, event: TraceEvent, mesg: &M) {
+        self.trace.push(TraceRecord {
+            event,
+            sender: mesg.sender().to_string(),
+            destination: mesg.destination().to_string(),
+            kind: mesg.kind(),
+            step: self.step,
+        });
     }
 
     fn has_messages(&self) -> bool {
         !self.messages.is_empty()
     }
+
+    /// Messages still queued for every node, per incoming channel in the order
+    /// they would be delivered.
+    fn inboxes(&self) -> HashMap<String, BTreeMap<String, Vec<String>>> {
+        let mut inboxes: HashMap<String, BTreeMap<String, Vec<String>>> = HashMap::new();
+        for mesg in self.messages.iter() {
+            inboxes
+                .entry(mesg.destination().to_string())
+                .or_default()
+                .entry(mesg.sender().to_string())
+                .or_default()
+                .push(mesg.to_string());
+        }
+        inboxes
+    }
+
+    /// Lamport clock of every node which keeps one, keyed by node name.
+    fn clocks(&self) -> HashMap<String, usize> {
+        self.nodes
+            .iter()
+            .filter_map(|n| n.clock().map(|c| (n.name_clone(), c.0)))
+            .collect()
+    }
+
+    /// Display state of every node which has one, keyed by node name.
+    fn display_states(&self) -> HashMap<String, NodeDisplayState> {
+        self.nodes
+            .iter()
+            .filter_map(|n| n.display_state().map(|s| (n.name_clone(), s)))
+            .collect()
+    }
+
+    /// Final state of every node, keyed by node name.
+    fn results(&self) -> HashMap<String, String> {
+        self.nodes
+            .iter()
+            .map(|n| (n.name_clone(), n.result()))
+            .collect()
+    }
+}
+
+impl<N, M> Algorithm<N, M>
+where
+    N: StepNode<M>,
+    M: Mesg,
+{
+    /// Lets the next initiator initiate the run, or else delivers the next
+    /// message.
+    fn step(&mut self, logger: &mut Vec<String>) -> Step {
+        if let Some(initiator) = self.initiators.pop_front() {
+            let sent = self.node_by_name(initiator.clone()).initiate(logger);
+            for mesg in sent {
+                self.enqueue(mesg, N::CHANNELS);
+            }
+            return Step::Initiated(initiator);
+        }
+        if !self.has_messages() || self.nodes.iter().any(N::ends_run) {
+            return Step::Finished;
+        }
+        if self.limit_reached(logger) {
+            return Step::Truncated;
+        }
+        let mesg = self.pop_mesg().unwrap();
+        let delivered = mesg.to_string();
+        let sent = self
+            .node_by_name(mesg.destination().to_string())
+            .receive(mesg, logger);
+        for mesg in sent {
+            self.enqueue(mesg, N::CHANNELS);
+        }
+        Step::Delivered(delivered)
+    }
 }
 
-trait Mesg: Clone + Default + Display {}
+impl<N, M> SteppedRun for Algorithm<N, M>
+where
+    N: StepNode<M> + std::fmt::Debug,
+    M: Mesg + std::fmt::Debug,
+{
+    fn advance(&mut self, logger: &mut Vec<String>) -> Step {
+        self.step(logger)
+    }
+    fn conclude(&mut self, logger: &mut Vec<String>) -> Result<()> {
+        self.log_metrics(logger);
+        N::conclude(self, logger)
+    }
+    fn store(&self, grid: &mut NodeGrid) {
+        grid.store_run(self);
+    }
+    fn in_transit(&self) -> usize {
+        self.messages.len()
+    }
+}
+
+/// Nodes of an algorithm which can be run one step at a time.
+trait StepNode<M: Mesg>: NodeLike {
+    /// Where the channels of the algorithm put sent messages in the queue.
+    const CHANNELS: Delivery;
+
+    /// Messages sent when the node initiates the run.
+    fn initiate(&mut self, logger: &mut Vec<String>) -> VecDeque<M>;
+
+    /// Handles a delivered message, returning the messages sent in response.
+    fn receive(&mut self, mesg: M, logger: &mut Vec<String>) -> VecDeque<M>;
+
+    /// Whether the node has brought the run to its end, like an elected leader.
+    fn ends_run(&self) -> bool {
+        false
+    }
+
+    /// Logs the outcome of `run` once it has no steps left, failing if it
+    /// went wrong.
+    fn conclude(run: &Algorithm<Self, M>, logger: &mut Vec<String>) -> Result<()>;
+}
+
+trait Mesg: Clone + Default + Display {
+    fn sender(&self) -> &str;
+    fn destination(&self) -> &str;
+    /// The kind of message, as shown in the log.
+    fn kind(&self) -> String;
+    /// Lamport timestamp, for messages which carry one. Messages without one
+    /// are delivered in sending order when ordering by timestamp.
+    fn timestamp(&self) -> Option<LamportsClock> {
+        None
+    }
+}
 
 /// Messages through a channel are received by a node in the same order as they
 /// were sent.
@@ -97,6 +548,122 @@ trait Fifo: Mesg {}
 /// Messages can be received in any order, irrespective of the sending order.
 trait NonFifo: Mesg {}
 
+/// The channel assumption an algorithm's messages are sent under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Channels {
+    Fifo,
+    NonFifo,
+}
+
+impl SelectedAlgorithm {
+    pub(crate) fn channels(self) -> Channels {
+        match self {
+            SelectedAlgorithm::ChandyLamport => Channels::Fifo,
+            SelectedAlgorithm::LaiYang => Channels::NonFifo,
+            SelectedAlgorithm::ChangRoberts => Channels::NonFifo,
+            SelectedAlgorithm::Franklin => Channels::NonFifo,
+            SelectedAlgorithm::Peterson => Channels::Fifo,
+            SelectedAlgorithm::Luby => Channels::NonFifo,
+            SelectedAlgorithm::Echo => Channels::NonFifo,
+            SelectedAlgorithm::Tarry => Channels::NonFifo,
+            SelectedAlgorithm::RicartAgrawala => Channels::NonFifo,
+            SelectedAlgorithm::DijkstraScholten => Channels::NonFifo,
+            SelectedAlgorithm::ChandyMisra => Channels::NonFifo,
+            SelectedAlgorithm::CausalOrder => Channels::NonFifo,
+        }
+    }
+
+    pub(crate) fn is_election(self) -> bool {
+        matches!(
+            self,
+            SelectedAlgorithm::ChangRoberts
+                | SelectedAlgorithm::Franklin
+                | SelectedAlgorithm::Peterson
+        )
+    }
+
+    /// Whether the algorithm can be run one step at a time. Franklin and Luby
+    /// work in rounds rather than by single messages, and Dijkstra-Scholten
+    /// is not split into steps yet.
+    pub(crate) fn steppable(self) -> bool {
+        !matches!(
+            self,
+            SelectedAlgorithm::Franklin
+                | SelectedAlgorithm::Luby
+                | SelectedAlgorithm::DijkstraScholten
+        )
+    }
+
+    /// Outline of the algorithm, to follow along with the log of a run.
+    pub(crate) fn pseudocode(self) -> &'static str {
+        match self {
+            SelectedAlgorithm::ChandyLamport => snapshots::CHANDY_LAMPORT_PSEUDOCODE,
+            SelectedAlgorithm::LaiYang => snapshots::LAI_YANG_PSEUDOCODE,
+            SelectedAlgorithm::ChangRoberts => elections::CHANG_ROBERTS_PSEUDOCODE,
+            SelectedAlgorithm::Franklin => elections::FRANKLIN_PSEUDOCODE,
+            SelectedAlgorithm::Peterson => elections::PETERSON_PSEUDOCODE,
+            SelectedAlgorithm::Luby => graphs::PSEUDOCODE,
+            SelectedAlgorithm::Echo => waves::ECHO_PSEUDOCODE,
+            SelectedAlgorithm::Tarry => waves::TARRY_PSEUDOCODE,
+            SelectedAlgorithm::RicartAgrawala => mutex::RICART_AGRAWALA_PSEUDOCODE,
+            SelectedAlgorithm::DijkstraScholten => termination::DIJKSTRA_SCHOLTEN_PSEUDOCODE,
+            SelectedAlgorithm::ChandyMisra => routing::CHANDY_MISRA_PSEUDOCODE,
+            SelectedAlgorithm::CausalOrder => causality::CAUSAL_ORDER_PSEUDOCODE,
+        }
+    }
+
+    /// Worst case message complexity, for the algorithms where it is known.
+    pub(crate) fn complexity(self) -> Option<&'static str> {
+        match self {
+            SelectedAlgorithm::ChandyLamport => None,
+            SelectedAlgorithm::LaiYang => None,
+            SelectedAlgorithm::ChangRoberts => Some("n²"),
+            SelectedAlgorithm::Franklin => Some("2n(⌊log n⌋+1)"),
+            SelectedAlgorithm::Peterson => Some("2n(⌊log n⌋+1)"),
+            SelectedAlgorithm::Luby => None,
+            SelectedAlgorithm::Echo => Some("2E"),
+            SelectedAlgorithm::Tarry => Some("2E"),
+            SelectedAlgorithm::RicartAgrawala => Some("2n(n-1)"),
+            SelectedAlgorithm::DijkstraScholten => None,
+            SelectedAlgorithm::ChandyMisra => None,
+            SelectedAlgorithm::CausalOrder => Some("3E"),
+        }
+    }
+
+    /// Checks the grid is one the algorithm can run on, listing every violated
+    /// precondition instead of stopping at the first.
+    pub(crate) fn preconditions(self, grid: &NodeGrid) -> Result<()> {
+        let violations: Vec<String> = grid
+            .unmet_preconditions(self)
+            .into_iter()
+            .map(|issue| issue.problem)
+            .collect();
+        match violations.is_empty() {
+            true => Ok(()),
+            false => Err(anyhow!("Cannot run {self}: {}", violations.join(" "))),
+        }
+    }
+
+    /// Worst case number of messages on a grid with `nodes` nodes and `channels`
+    /// directed channels, which is twice the number of edges `E`.
+    pub(crate) fn message_bound(self, nodes: usize, channels: usize) -> Option<usize> {
+        match self {
+            SelectedAlgorithm::ChandyLamport => None,
+            SelectedAlgorithm::LaiYang => None,
+            SelectedAlgorithm::ChangRoberts => Some(nodes * nodes),
+            SelectedAlgorithm::Franklin | SelectedAlgorithm::Peterson => {
+                Some(2 * nodes * (nodes.checked_ilog2().unwrap_or(0) as usize + 1))
+            }
+            SelectedAlgorithm::Luby => None,
+            SelectedAlgorithm::Echo | SelectedAlgorithm::Tarry => Some(channels),
+            SelectedAlgorithm::RicartAgrawala => Some(2 * nodes * nodes.saturating_sub(1)),
+            SelectedAlgorithm::DijkstraScholten => None,
+            SelectedAlgorithm::ChandyMisra => None,
+            SelectedAlgorithm::CausalOrder => Some(causality::CAUSAL_ORDER_TTL * channels),
+        }
+    }
+}
+
 trait FifoChannels<M: Fifo> {
     fn add_mesg(&mut self, mesg: M);
     fn add_mesg_iter(&mut self, messages: &mut VecDeque<M>);
@@ -108,10 +675,12 @@ where
 {
     /// Add a FIFO message to the back of the queue.
     fn add_mesg(&mut self, mesg: M) {
-        self.messages.push_back(mesg);
+        self.enqueue(mesg, Delivery::FrontFifo);
     }
     fn add_mesg_iter(&mut self, messages: &mut VecDeque<M>) {
-        self.messages.append(messages);
+        for mesg in messages.drain(..) {
+            self.add_mesg(mesg);
+        }
     }
 }
 
@@ -124,14 +693,10 @@ where
     N: NodeLike,
     M: NonFifo,
 {
-    /// Add a message in a random index of the message queue.
+    /// Add a message in a random index of the message queue, or to the back
+    /// when the run is frozen.
     fn add_mesg(&mut self, mesg: M) {
-        if self.has_messages() {
-            let index = random_range(0..self.messages.len());
-            self.messages.insert(index, mesg);
-        } else {
-            self.messages.push_back(mesg);
-        }
+        self.enqueue(mesg, Delivery::RandomInsert);
     }
     fn add_mesg_iter(&mut self, messages: &mut VecDeque<M>) {
         for mesg in messages.drain(..) {
@@ -145,6 +710,16 @@ trait NodeLike: for<'a> From<&'a Node> + Default {
     fn name_clone(&self) -> String {
         self.name().to_string()
     }
+    /// Short description of the node's state, kept on the `NodeGrid` after a run.
+    fn result(&self) -> String;
+    /// Current Lamport clock, for nodes of algorithms which keep one.
+    fn clock(&self) -> Option<LamportsClock> {
+        None
+    }
+    /// State the node is drawn in, for algorithms which tag their nodes.
+    fn display_state(&self) -> Option<NodeDisplayState> {
+        None
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -209,21 +784,250 @@ trait LamportsMessage: Mesg {
     fn time(&self) -> LamportsClock;
 }
 
+/// A counter for every node, ordering two events only if one could have
+/// affected the other. Nodes without a counter are at 0.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct VectorClock(BTreeMap<String, usize>);
+impl VectorClock {
+    /// Counts an event at `node`.
+    fn tick(&mut self, node: &str) -> Self {
+        *self.0.entry(node.to_string()).or_default() += 1;
+        self.clone()
+    }
+
+    /// Takes the latest of both clocks for every node.
+    fn merge(&mut self, other: &VectorClock) {
+        for (node, count) in other.0.iter() {
+            let own = self.0.entry(node.clone()).or_default();
+            *own = (*own).max(*count);
+        }
+    }
+
+    fn get(&self, node: &str) -> usize {
+        self.0.get(node).copied().unwrap_or_default()
+    }
+
+    /// Whether the event at this time could have affected the one at `other`.
+    fn happens_before(&self, other: &VectorClock) -> bool {
+        self.0.iter().all(|(node, count)| *count <= other.get(node))
+            && other.0.iter().any(|(node, count)| self.get(node) < *count)
+    }
+}
+
+impl Display for VectorClock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts: Vec<String> = self.0.iter().map(|(n, c)| format!("{n}={c}")).collect();
+        write!(f, "VC({})", counts.join(", "))
+    }
+}
+
 impl NodeGrid {
+    /// Hands the settings of the run to a new `algorithm`.
+    fn configure<N: NodeLike, M: Mesg>(&self, algorithm: &mut Algorithm<N, M>) {
+        algorithm.frozen = self.frozen;
+        algorithm.delivery = self.delivery;
+        algorithm.message_limit = self.message_limit;
+        if let Some(traffic) = self
+            .traffic
+            .and_then(|intensity| Traffic::scaled(intensity, algorithm.nodes.len()))
+        {
+            algorithm.traffic = traffic;
+        }
+        algorithm.partition = self.partition.clone();
+        if self.weighted_initiator {
+            algorithm.initiator_weights = Some(
+                algorithm
+                    .nodes
+                    .iter()
+                    .map(|n| (n.name_clone(), self.incident_weight(n.name())))
+                    .collect(),
+            );
+        }
+        if let Some(seed) = self.seed {
+            algorithm.rng = StdRng::seed_from_u64(seed);
+        }
+    }
+
+    /// Keeps what a finished run learned about the grid.
+    fn store_run<N: NodeLike, M: Mesg>(&mut self, algorithm: &Algorithm<N, M>) {
+        self.results = algorithm.results();
+        self.display_states = algorithm.display_states();
+        self.trace = algorithm.trace.clone();
+        self.inboxes = algorithm.inboxes();
+        self.critical = algorithm.critical_section.holder().map(String::from);
+        self.clocks = algorithm.clocks();
+    }
+
+    /// Keeps what was stored by a run on `subgrid`, as if it ran on this grid.
+    fn store_subgrid_run(&mut self, subgrid: NodeGrid) {
+        self.results = subgrid.results;
+        self.display_states = subgrid.display_states;
+        self.trace = subgrid.trace;
+        self.inboxes = subgrid.inboxes;
+        self.critical = subgrid.critical;
+        self.clocks = subgrid.clocks;
+    }
+
+    /// Number of messages sent during the last run.
+    pub(crate) fn messages_sent(&self) -> usize {
+        self.trace
+            .iter()
+            .filter(|r| r.event == TraceEvent::Sent)
+            .count()
+    }
+
+    pub(crate) fn channel_count(&self) -> usize {
+        self.nodes.iter().map(|n| n.connections.len()).sum()
+    }
+
+    /// Number of nodes and channels a run is on, which are those between the
+    /// included nodes if any are.
+    pub(crate) fn run_size(&self) -> (usize, usize) {
+        match self.included.is_empty() {
+            true => (self.nodes.len(), self.channel_count()),
+            false => {
+                let subgrid = self.included_subgrid();
+                (subgrid.nodes.len(), subgrid.channel_count())
+            }
+        }
+    }
+
+    /// Runs `algorithm` with every random choice drawn from `seed`, so the same
+    /// grid and seed give the same run.
+    pub(crate) fn run_algorithm_seeded(
+        &mut self,
+        algorithm: SelectedAlgorithm,
+        seed: u64,
+        logger: &mut Vec<String>,
+    ) -> Result<()> {
+        let unseeded = self.seed.replace(seed);
+        let result = self.run_algorithm(algorithm, logger);
+        self.seed = unseeded;
+        result
+    }
+
     pub fn run_algorithm(
         &mut self,
         algorithm: SelectedAlgorithm,
         logger: &mut Vec<String>,
     ) -> Result<()> {
-        let result = match algorithm {
-            SelectedAlgorithm::ChandyLamport => self.chandy_lamport(logger),
-            SelectedAlgorithm::LaiYang => self.lai_yang(logger),
-            SelectedAlgorithm::ChangRoberts => self.chang_roberts(logger),
+        self.check_delivery(algorithm, logger)?;
+        self.log_settings(logger);
+        let result = if self.included.is_empty() {
+            self.start_algorithm(algorithm, logger)
+        } else {
+            let mut subgrid = self.included_subgrid();
+            let excluded: Vec<&str> = self
+                .nodes
+                .iter()
+                .map(|n| n.name.as_str())
+                .filter(|n| !self.included.contains(*n))
+                .collect();
+            logger.push(format!(
+                "Running on included nodes {}.",
+                subgrid
+                    .nodes
+                    .iter()
+                    .map(|n| n.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+            logger.push(format!("Excluded nodes {}.", excluded.join(", ")));
+            let result = subgrid.start_algorithm(algorithm, logger);
+            self.store_subgrid_run(subgrid);
+            result
         };
         if result.is_err() {
             logger.push(format!("{} did not complete.", algorithm));
         }
+        result
+    }
+
+    /// Refuses a delivery order which breaks the FIFO channels `algorithm`
+    /// relies on.
+    fn check_delivery(&self, algorithm: SelectedAlgorithm, logger: &mut Vec<String>) -> Result<()> {
+        match self.delivery {
+            Some(delivery)
+                if algorithm.channels() == Channels::Fifo && delivery != Delivery::FrontFifo =>
+            {
+                let line = format!(
+                    "{algorithm} needs FIFO channels, it can't deliver messages {delivery}."
+                );
+                logger.push(line.clone());
+                Err(anyhow!(line))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Logs the settings a run deviates from the algorithm with.
+    fn log_settings(&self, logger: &mut Vec<String>) {
+        if let Some(seed) = self.seed {
+            logger.push(format!("Random choices are seeded with {seed}."));
+        }
+        if let Some(delivery) = self.delivery {
+            logger.push(format!("Delivering messages {delivery}."));
+        }
+        if !self.partition.is_empty() {
+            logger.push(format!(
+                "Partition cuts {} connections, messages over them are dropped.",
+                self.partition.len()
+            ));
+        }
+    }
+
+    /// Sets up `algorithm` to be run one step at a time, on the included nodes
+    /// if any are.
+    pub(crate) fn start_stepping(
+        &self,
+        algorithm: SelectedAlgorithm,
+        logger: &mut Vec<String>,
+    ) -> Result<Box<dyn SteppedRun>> {
+        if !algorithm.steppable() {
+            return Err(anyhow!("{algorithm} can't be run step by step."));
+        }
+        self.check_delivery(algorithm, logger)?;
+        let subgrid;
+        let grid = match self.included.is_empty() {
+            true => self,
+            false => {
+                subgrid = self.included_subgrid();
+                &subgrid
+            }
+        };
+        grid.log_settings(logger);
+        match algorithm {
+            SelectedAlgorithm::ChandyLamport => grid.chandy_lamport_stepped(logger),
+            SelectedAlgorithm::LaiYang => grid.lai_yang_stepped(logger),
+            SelectedAlgorithm::ChangRoberts => grid.chang_roberts_stepped(logger),
+            SelectedAlgorithm::Peterson => grid.peterson_stepped(logger),
+            SelectedAlgorithm::Echo => grid.echo_stepped(logger),
+            SelectedAlgorithm::Tarry => grid.tarry_stepped(logger),
+            SelectedAlgorithm::RicartAgrawala => grid.ricart_agrawala_stepped(logger),
+            SelectedAlgorithm::ChandyMisra => grid.chandy_misra_stepped(logger),
+            SelectedAlgorithm::CausalOrder => grid.causal_order_stepped(logger),
+            _ => unreachable!("{algorithm} is not steppable"),
+        }
+    }
 
-        Ok(())
+    fn start_algorithm(
+        &mut self,
+        algorithm: SelectedAlgorithm,
+        logger: &mut Vec<String>,
+    ) -> Result<()> {
+        match algorithm {
+            SelectedAlgorithm::ChandyLamport => self.chandy_lamport(logger),
+            SelectedAlgorithm::LaiYang => self.lai_yang(logger),
+            SelectedAlgorithm::ChangRoberts => self.chang_roberts(logger),
+            SelectedAlgorithm::Franklin => self.franklin(logger),
+            SelectedAlgorithm::Peterson => self.peterson(logger),
+            SelectedAlgorithm::Luby => self.luby(logger),
+            SelectedAlgorithm::Echo => self.echo(logger),
+            SelectedAlgorithm::Tarry => self.tarry(logger),
+            SelectedAlgorithm::RicartAgrawala => self.ricart_agrawala(logger),
+            SelectedAlgorithm::DijkstraScholten => self.dijkstra_scholten(logger),
+            SelectedAlgorithm::ChandyMisra => self.chandy_misra(logger),
+            SelectedAlgorithm::CausalOrder => self.causal_order(logger),
+        }
     }
 }
@@ -0,0 +1,239 @@
+use crate::nodegrid::algorithms::*;
+
+pub(super) use causalorder::PSEUDOCODE as CAUSAL_ORDER_PSEUDOCODE;
+pub(super) use causalorder::TTL as CAUSAL_ORDER_TTL;
+
+mod causalorder {
+    use anyhow::Result;
+    use displaydoc::Display;
+    use std::collections::VecDeque;
+
+    use crate::{
+        node::Node,
+        nodegrid::{NodeGrid, algorithms::*},
+    };
+
+    pub(in crate::nodegrid::algorithms) const PSEUDOCODE: &str = "\
+every node:
+    tick own entry of the vector clock
+    send <ping ttl=3> with the clock to every neighbour
+
+on receiving <ping ttl> with clock c:
+    merge c into the vector clock, then tick own entry
+    if c happened before the clock of a ping received earlier:
+        report the causal order broken
+    if ttl > 1:
+        tick, and send <ping ttl-1> to the next neighbour in turn";
+
+    /// Hops every ping makes, so the run ends while still building chains of
+    /// messages which causally follow each other.
+    pub(in crate::nodegrid::algorithms) const TTL: usize = 3;
+
+    #[derive(Debug, Default, Clone)]
+    struct AlgNode {
+        node: Node,
+        clock: VectorClock,
+        /// Sender and clock of every ping received so far.
+        received: Vec<(String, VectorClock)>,
+        /// Pings received ahead of one they causally follow.
+        violations: usize,
+        /// Index of the connection the next forwarded ping goes over.
+        next: usize,
+    }
+
+    #[derive(Debug, Display, Default, Clone)]
+    #[displaydoc("<ping ttl={ttl} {clock}> {sender}->{destination}")]
+    struct Message {
+        sender: String,
+        destination: String,
+        /// Clock of the sender when it sent the ping.
+        clock: VectorClock,
+        ttl: usize,
+    }
+    impl Mesg for Message {
+        fn sender(&self) -> &str {
+            &self.sender
+        }
+        fn destination(&self) -> &str {
+            &self.destination
+        }
+        fn kind(&self) -> String {
+            "ping".to_string()
+        }
+    }
+    impl NonFifo for Message {}
+
+    impl From<&Node> for AlgNode {
+        fn from(node: &Node) -> Self {
+            AlgNode {
+                node: node.clone(),
+                ..Default::default()
+            }
+        }
+    }
+    impl NodeLike for AlgNode {
+        fn name(&self) -> &str {
+            &self.node.name
+        }
+        fn result(&self) -> String {
+            match self.violations {
+                0 => self.clock.to_string(),
+                n => format!("{} with {n} out of order", self.clock),
+            }
+        }
+    }
+
+    impl StepNode<Message> for AlgNode {
+        const CHANNELS: Delivery = Delivery::RandomInsert;
+
+        fn initiate(&mut self, logger: &mut Vec<String>) -> VecDeque<Message> {
+            let destinations: Vec<String> = self
+                .node
+                .connections
+                .iter()
+                .map(|c| c.other.clone())
+                .collect();
+            destinations
+                .into_iter()
+                .map(|destination| self.send(destination, TTL, logger))
+                .collect()
+        }
+
+        fn receive(&mut self, mesg: Message, logger: &mut Vec<String>) -> VecDeque<Message> {
+            self.clock.merge(&mesg.clock);
+            self.clock.tick(&self.node.name);
+            log_routine(
+                logger,
+                format!("{} received {mesg} at {}", self.name(), self.clock),
+            );
+            for (sender, clock) in self.received.iter() {
+                if mesg.clock.happens_before(clock) {
+                    self.violations += 1;
+                    logger.push(format!(
+                        "{} received the ping from {} at {} after one from {sender} it led to.",
+                        self.name(),
+                        mesg.sender,
+                        mesg.clock
+                    ));
+                }
+            }
+            self.received.push((mesg.sender, mesg.clock));
+            if mesg.ttl <= 1 || self.node.connections.is_empty() {
+                return VecDeque::new();
+            }
+            let destination = self.node.connections[self.next % self.node.connections.len()]
+                .other
+                .clone();
+            self.next += 1;
+            VecDeque::from([self.send(destination, mesg.ttl - 1, logger)])
+        }
+
+        /// Logs whether every ping arrived after the pings which led to it.
+        fn conclude(run: &Algorithm<Self, Message>, logger: &mut Vec<String>) -> Result<()> {
+            let violations: usize = run.nodes.iter().map(|n| n.violations).sum();
+            logger.push(match violations {
+                0 => format!("Causal order was kept in all {} deliveries.", run.step),
+                n => format!(
+                    "Causal order was broken {n} times in {} deliveries.",
+                    run.step
+                ),
+            });
+            Ok(())
+        }
+    }
+
+    impl AlgNode {
+        /// A ping stamped with the clock after ticking it for the send.
+        fn send(&mut self, destination: String, ttl: usize, logger: &mut Vec<String>) -> Message {
+            let mesg = Message {
+                sender: self.name_clone(),
+                destination,
+                clock: self.clock.tick(&self.node.name),
+                ttl,
+            };
+            log_routine(logger, format!("Sent {mesg}."));
+            mesg
+        }
+    }
+
+    impl NodeGrid {
+        pub fn causal_order(&mut self, logger: &mut Vec<String>) -> Result<()> {
+            let mut algorithm = self.causal_order_algorithm(logger)?;
+            let result = algorithm.run(logger);
+            self.store_run(&algorithm);
+            result
+        }
+
+        /// Sets up the causal order check to be run one step at a time.
+        pub(in crate::nodegrid::algorithms) fn causal_order_stepped(
+            &self,
+            logger: &mut Vec<String>,
+        ) -> Result<Box<dyn SteppedRun>> {
+            Ok(Box::new(self.causal_order_algorithm(logger)?))
+        }
+
+        /// Sets up the causal order check on the grid, with every node yet to
+        /// send its first pings.
+        fn causal_order_algorithm(
+            &self,
+            logger: &mut Vec<String>,
+        ) -> Result<Algorithm<AlgNode, Message>> {
+            self.check_not_empty(logger)?;
+            let mut algorithm: Algorithm<AlgNode, Message> = Algorithm::new(&self.nodes);
+            self.configure(&mut algorithm);
+            logger.push(format!(
+                "Started causal order check with {} nodes.",
+                algorithm.nodes.len()
+            ));
+            algorithm.initiators = algorithm.nodes.iter().map(|n| n.name_clone()).collect();
+            Ok(algorithm)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// a pings c directly, and then by way of b.
+        fn triangle() -> NodeGrid {
+            let mut nodes: Vec<Node> = ["a", "b", "c"]
+                .into_iter()
+                .map(|name| Node {
+                    name: name.to_string(),
+                    ..Default::default()
+                })
+                .collect();
+            for (from, to) in [(0, "c"), (0, "b"), (1, "c")] {
+                nodes[from].add_connection(&connection::Connection::new(
+                    to.to_string(),
+                    connection::DEFAULT_WEIGHT,
+                ));
+            }
+            NodeGrid {
+                nodes,
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn only_out_of_order_deliveries_break_causal_order() -> Result<()> {
+            let mut grid = triangle();
+            grid.delivery = Some(Delivery::FrontFifo);
+            let mut logger = vec![];
+            grid.causal_order(&mut logger)?;
+            assert!(logger.last().unwrap().starts_with("Causal order was kept"));
+
+            grid.delivery = Some(Delivery::LifoStack);
+            let mut logger = vec![];
+            grid.causal_order(&mut logger)?;
+            assert!(
+                logger
+                    .last()
+                    .unwrap()
+                    .starts_with("Causal order was broken")
+            );
+            assert!(grid.result("c").unwrap().ends_with("out of order"));
+            Ok(())
+        }
+    }
+}
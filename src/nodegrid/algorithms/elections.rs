@@ -11,8 +11,46 @@ use std::{
 };
 
 use crate::node::{Node, connection};
+use strum::IntoEnumIterator;
+
 use crate::{NodeGrid, SelectedAlgorithm, nodegrid::algorithms::*};
 
+pub(super) use changroberts::PSEUDOCODE as CHANG_ROBERTS_PSEUDOCODE;
+pub(super) use franklin::PSEUDOCODE as FRANKLIN_PSEUDOCODE;
+pub(super) use peterson::PSEUDOCODE as PETERSON_PSEUDOCODE;
+
+impl NodeGrid {
+    /// Runs every election algorithm on a copy of the grid, and tabulates the
+    /// messages each one sent against its worst case.
+    pub(crate) fn compare_elections(&self) -> Vec<String> {
+        let nodes = self.nodes.len();
+        let channels = self.channel_count();
+        let mut rows = vec![format!(
+            "{:<14} {:>8} {:>10}",
+            "Algorithm", "Messages", "Worst case"
+        )];
+        for algorithm in SelectedAlgorithm::iter().filter(|a| a.is_election()) {
+            let mut grid = self.clone();
+            if algorithm == SelectedAlgorithm::Franklin {
+                // Franklin sends ids both ways around the ring.
+                grid.add_reverse_connections();
+            }
+            let messages = match grid.run_algorithm(algorithm, &mut vec![]) {
+                Result::Ok(()) => grid.messages_sent().to_string(),
+                Err(_) => "failed".to_string(),
+            };
+            let bound = algorithm
+                .message_bound(nodes, channels)
+                .map_or("-".to_string(), |b| b.to_string());
+            rows.push(format!(
+                "{:<14} {messages:>8} {bound:>10}",
+                algorithm.to_string()
+            ));
+        }
+        rows
+    }
+}
+
 mod changroberts {
     use anyhow::{Ok, Result, anyhow};
     use displaydoc::Display;
@@ -30,17 +68,33 @@ mod changroberts {
         nodegrid::{NodeGrid, SelectedAlgorithm, algorithms::snapshots::*, algorithms::*},
     };
 
+    pub(in crate::nodegrid::algorithms) const PSEUDOCODE: &str = "\
+every node, on a directed ring:
+    send <id> to its successor
+
+on receiving <id>:
+    if id > own id:
+        pass <id> on to the successor
+    if id < own id:
+        drop the message
+    if id == own id:
+        become leader";
+
     #[derive(Debug, Default, Clone)]
     struct AlgNode {
         node: Node,
         state: NodeState,
+        successor: String,
     }
 
-    #[derive(Debug, Default, Clone, EnumIs)]
+    #[derive(Debug, Display, Default, Clone, EnumIs)]
     enum NodeState {
         #[default]
+        /// active
         Active,
+        /// passive
         Passive,
+        /// leader
         Leader,
     }
 
@@ -52,7 +106,17 @@ mod changroberts {
         id: usize,
         kind: MesgKind,
     }
-    impl Mesg for Message {}
+    impl Mesg for Message {
+        fn sender(&self) -> &str {
+            &self.sender
+        }
+        fn destination(&self) -> &str {
+            &self.destination
+        }
+        fn kind(&self) -> String {
+            self.kind.to_string()
+        }
+    }
     impl NonFifo for Message {}
 
     #[derive(Debug, Display, Default, Clone, Copy, PartialEq, Eq, EnumIs)]
@@ -74,6 +138,16 @@ mod changroberts {
         fn name(&self) -> &str {
             &self.node.name
         }
+        fn result(&self) -> String {
+            self.state.to_string()
+        }
+        fn display_state(&self) -> Option<NodeDisplayState> {
+            match self.state {
+                NodeState::Active => None,
+                NodeState::Passive => Some(NodeDisplayState::Passive),
+                NodeState::Leader => Some(NodeDisplayState::Leader),
+            }
+        }
     }
 
     impl Message {
@@ -96,21 +170,50 @@ mod changroberts {
         }
     }
 
+    impl StepNode<Message> for AlgNode {
+        const CHANNELS: Delivery = Delivery::RandomInsert;
+
+        fn initiate(&mut self, logger: &mut Vec<String>) -> VecDeque<Message> {
+            let destination = self.successor.clone();
+            VecDeque::from([Message::new(self.name_clone(), destination, self.node.id)])
+        }
+
+        fn receive(&mut self, mesg: Message, logger: &mut Vec<String>) -> VecDeque<Message> {
+            self.handle_message(mesg, logger)
+        }
+
+        fn ends_run(&self) -> bool {
+            self.state.is_leader()
+        }
+
+        fn conclude(run: &Algorithm<Self, Message>, logger: &mut Vec<String>) -> Result<()> {
+            if let Some(leader) = run.nodes.iter().find(|n| n.state.is_leader()) {
+                logger.push(format!("Node {} was chosen as leader.", leader.name()));
+            } else {
+                logger.push("Leader election failed.".to_string());
+            }
+            Ok(())
+        }
+    }
+
     impl AlgNode {
         fn handle_message(&mut self, mesg: Message, logger: &mut Vec<String>) -> VecDeque<Message> {
-            logger.push(format!("{}={} received {mesg}", self.name(), {
-                if self.state.is_passive() {
-                    "passive".to_string()
-                } else {
-                    self.node.id.to_string()
-                }
-            }));
+            log_routine(
+                logger,
+                format!("{}={} received {mesg}", self.name(), {
+                    if self.state.is_passive() {
+                        "passive".to_string()
+                    } else {
+                        self.node.id.to_string()
+                    }
+                }),
+            );
 
             let mut output: VecDeque<Message> = VecDeque::new();
 
             match self.state {
                 NodeState::Passive => {
-                    let receiver = self.node.connections[0].other.clone();
+                    let receiver = self.successor.clone();
                     output.push_back(mesg.pass_on(self.name_clone(), receiver))
                 }
                 NodeState::Active => {
@@ -131,7 +234,7 @@ mod changroberts {
                                 self.name()
                             ));
                             self.state = NodeState::Passive;
-                            let receiver = self.node.connections[0].other.clone();
+                            let receiver = self.successor.clone();
                             output.push_back(mesg.pass_on(self.name_clone(), receiver));
                         }
                         std::cmp::Ordering::Equal => {
@@ -150,30 +253,263 @@ mod changroberts {
 
             output
         }
+    }
 
-        fn initiate(&self) -> Message {
-            let destination = self.node.connections[0].other.clone();
-            Message::new(self.name_clone(), destination, self.node.id)
+    impl NodeGrid {
+        pub fn chang_roberts(&mut self, logger: &mut Vec<String>) -> Result<()> {
+            let mut algorithm = self.chang_roberts_algorithm(logger)?;
+            let result = algorithm.run(logger);
+            self.store_run(&algorithm);
+            result
+        }
+
+        /// Sets up Chang-Roberts to be run one step at a time.
+        pub(in crate::nodegrid::algorithms) fn chang_roberts_stepped(
+            &self,
+            logger: &mut Vec<String>,
+        ) -> Result<Box<dyn SteppedRun>> {
+            Ok(Box::new(self.chang_roberts_algorithm(logger)?))
+        }
+
+        /// Sets up Chang-Roberts on the grid, with every node yet to initiate.
+        fn chang_roberts_algorithm(
+            &self,
+            logger: &mut Vec<String>,
+        ) -> Result<Algorithm<AlgNode, Message>> {
+            self.check_not_empty(logger)?;
+            let successors = self.check_ring(logger)?;
+            let mut algorithm: Algorithm<AlgNode, Message> = Algorithm::new(&self.nodes);
+            self.configure(&mut algorithm);
+            for node in algorithm.nodes.iter_mut() {
+                node.successor = successors[node.name()].clone();
+            }
+            logger.push(format!(
+                "Started Chang-Roberts election with {} nodes.",
+                algorithm.nodes.len()
+            ));
+            algorithm.initiators = algorithm.nodes.iter().map(|n| n.name_clone()).collect();
+            Ok(algorithm)
         }
     }
+}
 
-    impl Algorithm<AlgNode, Message> {
-        fn run(&mut self, logger: &mut Vec<String>) -> Result<()> {
-            let initiators: Vec<String> = self.nodes.iter().map(|n| n.name_clone()).collect();
-            for node_name in initiators.into_iter() {
-                let init_node = self.node_by_name(node_name);
-                let mesg = init_node.initiate();
-                self.add_mesg(mesg);
+mod franklin {
+    use anyhow::{Ok, Result};
+    use displaydoc::Display;
+    use std::collections::{HashMap, VecDeque};
+    use strum::EnumIs;
+
+    use crate::{
+        node::Node,
+        nodegrid::{NodeGrid, algorithms::*},
+    };
+
+    pub(in crate::nodegrid::algorithms) const PSEUDOCODE: &str = "\
+every active node, on a ring read in both directions, in each round:
+    send <id> to both neighbours
+    on receiving <id> while passive:
+        pass <id> on in the direction it travels
+    once <id> arrived from the nearest active node on both sides:
+        if both are own id:
+            become leader
+        if own id is larger than both:
+            stay active for the next round
+        else:
+            become passive";
+
+    #[derive(Debug, Default, Clone)]
+    struct AlgNode {
+        node: Node,
+        state: NodeState,
+        successor: String,
+        predecessor: String,
+        /// Ids received this round, with the node they started from, keyed by
+        /// the direction they travelled in.
+        received: HashMap<Direction, (usize, String)>,
+    }
+
+    #[derive(Debug, Display, Default, Clone, Copy, PartialEq, Eq, EnumIs)]
+    enum NodeState {
+        #[default]
+        /// active
+        Active,
+        /// passive
+        Passive,
+        /// leader
+        Leader,
+    }
+
+    #[derive(Debug, Display, Default, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Direction {
+        #[default]
+        /// to the successor
+        Forward,
+        /// to the predecessor
+        Backward,
+    }
+
+    #[derive(Debug, Display, Default, Clone)]
+    #[displaydoc("<id={id}> {sender}->{destination}")]
+    struct Message {
+        sender: String,
+        destination: String,
+        id: usize,
+        /// The active node which sent the id this round.
+        origin: String,
+        direction: Direction,
+    }
+    impl Mesg for Message {
+        fn sender(&self) -> &str {
+            &self.sender
+        }
+        fn destination(&self) -> &str {
+            &self.destination
+        }
+        fn kind(&self) -> String {
+            "id".to_string()
+        }
+    }
+    impl NonFifo for Message {}
+
+    impl From<&Node> for AlgNode {
+        fn from(node: &Node) -> Self {
+            AlgNode {
+                node: node.clone(),
+                ..Default::default()
             }
+        }
+    }
+    impl NodeLike for AlgNode {
+        fn name(&self) -> &str {
+            &self.node.name
+        }
+        fn result(&self) -> String {
+            self.state.to_string()
+        }
+        fn display_state(&self) -> Option<NodeDisplayState> {
+            match self.state {
+                NodeState::Active => None,
+                NodeState::Passive => Some(NodeDisplayState::Passive),
+                NodeState::Leader => Some(NodeDisplayState::Leader),
+            }
+        }
+    }
+
+    impl AlgNode {
+        fn neighbour(&self, direction: Direction) -> String {
+            match direction {
+                Direction::Forward => self.successor.clone(),
+                Direction::Backward => self.predecessor.clone(),
+            }
+        }
+
+        /// Sends the id of an active node to both neighbours.
+        fn send_id(&mut self) -> VecDeque<Message> {
+            self.received.clear();
+            [Direction::Forward, Direction::Backward]
+                .into_iter()
+                .map(|direction| Message {
+                    sender: self.name_clone(),
+                    destination: self.neighbour(direction),
+                    id: self.node.id,
+                    origin: self.name_clone(),
+                    direction,
+                })
+                .collect()
+        }
+
+        fn handle_message(&mut self, mesg: Message, logger: &mut Vec<String>) -> VecDeque<Message> {
+            log_routine(logger, format!("{} received {mesg}", self.name()));
+            match self.state {
+                NodeState::Passive => VecDeque::from([Message {
+                    sender: self.name_clone(),
+                    destination: self.neighbour(mesg.direction),
+                    ..mesg
+                }]),
+                _ => {
+                    self.received.insert(mesg.direction, (mesg.id, mesg.origin));
+                    VecDeque::new()
+                }
+            }
+        }
+
+        /// Compares the ids received from both sides at the end of a round. Ties
+        /// between equal ids are broken by name.
+        fn decide(&mut self) {
+            // Without ids from both sides, as when a partition dropped some,
+            // nothing can be decided yet.
+            if self.received.len() < 2 {
+                return;
+            }
+            let own = (self.node.id, self.name_clone());
+            let received: Vec<&(usize, String)> = self.received.values().collect();
+            if received.iter().all(|(_, origin)| *origin == own.1) {
+                self.state = NodeState::Leader;
+            } else if received.iter().any(|&other| *other > own) {
+                self.state = NodeState::Passive;
+            }
+        }
+    }
 
-            while !self.messages.is_empty() && !self.nodes.iter().any(|n| n.state.is_leader()) {
+    impl Algorithm<AlgNode, Message> {
+        fn active_names(&self) -> Vec<String> {
+            self.nodes
+                .iter()
+                .filter(|n| n.state.is_active())
+                .map(NodeLike::name_clone)
+                .collect()
+        }
+
+        /// Delivers messages until none are left, returning whether that
+        /// happened before the message limit.
+        fn deliver_all(&mut self, logger: &mut Vec<String>) -> bool {
+            while self.has_messages() {
+                if self.limit_reached(logger) {
+                    return false;
+                }
                 let mesg = self.pop_mesg().unwrap();
                 let mut response = self
                     .node_by_name(mesg.destination.clone())
                     .handle_message(mesg, logger);
                 self.add_mesg_iter(&mut response);
             }
+            true
+        }
 
+        /// One round: every active node sends its id both ways, and stays
+        /// active only if it is larger than the ids of the nearest active nodes.
+        /// Returns whether the round was completed.
+        fn round(&mut self, round: usize, logger: &mut Vec<String>) -> bool {
+            let active = self.active_names();
+            for name in active.iter() {
+                let mut ids = self.node_by_name(name.clone()).send_id();
+                self.add_mesg_iter(&mut ids);
+            }
+            if !self.deliver_all(logger) {
+                return false;
+            }
+            for name in active.iter() {
+                self.node_by_name(name.clone()).decide();
+            }
+            if self.active_names().len() == active.len() {
+                logger.push(format!(
+                    "Round {round}: no node could decide, ids were lost on the way."
+                ));
+                return false;
+            }
+            logger.push(format!(
+                "Round {round}: {:?} remain active.",
+                self.active_names()
+            ));
+            true
+        }
+
+        fn run(&mut self, logger: &mut Vec<String>) -> Result<()> {
+            let mut round = 1;
+            while !self.active_names().is_empty() && self.round(round, logger) {
+                round += 1;
+            }
+            self.log_metrics(logger);
             if let Some(leader) = self.nodes.iter().find(|n| n.state.is_leader()) {
                 logger.push(format!("Node {} was chosen as leader.", leader.name()));
             } else {
@@ -184,14 +520,328 @@ mod changroberts {
     }
 
     impl NodeGrid {
-        pub fn chang_roberts(&mut self, logger: &mut Vec<String>) -> Result<()> {
+        pub fn franklin(&mut self, logger: &mut Vec<String>) -> Result<()> {
             self.check_not_empty(logger)?;
+            let successors = self.check_ring(logger)?;
+            if !self.is_bidirectional_ring() {
+                logger.push("Ring is not connected in both directions.".to_string());
+                return Err(anyhow!("Ring is not connected in both directions."));
+            }
             let mut algorithm: Algorithm<AlgNode, Message> = Algorithm::new(&self.nodes);
+            self.configure(&mut algorithm);
+            for (name, successor) in successors.iter() {
+                algorithm.node_by_name(name.clone()).successor = successor.clone();
+                algorithm.node_by_name(successor.clone()).predecessor = name.clone();
+            }
             logger.push(format!(
-                "Started Chang-Roberts election with {} nodes.",
+                "Started Franklin election with {} nodes.",
+                algorithm.nodes.len()
+            ));
+            let result = algorithm.run(logger);
+            self.store_run(&algorithm);
+            result
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn elects_largest_id_in_logarithmic_rounds() -> Result<()> {
+            for size in [1, 2, 5, 16] {
+                let mut grid = NodeGrid::generate_bidirectional_ring(
+                    size,
+                    &mut StdRng::seed_from_u64(size as u64),
+                );
+                let mut logger = vec![];
+                grid.franklin(&mut logger)?;
+
+                let largest = grid.nodes.iter().max_by_key(|n| n.id).unwrap();
+                assert_eq!(
+                    logger.last().unwrap(),
+                    &format!("Node {} was chosen as leader.", largest.name)
+                );
+                let rounds = logger.iter().filter(|l| l.starts_with("Round ")).count();
+                assert!(rounds <= size.ilog2() as usize + 1);
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn lost_ids_elect_nobody() -> Result<()> {
+            let mut grid = NodeGrid::generate_bidirectional_ring(6, &mut StdRng::seed_from_u64(6));
+            // Cut the ring between two neighbours, both ways.
+            let a = grid.nodes[0].name.clone();
+            let b = grid.nodes[0].connections[0].other.clone();
+            grid.partition = BTreeSet::from([(a.clone(), b.clone()), (b, a)]);
+            let mut logger = vec![];
+            grid.franklin(&mut logger)?;
+            assert_eq!(logger.last().unwrap(), "Leader election failed.");
+            assert!(grid.results.values().all(|r| r != "leader"));
+            Ok(())
+        }
+    }
+}
+
+mod peterson {
+    use anyhow::{Ok, Result};
+    use displaydoc::Display;
+    use std::collections::VecDeque;
+    use strum::EnumIs;
+
+    use crate::{
+        node::Node,
+        nodegrid::{NodeGrid, algorithms::*},
+    };
+
+    pub(in crate::nodegrid::algorithms) const PSEUDOCODE: &str = "\
+every active node, on a directed ring, in each phase:
+    send <first=tid> to its successor
+    on receiving <first=ntid>:
+        if ntid == tid:
+            become leader
+        send <second=ntid> to its successor
+    on receiving <second=nntid>:
+        if ntid > tid and ntid > nntid:
+            take ntid as tid for the next phase
+        else:
+            become passive
+
+every passive node:
+    pass every message on to its successor";
+
+    #[derive(Debug, Default, Clone)]
+    struct AlgNode {
+        node: Node,
+        state: NodeState,
+        successor: String,
+        phase: usize,
+        /// Id the node stands for in the current phase.
+        tid: usize,
+        /// Id of the nearest active predecessor this phase.
+        ntid: Option<usize>,
+    }
+
+    #[derive(Debug, Display, Default, Clone, Copy, PartialEq, Eq, EnumIs)]
+    enum NodeState {
+        #[default]
+        /// active
+        Active,
+        /// passive
+        Passive,
+        /// leader
+        Leader,
+    }
+
+    #[derive(Debug, Display, Default, Clone)]
+    #[displaydoc("<{kind}={id}> {sender}->{destination}")]
+    struct Message {
+        sender: String,
+        destination: String,
+        id: usize,
+        kind: MesgKind,
+    }
+    impl Mesg for Message {
+        fn sender(&self) -> &str {
+            &self.sender
+        }
+        fn destination(&self) -> &str {
+            &self.destination
+        }
+        fn kind(&self) -> String {
+            self.kind.to_string()
+        }
+    }
+    impl Fifo for Message {}
+
+    #[derive(Debug, Display, Default, Clone, Copy, PartialEq, Eq, EnumIs)]
+    enum MesgKind {
+        #[default]
+        /// first
+        First,
+        /// second
+        Second,
+    }
+
+    impl From<&Node> for AlgNode {
+        fn from(node: &Node) -> Self {
+            AlgNode {
+                node: node.clone(),
+                ..Default::default()
+            }
+        }
+    }
+    impl NodeLike for AlgNode {
+        fn name(&self) -> &str {
+            &self.node.name
+        }
+        fn result(&self) -> String {
+            self.state.to_string()
+        }
+        fn display_state(&self) -> Option<NodeDisplayState> {
+            match self.state {
+                NodeState::Active => None,
+                NodeState::Passive => Some(NodeDisplayState::Passive),
+                NodeState::Leader => Some(NodeDisplayState::Leader),
+            }
+        }
+    }
+
+    impl StepNode<Message> for AlgNode {
+        const CHANNELS: Delivery = Delivery::FrontFifo;
+
+        fn initiate(&mut self, logger: &mut Vec<String>) -> VecDeque<Message> {
+            self.tid = self.node.id;
+            self.start_phase(logger)
+        }
+
+        fn receive(&mut self, mesg: Message, logger: &mut Vec<String>) -> VecDeque<Message> {
+            log_routine(logger, format!("{} received {mesg}", self.name()));
+            match (self.state, mesg.kind) {
+                (NodeState::Passive, _) => self.send(mesg.kind, mesg.id),
+                (NodeState::Active, MesgKind::First) if mesg.id == self.tid => {
+                    logger.push(format!(
+                        "{} got its own id {} back in phase {}, so it is the leader.",
+                        self.name(),
+                        self.tid,
+                        self.phase
+                    ));
+                    self.state = NodeState::Leader;
+                    VecDeque::new()
+                }
+                (NodeState::Active, MesgKind::First) => {
+                    self.ntid = Some(mesg.id);
+                    self.send(MesgKind::Second, mesg.id)
+                }
+                (NodeState::Active, MesgKind::Second) => {
+                    let ntid = self.ntid.take().unwrap_or_default();
+                    if ntid > self.tid && ntid > mesg.id {
+                        logger.push(format!(
+                            "{ntid}>{} and {ntid}>{} so {} stays active with id {ntid}.",
+                            self.tid,
+                            mesg.id,
+                            self.name()
+                        ));
+                        self.tid = ntid;
+                        self.phase += 1;
+                        self.start_phase(logger)
+                    } else {
+                        logger.push(format!(
+                            "{ntid} is not larger than both {} and {} so {} is now passive.",
+                            self.tid,
+                            mesg.id,
+                            self.name()
+                        ));
+                        self.state = NodeState::Passive;
+                        VecDeque::new()
+                    }
+                }
+                (NodeState::Leader, _) => VecDeque::new(),
+            }
+        }
+
+        fn ends_run(&self) -> bool {
+            self.state.is_leader()
+        }
+
+        fn conclude(run: &Algorithm<Self, Message>, logger: &mut Vec<String>) -> Result<()> {
+            if let Some(leader) = run.nodes.iter().find(|n| n.state.is_leader()) {
+                logger.push(format!(
+                    "Node {} was chosen as leader, after {} phases.",
+                    leader.name(),
+                    leader.phase
+                ));
+            } else {
+                logger.push("Leader election failed.".to_string());
+            }
+            Ok(())
+        }
+    }
+
+    impl AlgNode {
+        fn send(&self, kind: MesgKind, id: usize) -> VecDeque<Message> {
+            VecDeque::from([Message {
+                sender: self.name_clone(),
+                destination: self.successor.clone(),
+                id,
+                kind,
+            }])
+        }
+
+        /// Sends the id the node stands for to start its next phase.
+        fn start_phase(&mut self, logger: &mut Vec<String>) -> VecDeque<Message> {
+            self.phase = self.phase.max(1);
+            logger.push(format!(
+                "{} enters phase {} with id {}.",
+                self.name(),
+                self.phase,
+                self.tid
+            ));
+            self.send(MesgKind::First, self.tid)
+        }
+    }
+
+    impl NodeGrid {
+        pub fn peterson(&mut self, logger: &mut Vec<String>) -> Result<()> {
+            let mut algorithm = self.peterson_algorithm(logger)?;
+            let result = algorithm.run(logger);
+            self.store_run(&algorithm);
+            result
+        }
+
+        /// Sets up Peterson to be run one step at a time.
+        pub(in crate::nodegrid::algorithms) fn peterson_stepped(
+            &self,
+            logger: &mut Vec<String>,
+        ) -> Result<Box<dyn SteppedRun>> {
+            Ok(Box::new(self.peterson_algorithm(logger)?))
+        }
+
+        /// Sets up Peterson on the grid, with every node yet to start its first
+        /// phase.
+        fn peterson_algorithm(
+            &self,
+            logger: &mut Vec<String>,
+        ) -> Result<Algorithm<AlgNode, Message>> {
+            self.check_not_empty(logger)?;
+            let successors = self.check_ring(logger)?;
+            let mut algorithm: Algorithm<AlgNode, Message> = Algorithm::new(&self.nodes);
+            self.configure(&mut algorithm);
+            for node in algorithm.nodes.iter_mut() {
+                node.successor = successors[node.name()].clone();
+            }
+            logger.push(format!(
+                "Started Peterson election with {} nodes.",
                 algorithm.nodes.len()
             ));
-            algorithm.run(logger)
+            algorithm.initiators = algorithm.nodes.iter().map(|n| n.name_clone()).collect();
+            Ok(algorithm)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn elects_a_single_leader_within_the_message_bound() -> Result<()> {
+            for size in [1, 2, 5, 16] {
+                let mut grid =
+                    NodeGrid::generate_ring(size, &mut StdRng::seed_from_u64(size as u64));
+                let mut logger = vec![];
+                grid.peterson(&mut logger)?;
+
+                let leaders = grid
+                    .nodes
+                    .iter()
+                    .filter(|n| grid.result(&n.name).map(String::as_str) == Some("leader"));
+                assert_eq!(leaders.count(), 1);
+                assert!(logger.last().unwrap().starts_with("Node "));
+                let bound = SelectedAlgorithm::Peterson.message_bound(size, size);
+                assert!(grid.messages_sent() <= bound.unwrap());
+            }
+            Ok(())
         }
     }
 }
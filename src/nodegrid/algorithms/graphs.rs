@@ -0,0 +1,361 @@
+use crate::nodegrid::algorithms::*;
+
+pub(super) use luby::PSEUDOCODE;
+
+mod luby {
+    use anyhow::{Result, anyhow};
+    use displaydoc::Display;
+    use rand::Rng;
+    use std::collections::{BTreeSet, HashMap, VecDeque};
+    use strum::EnumIs;
+
+    use crate::{
+        node::Node,
+        nodegrid::{NodeGrid, algorithms::*},
+    };
+
+    pub(in crate::nodegrid::algorithms) const PSEUDOCODE: &str = "\
+while some node is undecided, every undecided node in each round:
+    pick a random value
+    send <value> to every undecided neighbour
+    if own (value, id) is smaller than that of every undecided neighbour:
+        join the set
+        send <joined> to every undecided neighbour
+    on receiving <joined>:
+        leave, excluded from the set
+    if excluded:
+        send <removed> to every undecided neighbour
+    on receiving <removed> from q:
+        q is no longer an undecided neighbour";
+
+    #[derive(Debug, Default, Clone)]
+    struct AlgNode {
+        node: Node,
+        state: NodeState,
+        /// Neighbours when connections are read as undirected edges.
+        neighbours: BTreeSet<String>,
+        /// Neighbours which have not yet joined or left the set.
+        undecided: BTreeSet<String>,
+        value: u32,
+        /// Values received from undecided neighbours this round.
+        received: HashMap<String, u32>,
+    }
+
+    #[derive(Debug, Display, Default, Clone, Copy, PartialEq, Eq, EnumIs)]
+    enum NodeState {
+        #[default]
+        /// undecided
+        Undecided,
+        /// in set
+        InSet,
+        /// excluded
+        Excluded,
+    }
+
+    #[derive(Debug, Display, Default, Clone)]
+    #[displaydoc("<{kind}> {sender}->{destination}")]
+    struct Message {
+        sender: String,
+        destination: String,
+        kind: MesgKind,
+    }
+    impl Mesg for Message {
+        fn sender(&self) -> &str {
+            &self.sender
+        }
+        fn destination(&self) -> &str {
+            &self.destination
+        }
+        fn kind(&self) -> String {
+            self.kind.to_string()
+        }
+    }
+    impl NonFifo for Message {}
+
+    #[derive(Debug, Display, Default, Clone, Copy, PartialEq, Eq)]
+    enum MesgKind {
+        /// value={0}
+        Value(u32),
+        #[default]
+        /// joined
+        Joined,
+        /// removed
+        Removed,
+    }
+
+    impl From<&Node> for AlgNode {
+        fn from(node: &Node) -> Self {
+            AlgNode {
+                node: node.clone(),
+                ..Default::default()
+            }
+        }
+    }
+    impl NodeLike for AlgNode {
+        fn name(&self) -> &str {
+            &self.node.name
+        }
+        fn result(&self) -> String {
+            self.state.to_string()
+        }
+    }
+
+    impl AlgNode {
+        fn send_all(&self, kind: MesgKind) -> VecDeque<Message> {
+            self.undecided
+                .iter()
+                .map(|other| Message {
+                    sender: self.name_clone(),
+                    destination: other.clone(),
+                    kind,
+                })
+                .collect()
+        }
+
+        fn handle_message(&mut self, mesg: Message) {
+            match mesg.kind {
+                MesgKind::Value(value) => {
+                    self.received.insert(mesg.sender, value);
+                }
+                MesgKind::Joined => {
+                    self.state = NodeState::Excluded;
+                    self.undecided.remove(&mesg.sender);
+                }
+                MesgKind::Removed => {
+                    self.undecided.remove(&mesg.sender);
+                }
+            }
+        }
+
+        /// Whether this node holds the smallest value among its undecided
+        /// neighbours. Ties are broken by id.
+        fn is_local_minimum(&self, ids: &HashMap<String, usize>) -> bool {
+            self.received
+                .iter()
+                .all(|(other, &value)| (self.value, self.node.id) < (value, ids[other.as_str()]))
+        }
+    }
+
+    impl Algorithm<AlgNode, Message> {
+        /// Delivers messages until none are left, returning whether that
+        /// happened before the message limit.
+        fn deliver_all(&mut self, logger: &mut Vec<String>) -> bool {
+            while self.has_messages() {
+                if self.limit_reached(logger) {
+                    return false;
+                }
+                let mesg = self.pop_mesg().unwrap();
+                self.node_by_name(mesg.destination.clone())
+                    .handle_message(mesg);
+            }
+            true
+        }
+
+        fn undecided_names(&self) -> Vec<String> {
+            self.nodes
+                .iter()
+                .filter(|n| n.state.is_undecided())
+                .map(NodeLike::name_clone)
+                .collect()
+        }
+
+        /// One synchronous round: exchange values, let local minima join, and tell
+        /// the neighbours of joined and excluded nodes. Returns whether the round
+        /// was completed.
+        fn round(&mut self, round: usize, logger: &mut Vec<String>) -> bool {
+            let undecided = self.undecided_names();
+            for name in undecided.iter() {
+                let value = self.rng.random();
+                let node = self.node_by_name(name.clone());
+                node.value = value;
+                node.received.clear();
+                let mut values = node.send_all(MesgKind::Value(node.value));
+                self.add_mesg_iter(&mut values);
+            }
+            if !self.deliver_all(logger) {
+                return false;
+            }
+
+            let ids: HashMap<String, usize> = self
+                .nodes
+                .iter()
+                .map(|n| (n.name_clone(), n.node.id))
+                .collect();
+            let joined: Vec<String> = undecided
+                .iter()
+                .filter(|name| {
+                    self.nodes
+                        .iter()
+                        .find(|n| n.name() == name.as_str())
+                        .is_some_and(|n| n.is_local_minimum(&ids))
+                })
+                .cloned()
+                .collect();
+            for name in joined.iter() {
+                let node = self.node_by_name(name.clone());
+                node.state = NodeState::InSet;
+                let mut notices = node.send_all(MesgKind::Joined);
+                self.add_mesg_iter(&mut notices);
+            }
+            if !self.deliver_all(logger) {
+                return false;
+            }
+
+            let excluded: Vec<String> = undecided
+                .iter()
+                .filter(|name| {
+                    self.nodes
+                        .iter()
+                        .any(|n| n.name() == name.as_str() && n.state.is_excluded())
+                })
+                .cloned()
+                .collect();
+            for name in excluded.iter() {
+                let mut notices = self.node_by_name(name.clone()).send_all(MesgKind::Removed);
+                self.add_mesg_iter(&mut notices);
+            }
+            if !self.deliver_all(logger) {
+                return false;
+            }
+
+            logger.push(format!(
+                "Round {round}: {joined:?} joined, {excluded:?} excluded."
+            ));
+            true
+        }
+
+        fn run(&mut self, logger: &mut Vec<String>) -> Result<()> {
+            let mut round = 1;
+            let mut completed = true;
+            while completed && self.nodes.iter().any(|n| n.state.is_undecided()) {
+                completed = self.round(round, logger);
+                round += 1;
+            }
+            self.log_metrics(logger);
+            match completed {
+                true => self.verify_set(logger),
+                false => Ok(()),
+            }
+        }
+
+        /// Checks that no two nodes in the set are adjacent, and that every node
+        /// outside of it has a neighbour in it.
+        fn verify_set(&self, logger: &mut Vec<String>) -> Result<()> {
+            let in_set: BTreeSet<&str> = self
+                .nodes
+                .iter()
+                .filter(|n| n.state.is_in_set())
+                .map(NodeLike::name)
+                .collect();
+            for node in self.nodes.iter() {
+                let has_neighbour_in_set =
+                    node.neighbours.iter().any(|n| in_set.contains(n.as_str()));
+                match (node.state.is_in_set(), has_neighbour_in_set) {
+                    (true, true) => {
+                        logger.push(format!(
+                            "{} and a neighbour are both in the set.",
+                            node.name()
+                        ));
+                        return Err(anyhow!("Set is not independent."));
+                    }
+                    (false, false) => {
+                        logger.push(format!("{} has no neighbour in the set.", node.name()));
+                        return Err(anyhow!("Set is not maximal."));
+                    }
+                    _ => {}
+                }
+            }
+            logger.push(format!(
+                "Found maximal independent set {in_set:?} of {} nodes.",
+                in_set.len()
+            ));
+            Ok(())
+        }
+    }
+
+    impl NodeGrid {
+        pub fn luby(&mut self, logger: &mut Vec<String>) -> Result<()> {
+            self.check_not_empty(logger)?;
+            let mut algorithm: Algorithm<AlgNode, Message> = Algorithm::new(&self.nodes);
+            self.configure(&mut algorithm);
+            let neighbours = self.undirected_neighbours();
+            for node in algorithm.nodes.iter_mut() {
+                node.neighbours = neighbours[node.name()]
+                    .iter()
+                    .map(|n| n.to_string())
+                    .collect();
+                node.undecided = node.neighbours.clone();
+            }
+            logger.push(format!(
+                "Started Luby's maximal independent set with {} nodes.",
+                algorithm.nodes.len()
+            ));
+            let result = algorithm.run(logger);
+            self.store_run(&algorithm);
+            result
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn finds_maximal_independent_set() -> Result<()> {
+            for seed in 0..10 {
+                let mut grid = NodeGrid::generate_connected(9, &mut StdRng::seed_from_u64(seed));
+                let mut logger = vec![];
+                grid.luby(&mut logger)?;
+                assert!(
+                    logger
+                        .last()
+                        .unwrap()
+                        .starts_with("Found maximal independent set")
+                );
+                assert!(logger.iter().any(|l| l.starts_with("Round 1:")));
+
+                let neighbours = grid.undirected_neighbours();
+                let in_set = |name: &str| grid.result(name).unwrap() == "in set";
+                for node in grid.nodes.iter() {
+                    let adjacent = neighbours[node.name.as_str()].iter().any(|n| in_set(n));
+                    assert_ne!(in_set(&node.name), adjacent);
+                }
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn stops_at_the_message_limit() -> Result<()> {
+            let mut grid = NodeGrid::generate_connected(9, &mut StdRng::seed_from_u64(1));
+            grid.message_limit = Some(5);
+            let mut logger = vec![];
+            grid.luby(&mut logger)?;
+            assert!(logger.contains(
+                &"Stopped after delivering 5 messages, the run is truncated.".to_string()
+            ));
+            let delivered = grid
+                .trace
+                .iter()
+                .filter(|r| r.event == TraceEvent::Delivered);
+            assert_eq!(delivered.count(), 5);
+            Ok(())
+        }
+
+        #[test]
+        fn rejects_dependent_set() {
+            let nodes: Vec<Node> = ["a", "b"]
+                .into_iter()
+                .map(|name| Node {
+                    name: name.to_string(),
+                    ..Default::default()
+                })
+                .collect();
+            let mut algorithm: Algorithm<AlgNode, Message> = Algorithm::new(&nodes);
+            for (node, other) in algorithm.nodes.iter_mut().zip(["b", "a"]) {
+                node.neighbours.insert(other.to_string());
+                node.state = NodeState::InSet;
+            }
+            assert!(algorithm.verify_set(&mut vec![]).is_err());
+        }
+    }
+}
@@ -0,0 +1,513 @@
+use anyhow::{Result, anyhow};
+use std::collections::BTreeMap;
+
+pub(super) use ricartagrawala::PSEUDOCODE as RICART_AGRAWALA_PSEUDOCODE;
+
+/// Keeps track of who asked for and who entered the critical section during a
+/// run of a mutual exclusion algorithm, shared by all of them through
+/// `Algorithm::critical_section`.
+#[derive(Debug, Default, Clone)]
+pub(super) struct CriticalSection {
+    requests: BTreeMap<String, usize>,
+    entries: BTreeMap<String, usize>,
+    holder: Option<String>,
+    /// A node entered while another was in the critical section.
+    violated: bool,
+}
+
+impl CriticalSection {
+    pub(super) fn request(&mut self, name: &str) {
+        *self.requests.entry(name.to_string()).or_default() += 1;
+    }
+
+    /// Fails if another node is still in the critical section.
+    pub(super) fn enter(&mut self, name: &str) -> Result<()> {
+        if let Some(holder) = &self.holder {
+            self.violated = true;
+            return Err(anyhow!(
+                "{name} entered the critical section while {holder} was in it."
+            ));
+        }
+        *self.entries.entry(name.to_string()).or_default() += 1;
+        self.holder = Some(name.to_string());
+        Ok(())
+    }
+
+    pub(super) fn exit(&mut self, name: &str) {
+        if self.holder.as_deref() == Some(name) {
+            self.holder = None;
+        }
+    }
+
+    /// The node in the critical section, if any.
+    pub(super) fn holder(&self) -> Option<&str> {
+        self.holder.as_deref()
+    }
+
+    /// Whether mutual exclusion was broken at any point.
+    pub(super) fn violated(&self) -> bool {
+        self.violated
+    }
+
+    pub(super) fn entries(&self, name: &str) -> usize {
+        self.entries.get(name).copied().unwrap_or_default()
+    }
+
+    /// Logs how often every node entered the critical section, and fails if a
+    /// request was never granted.
+    pub(super) fn report_fairness(&self, logger: &mut Vec<String>) -> Result<()> {
+        for (name, requests) in self.requests.iter() {
+            logger.push(format!(
+                "{name} entered the critical section {} of {requests} times.",
+                self.entries(name)
+            ));
+        }
+        let counts = self.requests.keys().map(|name| self.entries(name));
+        if let (Some(min), Some(max)) = (counts.clone().min(), counts.max()) {
+            logger.push(format!("Entries differ by at most {}.", max - min));
+        }
+        let starved: Vec<&str> = self
+            .requests
+            .iter()
+            .filter(|(name, requests)| self.entries(name) < **requests)
+            .map(|(name, _)| name.as_str())
+            .collect();
+        match starved.is_empty() {
+            true => Ok(()),
+            false => {
+                logger.push(format!("Starved requesters: {}.", starved.join(", ")));
+                Err(anyhow!(
+                    "Not every request for the critical section was granted."
+                ))
+            }
+        }
+    }
+}
+
+mod ricartagrawala {
+    use anyhow::{Result, anyhow};
+    use displaydoc::Display;
+    use rand::seq::SliceRandom;
+    use std::collections::VecDeque;
+    use strum::EnumIs;
+
+    use crate::{
+        node::Node,
+        nodegrid::{NodeGrid, algorithms::*},
+    };
+
+    pub(in crate::nodegrid::algorithms) const PSEUDOCODE: &str = "\
+every node, to enter the critical section:
+    tick the clock and send <request, t> to every other node
+    enter once every other node replied
+
+on receiving <request, t> from q:
+    if in the critical section, or requesting with own (t, id) earlier:
+        defer the reply to q
+    else:
+        send <reply> to q
+
+on leaving the critical section:
+    send the deferred replies";
+
+    #[derive(Debug, Default, Clone)]
+    struct AlgNode {
+        node: Node,
+        state: NodeState,
+        clock: LamportsClock,
+        /// Time of the pending request, which orders it against the others.
+        requested_at: LamportsClock,
+        /// Every other node, which all have to reply to a request.
+        others: Vec<String>,
+        replies: usize,
+        /// Nodes whose requests are answered on leaving the critical section.
+        deferred: Vec<String>,
+    }
+
+    #[derive(Debug, Display, Default, Clone, Copy, PartialEq, Eq, EnumIs)]
+    enum NodeState {
+        #[default]
+        /// idle
+        Idle,
+        /// requesting
+        Requesting,
+        /// critical
+        Critical,
+        /// done
+        Done,
+    }
+
+    #[derive(Debug, Display, Default, Clone)]
+    #[displaydoc("<{kind} {time}> {sender}->{destination}")]
+    struct Message {
+        sender: String,
+        destination: String,
+        kind: MesgKind,
+        time: LamportsClock,
+    }
+    impl Mesg for Message {
+        fn sender(&self) -> &str {
+            &self.sender
+        }
+        fn destination(&self) -> &str {
+            &self.destination
+        }
+        fn kind(&self) -> String {
+            self.kind.to_string()
+        }
+        fn timestamp(&self) -> Option<LamportsClock> {
+            Some(self.time)
+        }
+    }
+    impl NonFifo for Message {}
+    impl LamportsMessage for Message {
+        fn time(&self) -> LamportsClock {
+            self.time
+        }
+    }
+
+    #[derive(Debug, Display, Default, Clone, Copy, PartialEq, Eq)]
+    enum MesgKind {
+        #[default]
+        /// request
+        Request,
+        /// reply
+        Reply,
+    }
+
+    impl From<&Node> for AlgNode {
+        fn from(node: &Node) -> Self {
+            AlgNode {
+                node: node.clone(),
+                ..Default::default()
+            }
+        }
+    }
+    impl NodeLike for AlgNode {
+        fn name(&self) -> &str {
+            &self.node.name
+        }
+        fn result(&self) -> String {
+            self.state.to_string()
+        }
+        fn clock(&self) -> Option<LamportsClock> {
+            Some(self.clock)
+        }
+    }
+
+    impl AlgNode {
+        fn message(&mut self, destination: String, kind: MesgKind) -> Message {
+            Message {
+                sender: self.name_clone(),
+                destination,
+                kind,
+                time: self.clock.tick(),
+            }
+        }
+
+        /// Asks every other node for the critical section. Without any other
+        /// node it is entered right away.
+        fn request(&mut self) -> VecDeque<Message> {
+            self.requested_at = self.clock.tick();
+            self.state = match self.others.is_empty() {
+                true => NodeState::Critical,
+                false => NodeState::Requesting,
+            };
+            self.others
+                .iter()
+                .map(|other| Message {
+                    sender: self.name_clone(),
+                    destination: other.clone(),
+                    kind: MesgKind::Request,
+                    time: self.requested_at,
+                })
+                .collect()
+        }
+
+        fn handle_message(&mut self, mesg: Message, logger: &mut Vec<String>) -> VecDeque<Message> {
+            log_routine(logger, format!("{} received {mesg}", self.name()));
+            self.clock.receive(&mesg);
+            let mut output = VecDeque::new();
+            match mesg.kind {
+                MesgKind::Request => {
+                    let own = (self.requested_at, self.name());
+                    let defer = self.state.is_critical()
+                        || (self.state.is_requesting() && own < (mesg.time, mesg.sender.as_str()));
+                    match defer {
+                        true => {
+                            log_routine(logger, format!("{} defers {}.", self.name(), mesg.sender));
+                            self.deferred.push(mesg.sender);
+                        }
+                        false => output.push_back(self.message(mesg.sender, MesgKind::Reply)),
+                    }
+                }
+                MesgKind::Reply => {
+                    self.replies += 1;
+                    if self.state.is_requesting() && self.replies == self.others.len() {
+                        self.state = NodeState::Critical;
+                    }
+                }
+            }
+            output
+        }
+
+        /// Leaves the critical section, replying to the deferred requests.
+        fn leave(&mut self) -> VecDeque<Message> {
+            self.state = NodeState::Done;
+            let deferred: Vec<String> = self.deferred.drain(..).collect();
+            deferred
+                .into_iter()
+                .map(|other| self.message(other, MesgKind::Reply))
+                .collect()
+        }
+    }
+
+    impl Algorithm<AlgNode, Message> {
+        /// Lets the node named `name` into the critical section, if it just
+        /// collected every reply.
+        fn enter_if_granted(&mut self, name: &str, logger: &mut Vec<String>) {
+            let node = self.node_by_name(name.to_string());
+            let (granted, time) = (node.state.is_critical(), node.clock);
+            if !granted || self.critical_section.holder() == Some(name) {
+                return;
+            }
+            logger.push(format!("{name} enters the critical section at {time}."));
+            if let Err(violation) = self.critical_section.enter(name) {
+                logger.push(violation.to_string());
+            }
+        }
+
+        fn leave(&mut self, name: &str, logger: &mut Vec<String>) {
+            logger.push(format!("{name} leaves the critical section."));
+            self.critical_section.exit(name);
+            let mut replies = self.node_by_name(name.to_string()).leave();
+            self.add_mesg_iter(&mut replies);
+        }
+
+        /// Every node requests the critical section once, one per step. After
+        /// that a step delivers a message, and a node in the critical section
+        /// leaves once one more message is delivered.
+        fn take_step(&mut self, logger: &mut Vec<String>) -> Step {
+            if let Some(name) = self.initiators.pop_front() {
+                self.critical_section.request(&name);
+                let mut requests = self.node_by_name(name.clone()).request();
+                self.add_mesg_iter(&mut requests);
+                self.enter_if_granted(&name, logger);
+                return Step::Initiated(name);
+            }
+            let holder = self.critical_section.holder().map(String::from);
+            let step = match self.messages.front() {
+                None if holder.is_none() => return Step::Finished,
+                None => None,
+                Some(_) if self.limit_reached(logger) => return Step::Truncated,
+                Some(_) => {
+                    let mesg = self.pop_mesg().unwrap();
+                    let delivered = mesg.to_string();
+                    let destination = mesg.destination.clone();
+                    let mut response = self
+                        .node_by_name(destination.clone())
+                        .handle_message(mesg, logger);
+                    self.add_mesg_iter(&mut response);
+                    self.enter_if_granted(&destination, logger);
+                    Some(Step::Delivered(delivered))
+                }
+            };
+            match holder {
+                Some(holder) => {
+                    self.leave(&holder, logger);
+                    step.unwrap_or(Step::Left(holder))
+                }
+                None => step.unwrap(),
+            }
+        }
+
+        /// Logs how often every node entered, and whether mutual exclusion
+        /// held.
+        fn conclude(&self, logger: &mut Vec<String>) -> Result<()> {
+            let fair = self.critical_section.report_fairness(logger);
+            match self.critical_section.violated() {
+                false => {
+                    logger.push("Mutual exclusion held throughout the run.".to_string());
+                    fair
+                }
+                true => Err(anyhow!("Mutual exclusion was violated.")),
+            }
+        }
+    }
+
+    impl SteppedRun for Algorithm<AlgNode, Message> {
+        fn advance(&mut self, logger: &mut Vec<String>) -> Step {
+            self.take_step(logger)
+        }
+        fn conclude(&mut self, logger: &mut Vec<String>) -> Result<()> {
+            self.log_metrics(logger);
+            Algorithm::conclude(self, logger)
+        }
+        fn store(&self, grid: &mut NodeGrid) {
+            grid.store_run(self);
+        }
+        fn in_transit(&self) -> usize {
+            self.messages.len()
+        }
+    }
+
+    impl NodeGrid {
+        pub fn ricart_agrawala(&mut self, logger: &mut Vec<String>) -> Result<()> {
+            let mut algorithm = self.ricart_agrawala_algorithm(logger)?;
+            let result = algorithm.run(logger);
+            self.store_run(&algorithm);
+            result
+        }
+
+        /// Sets up Ricart-Agrawala to be run one step at a time.
+        pub(in crate::nodegrid::algorithms) fn ricart_agrawala_stepped(
+            &self,
+            logger: &mut Vec<String>,
+        ) -> Result<Box<dyn SteppedRun>> {
+            Ok(Box::new(self.ricart_agrawala_algorithm(logger)?))
+        }
+
+        /// Sets up Ricart-Agrawala on the grid, with every node yet to request
+        /// the critical section, in random order unless the run is frozen.
+        fn ricart_agrawala_algorithm(
+            &self,
+            logger: &mut Vec<String>,
+        ) -> Result<Algorithm<AlgNode, Message>> {
+            self.check_not_empty(logger)?;
+            let mut algorithm: Algorithm<AlgNode, Message> = Algorithm::new(&self.nodes);
+            self.configure(&mut algorithm);
+            let mut names: Vec<String> = algorithm.nodes.iter().map(|n| n.name_clone()).collect();
+            for node in algorithm.nodes.iter_mut() {
+                node.others = names
+                    .iter()
+                    .filter(|n| *n != node.name())
+                    .cloned()
+                    .collect();
+            }
+            logger.push(format!(
+                "Started Ricart-Agrawala mutual exclusion with {} nodes.",
+                algorithm.nodes.len()
+            ));
+            if !algorithm.frozen {
+                names.shuffle(&mut algorithm.rng);
+            }
+            algorithm.initiators = names.into();
+            Ok(algorithm)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn every_node_enters_once_in_turn() -> Result<()> {
+            for size in [1, 6] {
+                let mut grid =
+                    NodeGrid::generate_connected(size, &mut StdRng::seed_from_u64(size as u64));
+                let mut logger = vec![];
+                grid.ricart_agrawala(&mut logger)?;
+                assert_eq!(
+                    logger.last().unwrap(),
+                    "Mutual exclusion held throughout the run."
+                );
+                let entries: Vec<&String> = logger
+                    .iter()
+                    .filter(|l| l.contains(" enters the critical section"))
+                    .collect();
+                assert_eq!(entries.len(), size);
+                assert_eq!(grid.messages_sent(), 2 * size * (size - 1));
+                assert!(
+                    grid.nodes
+                        .iter()
+                        .all(|n| grid.result(&n.name).unwrap() == "done")
+                );
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn stepping_shows_who_is_in_the_critical_section() -> Result<()> {
+            let mut grid = NodeGrid::generate_connected(4, &mut StdRng::seed_from_u64(3));
+            grid.seed = Some(5);
+            let mut whole = vec![];
+            grid.ricart_agrawala(&mut whole)?;
+
+            let mut logger = vec![];
+            let mut run = grid.ricart_agrawala_stepped(&mut logger)?;
+            let mut holders = vec![];
+            while !run.advance(&mut logger).is_last() {
+                run.store(&mut grid);
+                assert_eq!(grid.clocks.len(), 4);
+                holders.extend(grid.critical.clone());
+            }
+            run.conclude(&mut logger)?;
+            assert_eq!(logger, whole);
+            holders.dedup();
+            assert_eq!(holders.len(), 4);
+            Ok(())
+        }
+
+        #[test]
+        fn earlier_request_is_not_deferred() {
+            let mut node = AlgNode {
+                others: vec!["b".to_string()],
+                ..AlgNode::from(&Node {
+                    name: "a".to_string(),
+                    ..Default::default()
+                })
+            };
+            node.request();
+            let earlier = Message {
+                sender: "b".to_string(),
+                destination: "a".to_string(),
+                kind: MesgKind::Request,
+                time: LamportsClock(0),
+            };
+            let later = Message {
+                time: LamportsClock(5),
+                ..earlier.clone()
+            };
+            assert_eq!(node.handle_message(earlier, &mut vec![]).len(), 1);
+            assert!(node.handle_message(later, &mut vec![]).is_empty());
+            assert_eq!(node.deferred, ["b"]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_entries_and_finds_starved_requester() -> Result<()> {
+        let mut section = CriticalSection::default();
+        for name in ["a", "b", "a", "c"] {
+            section.request(name);
+        }
+        for name in ["a", "b", "a"] {
+            section.enter(name)?;
+            assert_eq!(section.holder(), Some(name));
+            assert!(section.enter("c").is_err());
+            section.exit(name);
+        }
+        assert_eq!(["a", "b", "c"].map(|name| section.entries(name)), [2, 1, 0]);
+
+        let mut logger = vec![];
+        assert!(section.report_fairness(&mut logger).is_err());
+        assert_eq!(
+            logger,
+            [
+                "a entered the critical section 2 of 2 times.",
+                "b entered the critical section 1 of 1 times.",
+                "c entered the critical section 0 of 1 times.",
+                "Entries differ by at most 2.",
+                "Starved requesters: c.",
+            ]
+        );
+
+        section.enter("c")?;
+        assert!(section.report_fairness(&mut vec![]).is_ok());
+        Ok(())
+    }
+}
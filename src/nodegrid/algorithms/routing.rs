@@ -0,0 +1,275 @@
+use crate::nodegrid::algorithms::*;
+
+pub(super) use chandymisra::PSEUDOCODE as CHANDY_MISRA_PSEUDOCODE;
+
+mod chandymisra {
+    use anyhow::{Result, anyhow};
+    use displaydoc::Display;
+    use std::collections::VecDeque;
+    use strum::EnumIs;
+
+    use crate::{
+        node::Node,
+        nodegrid::{NodeGrid, algorithms::*},
+    };
+
+    pub(in crate::nodegrid::algorithms) const PSEUDOCODE: &str = "\
+the initiator, at distance 0:
+    send <distance 0 + w> over every outgoing connection of weight w
+
+on receiving <distance d> from q:
+    if d is shorter than the known distance:
+        take d as distance, with q as parent
+        send <distance d + w> over every outgoing connection of weight w
+
+once no messages are left:
+    every reached node knows its distance and parent
+
+an improvement over a path of n hops or more went around a negative cycle";
+
+    #[derive(Debug, Default, Clone)]
+    struct AlgNode {
+        node: Node,
+        state: NodeState,
+        distance: f64,
+        parent: Option<String>,
+        /// Number of nodes in the run, the least number of hops of a path
+        /// which must have gone around a cycle.
+        hop_limit: usize,
+    }
+
+    #[derive(Debug, Display, Default, Clone, Copy, PartialEq, Eq, EnumIs)]
+    enum NodeState {
+        #[default]
+        /// unreachable
+        Unreached,
+        /// reached
+        Reached,
+        /// negative cycle
+        NegativeCycle,
+    }
+
+    #[derive(Debug, Display, Default, Clone)]
+    #[displaydoc("<distance {distance}> {sender}->{destination}")]
+    struct Message {
+        sender: String,
+        destination: String,
+        /// Length of the path to the destination through the sender.
+        distance: f64,
+        /// Number of connections on that path.
+        hops: usize,
+    }
+    impl Mesg for Message {
+        fn sender(&self) -> &str {
+            &self.sender
+        }
+        fn destination(&self) -> &str {
+            &self.destination
+        }
+        fn kind(&self) -> String {
+            "distance".to_string()
+        }
+    }
+    impl NonFifo for Message {}
+
+    impl From<&Node> for AlgNode {
+        fn from(node: &Node) -> Self {
+            AlgNode {
+                node: node.clone(),
+                ..Default::default()
+            }
+        }
+    }
+    impl NodeLike for AlgNode {
+        fn name(&self) -> &str {
+            &self.node.name
+        }
+        fn result(&self) -> String {
+            match self.state {
+                NodeState::Reached => format!("distance {}", self.distance),
+                state => state.to_string(),
+            }
+        }
+    }
+
+    impl StepNode<Message> for AlgNode {
+        const CHANNELS: Delivery = Delivery::RandomInsert;
+
+        fn initiate(&mut self, logger: &mut Vec<String>) -> VecDeque<Message> {
+            self.state = NodeState::Reached;
+            self.distance = 0.0;
+            self.send_distances(0)
+        }
+
+        fn receive(&mut self, mesg: Message, logger: &mut Vec<String>) -> VecDeque<Message> {
+            log_routine(logger, format!("{} received {mesg}", self.name()));
+            if self.state.is_reached() && mesg.distance >= self.distance {
+                return VecDeque::new();
+            }
+            if mesg.hops >= self.hop_limit {
+                logger.push(format!(
+                    "{} improved over a path of {} hops, there is a negative cycle.",
+                    self.name(),
+                    mesg.hops
+                ));
+                self.state = NodeState::NegativeCycle;
+                return VecDeque::new();
+            }
+            logger.push(format!(
+                "{} updates its distance to {} via {}.",
+                self.name(),
+                mesg.distance,
+                mesg.sender
+            ));
+            self.state = NodeState::Reached;
+            self.distance = mesg.distance;
+            self.parent = Some(mesg.sender);
+            self.send_distances(mesg.hops)
+        }
+
+        fn ends_run(&self) -> bool {
+            self.state.is_negative_cycle()
+        }
+
+        /// Logs the distance and parent every node ended with, unless a
+        /// negative cycle left them without a shortest path.
+        fn conclude(run: &Algorithm<Self, Message>, logger: &mut Vec<String>) -> Result<()> {
+            if let Some(node) = run.nodes.iter().find(|n| n.state.is_negative_cycle()) {
+                logger.push(format!(
+                    "Stopped, the negative cycle found by {} leaves no shortest paths.",
+                    node.name()
+                ));
+                return Err(anyhow!("Negative cycle reachable from the initiator."));
+            }
+            for node in run.nodes.iter() {
+                logger.push(match (node.state, &node.parent) {
+                    (NodeState::Reached, Some(parent)) => format!(
+                        "{} is at distance {} with parent {parent}.",
+                        node.name(),
+                        node.distance
+                    ),
+                    (NodeState::Reached, None) => format!("{} is the source.", node.name()),
+                    _ => format!("{} is unreachable.", node.name()),
+                });
+            }
+            Ok(())
+        }
+    }
+
+    impl AlgNode {
+        /// Offers every outgoing neighbour the path through this node, which
+        /// was reached over `hops` connections.
+        fn send_distances(&self, hops: usize) -> VecDeque<Message> {
+            self.node
+                .connections
+                .iter()
+                .map(|c| Message {
+                    sender: self.name_clone(),
+                    destination: c.other.clone(),
+                    distance: self.distance + *c.weight,
+                    hops: hops + 1,
+                })
+                .collect()
+        }
+    }
+
+    impl NodeGrid {
+        pub fn chandy_misra(&mut self, logger: &mut Vec<String>) -> Result<()> {
+            let mut algorithm = self.chandy_misra_algorithm(logger)?;
+            let result = algorithm.run(logger);
+            self.store_run(&algorithm);
+            result
+        }
+
+        /// Sets up Chandy-Misra to be run one step at a time.
+        pub(in crate::nodegrid::algorithms) fn chandy_misra_stepped(
+            &self,
+            logger: &mut Vec<String>,
+        ) -> Result<Box<dyn SteppedRun>> {
+            Ok(Box::new(self.chandy_misra_algorithm(logger)?))
+        }
+
+        /// Sets up Chandy-Misra on the grid, with the initiator yet to send
+        /// the first distances.
+        fn chandy_misra_algorithm(
+            &self,
+            logger: &mut Vec<String>,
+        ) -> Result<Algorithm<AlgNode, Message>> {
+            self.check_not_empty(logger)?;
+            let mut algorithm: Algorithm<AlgNode, Message> = Algorithm::new(&self.nodes);
+            self.configure(&mut algorithm);
+            let hop_limit = algorithm.nodes.len();
+            for node in algorithm.nodes.iter_mut() {
+                node.hop_limit = hop_limit;
+            }
+            logger.push(format!(
+                "Started Chandy-Misra with {} nodes.",
+                algorithm.nodes.len()
+            ));
+            let initiator = algorithm.choose_initiator(logger);
+            algorithm.initiators.push_back(initiator);
+            Ok(algorithm)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn weighted(edges: &[(&str, &str, f64)]) -> NodeGrid {
+            let mut nodes: Vec<Node> = ["a", "b", "c", "d"]
+                .into_iter()
+                .map(|name| Node {
+                    name: name.to_string(),
+                    ..Default::default()
+                })
+                .collect();
+            for (from, to, weight) in edges {
+                let node = nodes.iter_mut().find(|n| n.name == *from).unwrap();
+                node.add_connection(&connection::Connection::new(to.to_string(), *weight));
+            }
+            // Frozen, so a is the source.
+            NodeGrid {
+                nodes,
+                frozen: true,
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn distances_match_the_shortest_paths() -> Result<()> {
+            let mut grid = weighted(&[
+                ("a", "b", 4.0),
+                ("a", "c", 1.0),
+                ("c", "b", 2.0),
+                ("b", "d", 1.0),
+                ("c", "d", 5.0),
+            ]);
+            let mut logger = vec![];
+            grid.chandy_misra(&mut logger)?;
+            let expected = &grid.all_pairs_shortest_paths()?[0];
+            for (node, distance) in grid.nodes.iter().zip(expected) {
+                assert_eq!(
+                    grid.result(&node.name).unwrap(),
+                    &format!("distance {distance}")
+                );
+            }
+            assert!(logger.contains(&"b is at distance 3 with parent c.".to_string()));
+            assert!(logger.contains(&"d is at distance 4 with parent b.".to_string()));
+            Ok(())
+        }
+
+        #[test]
+        fn negative_cycle_is_reported() {
+            let mut grid = weighted(&[
+                ("a", "b", 1.0),
+                ("b", "c", -2.0),
+                ("c", "b", 1.0),
+                ("c", "d", 1.0),
+            ]);
+            let mut logger = vec![];
+            assert!(grid.chandy_misra(&mut logger).is_err());
+            assert!(logger.last().unwrap().contains("negative cycle"));
+        }
+    }
+}
@@ -1,11 +1,11 @@
 use anyhow::{Ok, Result, anyhow};
 use displaydoc::Display;
 use rand::{
-    random_range,
+    Rng, random_range,
     seq::{IndexedRandom, IteratorRandom},
 };
 use std::{
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     fmt::{Display, format},
     ops::{Deref, DerefMut},
 };
@@ -14,7 +14,7 @@ use crate::node::{Node, connection};
 use crate::{NodeGrid, SelectedAlgorithm, nodegrid::algorithms::*};
 
 #[derive(Debug, Display, Default, Clone)]
-struct Snapshot<T: Mesg> {
+pub(super) struct Snapshot<T: Mesg> {
     state: isize,
     timestamp: LamportsClock,
     messages: MessageVec<T>,
@@ -35,6 +35,205 @@ impl<T: Mesg> std::fmt::Display for Snapshot<T> {
     }
 }
 
+/// Senders of the channels into every node.
+fn incoming_channels<'a>(nodes: impl Iterator<Item = &'a Node>) -> HashMap<String, Vec<String>> {
+    let mut incoming: HashMap<String, Vec<String>> = HashMap::new();
+    for node in nodes {
+        incoming.entry(node.name.clone()).or_default();
+        for connection in node.connections.iter() {
+            incoming
+                .entry(connection.other.clone())
+                .or_default()
+                .push(node.name.clone());
+        }
+    }
+    incoming
+}
+
+/// Basic message of a snapshot algorithm, which moves a unit of state between
+/// two nodes.
+pub(super) trait Transfer: Mesg {
+    /// Channels the algorithm sends its messages over.
+    const CHANNELS: Delivery;
+    /// Change to the total of node states while the message is in transit.
+    fn amount(&self) -> isize;
+}
+
+/// Node of a snapshot algorithm.
+pub(super) trait SnapshotNode<M: Transfer>: NodeLike {
+    /// Whether the run ends as soon as every node has recorded, even with
+    /// basic messages still in transit.
+    const ENDS_WHEN_RECORDED: bool;
+
+    fn snapshot(&self) -> Option<&Snapshot<M>>;
+    /// The part of the snapshot recorded by this node is final.
+    fn recorded(&self) -> bool;
+    /// Sends a random basic message to one of the neighbours, if the node has
+    /// any.
+    fn random_process(&mut self, rng: &mut StdRng, logger: &mut Vec<String>) -> Option<M>;
+    /// Takes the snapshot of the node, returning the control messages it sends.
+    fn create_snapshot(&mut self, logger: &mut Vec<String>) -> VecDeque<M>;
+    /// Handles a delivered message, returning the messages sent in response.
+    fn handle_message(&mut self, mesg: M, logger: &mut Vec<String>) -> VecDeque<M>;
+}
+
+impl<N, M> Algorithm<N, M>
+where
+    N: SnapshotNode<M>,
+    M: Transfer,
+{
+    fn snapshot_recorded(&self) -> bool {
+        self.nodes.iter().all(N::recorded)
+    }
+
+    /// Node states plus the increments and decrements in transit, as recorded
+    /// by a finished snapshot.
+    fn recorded_total(&self) -> Option<isize> {
+        if !self.snapshot_recorded() {
+            return None;
+        }
+        Some(
+            self.nodes
+                .iter()
+                .filter_map(N::snapshot)
+                .map(|s| s.state + s.messages.iter().map(M::amount).sum::<isize>())
+                .sum(),
+        )
+    }
+
+    /// Lets `times` random nodes send a random basic message, unless the
+    /// run is frozen.
+    fn process_randomly(&mut self, times: usize, logger: &mut Vec<String>) {
+        if self.frozen {
+            return;
+        }
+        for _ in 0..times {
+            let (node, rng) = self.random_node();
+            if let Some(mesg) = node.random_process(rng, logger) {
+                self.enqueue(mesg, M::CHANNELS);
+            }
+        }
+    }
+
+    /// Lets the initiator take its snapshot between random basic messages, or
+    /// else delivers the next message. A node which answers a message sends
+    /// random basic messages along with its answer.
+    fn snapshot_step(&mut self, logger: &mut Vec<String>) -> Step {
+        if let Some(initiator) = self.initiators.pop_front() {
+            self.process_randomly(self.traffic.around_start, logger);
+            let sent = self.node_by_name(initiator.clone()).create_snapshot(logger);
+            for mesg in sent {
+                self.enqueue(mesg, M::CHANNELS);
+            }
+            self.process_randomly(self.traffic.around_start, logger);
+            return Step::Initiated(initiator);
+        }
+        if !self.has_messages() {
+            return Step::Finished;
+        }
+        if N::ENDS_WHEN_RECORDED && self.snapshot_recorded() {
+            logger.push(format!(
+                "Snapshot recorded with {} messages still in transit.",
+                self.messages.len()
+            ));
+            return Step::Finished;
+        }
+        if self.limit_reached(logger) {
+            return Step::Truncated;
+        }
+        let mesg = self.pop_mesg().unwrap();
+        let delivered = mesg.to_string();
+        let sent = self
+            .node_by_name(mesg.destination().to_string())
+            .handle_message(mesg, logger);
+        if !sent.is_empty() {
+            for mesg in sent {
+                self.enqueue(mesg, M::CHANNELS);
+            }
+            self.process_randomly(self.traffic.per_response, logger);
+        }
+        Step::Delivered(delivered)
+    }
+
+    fn log_unrecorded(&self, logger: &mut Vec<String>) {
+        logger.push("Snapshot did not complete.".to_string());
+        let missing: Vec<&str> = self
+            .nodes
+            .iter()
+            .filter(|n| !n.recorded())
+            .map(|n| n.name())
+            .collect();
+        logger.push(format!("No snapshot recorded by {}.", missing.join(", ")));
+    }
+
+    fn log_snapshots(&self, logger: &mut Vec<String>) {
+        for node in self.nodes.iter() {
+            logger.push(format!(
+                "{:?}",
+                node.snapshot()
+                    .map(Snapshot::to_string)
+                    .unwrap_or("None".to_string())
+            ));
+        }
+        logger.push(String::new());
+    }
+}
+
+impl NodeGrid {
+    /// Gives every node a random initial state between `-range` and `range`, so
+    /// the snapshots have a non-trivial total to conserve.
+    pub(crate) fn seed_states<R: Rng>(
+        &mut self,
+        range: isize,
+        rng: &mut R,
+        logger: &mut Vec<String>,
+    ) {
+        for node in self.nodes.iter_mut() {
+            node.state = rng.random_range(-range as i64..=range as i64) as isize;
+        }
+        logger.push(format!(
+            "Assigned initial states {}.",
+            self.nodes
+                .iter()
+                .map(|n| format!("{}={}", n.name, n.state))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        logger.push(format!("Initial total: {}", self.initial_total()));
+    }
+
+    pub(crate) fn initial_total(&self) -> isize {
+        self.nodes.iter().map(|n| n.state).sum()
+    }
+
+    /// Runs Chandy-Lamport and Lai-Yang on the grid, and checks both snapshots
+    /// record the same conserved total of node states and messages in transit.
+    pub(crate) fn compare_snapshots(&mut self, logger: &mut Vec<String>) -> Result<()> {
+        let chandy_lamport = self.chandy_lamport_snapshot(logger)?;
+        logger.push(String::new());
+        let lai_yang = self.lai_yang_snapshot(logger)?;
+        logger.push(String::new());
+        match (chandy_lamport, lai_yang) {
+            (Some(a), Some(b)) if a == b => {
+                logger.push(format!("Pass: both snapshots record a total of {a}."));
+                Ok(())
+            }
+            (a, b) => {
+                let show = |t: Option<isize>| t.map_or("nothing".to_string(), |t| t.to_string());
+                logger.push(format!(
+                    "Fail: Chandy-Lamport records {}, Lai-Yang records {}.",
+                    show(a),
+                    show(b)
+                ));
+                Err(anyhow!("Snapshots disagree."))
+            }
+        }
+    }
+}
+
+pub(super) use chandylamport::PSEUDOCODE as CHANDY_LAMPORT_PSEUDOCODE;
+pub(super) use laiyang::PSEUDOCODE as LAI_YANG_PSEUDOCODE;
+
 mod chandylamport {
     use anyhow::{Ok, Result, anyhow};
     use displaydoc::Display;
@@ -51,17 +250,116 @@ mod chandylamport {
         nodegrid::{NodeGrid, SelectedAlgorithm, algorithms::snapshots::*, algorithms::*},
     };
 
+    pub(in crate::nodegrid::algorithms) const PSEUDOCODE: &str = "\
+initiator:
+    take snapshot
+    send <mark> on every outgoing channel
+
+on receiving <mark> from q:
+    if no snapshot taken:
+        take snapshot
+        send <mark> on every outgoing channel
+    stop recording the channel from q
+    if <mark> received on every incoming channel:
+        snapshot is recorded
+
+on receiving a basic message m from q:
+    if snapshot taken and still recording the channel from q:
+        save m in snapshot as in transit
+    handle m";
+
     #[derive(Debug, Default, Clone)]
     struct AlgNode {
         node: Node,
         state: isize,
+        /// Nodes with a channel towards this node.
+        incoming: Vec<String>,
         received: Vec<String>,
         snapshot: Option<Snapshot<Message>>,
+        /// Number of messages sent and received so far.
+        events: usize,
+        /// Number of events before the snapshot was taken.
+        cut: usize,
+        /// Basic messages received before the snapshot was taken.
+        received_before_cut: Vec<Message>,
     }
 
     impl AlgNode {
+        fn log_if_recorded(&self, logger: &mut Vec<String>) {
+            if self.recorded() {
+                logger.push(format!(
+                    "{} has received <mark> on all incoming channels and stops recording.",
+                    self.name()
+                ));
+            }
+        }
+
+        /// Counts a send or receive, returning its number.
+        fn event(&mut self) -> usize {
+            self.events += 1;
+            self.events
+        }
+
+        fn update_snapshot(&mut self, mesg: Message, logger: &mut Vec<String>) {
+            if let Some(snapshot) = &mut self.snapshot
+                && !self.received.contains(&mesg.sender)
+            {
+                log_routine(
+                    logger,
+                    format!("{} saves {mesg} in snapshot.", self.node.name),
+                );
+                snapshot.messages.push(mesg);
+            }
+        }
+    }
+
+    impl SnapshotNode<Message> for AlgNode {
+        const ENDS_WHEN_RECORDED: bool = true;
+
+        fn snapshot(&self) -> Option<&Snapshot<Message>> {
+            self.snapshot.as_ref()
+        }
+
+        /// The node has taken its snapshot and received <mark> on every incoming
+        /// channel, so its part of the snapshot is final.
+        fn recorded(&self) -> bool {
+            self.snapshot.is_some() && self.incoming.iter().all(|n| self.received.contains(n))
+        }
+
+        fn random_process(
+            &mut self,
+            rng: &mut StdRng,
+            logger: &mut Vec<String>,
+        ) -> Option<Message> {
+            let destination = self.node.connections.iter().choose(rng)?;
+            let mesg = Message::random(self.name_clone(), destination.other.clone(), rng);
+            let mesg = Message {
+                sent_at: self.event(),
+                ..mesg
+            };
+            match mesg.kind {
+                MesgKind::Decrement => {
+                    self.state += 1;
+                    log_routine(
+                        logger,
+                        format!("{}={} and send {mesg}", self.name(), self.state),
+                    );
+                }
+                MesgKind::Increment => {
+                    self.state -= 1;
+                    log_routine(
+                        logger,
+                        format!("{}={} and send {mesg}", self.name(), self.state),
+                    );
+                }
+                _ => {}
+            };
+            Some(mesg)
+        }
+
         fn create_snapshot(&mut self, logger: &mut Vec<String>) -> VecDeque<Message> {
             self.snapshot = Some(Snapshot::new(self.state));
+            self.cut = self.events;
             logger.push(format!(
                 "{} took {}",
                 self.name(),
@@ -71,19 +369,26 @@ mod chandylamport {
             let mut outgoing = VecDeque::new();
 
             for connection in self.node.connections.iter() {
+                self.events += 1;
                 outgoing.push_back(Message {
                     sender: self.name_clone(),
                     destination: connection.other.clone(),
                     kind: MesgKind::Mark,
+                    sent_at: self.events,
                 });
             }
             log_sent_messages(&outgoing, logger);
+            self.log_if_recorded(logger);
 
             outgoing
         }
 
         fn handle_message(&mut self, mesg: Message, logger: &mut Vec<String>) -> VecDeque<Message> {
-            logger.push(format!("{} received {mesg}", self.name()));
+            log_routine(logger, format!("{} received {mesg}", self.name()));
+            self.event();
+            if self.snapshot.is_none() && mesg.kind != MesgKind::Mark {
+                self.received_before_cut.push(mesg.clone());
+            }
 
             match mesg.kind {
                 MesgKind::Mark => {
@@ -98,6 +403,7 @@ mod chandylamport {
                         self.name(),
                         mesg.sender
                     ));
+                    self.log_if_recorded(logger);
                     output
                 }
                 MesgKind::Increment => {
@@ -112,37 +418,6 @@ mod chandylamport {
                 }
             }
         }
-
-        fn update_snapshot(&mut self, mesg: Message, logger: &mut Vec<String>) {
-            if let Some(snapshot) = &mut self.snapshot {
-                if !self.received.contains(&mesg.sender) {
-                    logger.push(format!("{} saves {mesg} in snapshot.", self.node.name));
-                    snapshot.messages.push(mesg);
-                }
-            }
-        }
-
-        fn random_process(&mut self, logger: &mut Vec<String>) -> Message {
-            let destination = self
-                .node
-                .connections
-                .iter()
-                .choose(&mut rand::rng())
-                .expect("Node has no connections.");
-            let mesg = Message::random(self.name_clone(), destination.other.clone());
-            match mesg.kind {
-                MesgKind::Decrement => {
-                    self.state += 1;
-                    logger.push(format!("{}={} and send {mesg}", self.name(), self.state));
-                }
-                MesgKind::Increment => {
-                    self.state -= 1;
-                    logger.push(format!("{}={} and send {mesg}", self.name(), self.state));
-                }
-                _ => {}
-            };
-            mesg
-        }
     }
 
     #[derive(Debug, Display, Default, Clone)]
@@ -151,19 +426,44 @@ mod chandylamport {
         sender: String,
         destination: String,
         kind: MesgKind,
+        /// Event number of the send at the sender.
+        sent_at: usize,
+    }
+    impl Mesg for Message {
+        fn sender(&self) -> &str {
+            &self.sender
+        }
+        fn destination(&self) -> &str {
+            &self.destination
+        }
+        fn kind(&self) -> String {
+            self.kind.to_string()
+        }
     }
-    impl Mesg for Message {}
     impl Fifo for Message {}
 
+    impl Transfer for Message {
+        const CHANNELS: Delivery = Delivery::FrontFifo;
+
+        fn amount(&self) -> isize {
+            match self.kind {
+                MesgKind::Increment => 1,
+                MesgKind::Decrement => -1,
+                MesgKind::Mark => 0,
+            }
+        }
+    }
+
     impl Message {
-        fn random(sender: String, destination: String) -> Self {
+        fn random(sender: String, destination: String, rng: &mut StdRng) -> Self {
             Self {
                 sender,
                 destination,
                 kind: [MesgKind::Increment, MesgKind::Decrement]
-                    .choose(&mut rand::rng())
+                    .choose(rng)
                     .unwrap()
                     .to_owned(),
+                ..Default::default()
             }
         }
     }
@@ -181,60 +481,121 @@ mod chandylamport {
 
     impl NodeGrid {
         pub fn chandy_lamport(&mut self, logger: &mut Vec<String>) -> Result<()> {
+            self.chandy_lamport_snapshot(logger).map(|_| ())
+        }
+
+        /// Runs Chandy-Lamport, returning the conserved total the snapshot recorded.
+        pub(in crate::nodegrid::algorithms::snapshots) fn chandy_lamport_snapshot(
+            &mut self,
+            logger: &mut Vec<String>,
+        ) -> Result<Option<isize>> {
+            let mut algorithm = self.chandy_lamport_algorithm(logger)?;
+            let result = algorithm.run(logger);
+            self.store_run(&algorithm);
+            result.map(|_| algorithm.recorded_total())
+        }
+
+        /// Sets up Chandy-Lamport to be run one step at a time.
+        pub(in crate::nodegrid::algorithms) fn chandy_lamport_stepped(
+            &self,
+            logger: &mut Vec<String>,
+        ) -> Result<Box<dyn SteppedRun>> {
+            Ok(Box::new(self.chandy_lamport_algorithm(logger)?))
+        }
+
+        /// Sets up Chandy-Lamport on the grid, with the initiator yet to take
+        /// its snapshot.
+        fn chandy_lamport_algorithm(
+            &self,
+            logger: &mut Vec<String>,
+        ) -> Result<Algorithm<AlgNode, Message>> {
             self.check_not_empty(logger)?;
             let mut algorithm: Algorithm<AlgNode, Message> = Algorithm::new(&self.nodes);
+            self.configure(&mut algorithm);
+            algorithm.find_incoming();
             logger.push(format!(
                 "Started Chandy-Lamport snapshot with {} nodes.",
                 algorithm.nodes.len()
             ));
-            algorithm.run(logger)
+            if algorithm.frozen {
+                logger.push("Random processing disabled.".to_string());
+            }
+            let initiator = algorithm.choose_initiator(logger);
+            algorithm.initiators.push_back(initiator);
+            Ok(algorithm)
         }
     }
 
     impl Algorithm<AlgNode, Message> {
-        fn run(&mut self, logger: &mut Vec<String>) -> Result<()> {
-            let initiator = self.choose_initiator(logger);
-
-            for i in 0..5 {
-                let node = self.random_node();
-                let mesg = node.random_process(logger);
-                self.add_mesg(mesg);
+        fn find_incoming(&mut self) {
+            let mut incoming = incoming_channels(self.nodes.iter().map(|n| &n.node));
+            for node in self.nodes.iter_mut() {
+                node.incoming = incoming.remove(node.name()).unwrap_or_default();
             }
+        }
 
-            let mut response = self.node_by_name(initiator).create_snapshot(logger);
-            self.add_mesg_iter(&mut response);
-
-            for i in 0..5 {
-                let node = self.random_node();
-                let mesg = node.random_process(logger);
-                self.add_mesg(mesg);
+        /// Checks that the snapshot is a consistent cut: messages recorded in
+        /// transit were sent before the snapshot of their sender, and no message
+        /// received before a snapshot was sent after the snapshot of its sender.
+        fn check_cut(&self, logger: &mut Vec<String>) -> Result<()> {
+            let cut = |name: &str| self.nodes.iter().find(|n| n.name() == name).unwrap().cut;
+            let mut consistent = true;
+            for node in self.nodes.iter() {
+                let in_transit = node.snapshot.iter().flat_map(|s| s.messages.iter());
+                for mesg in in_transit.filter(|m| m.sent_at > cut(&m.sender)) {
+                    consistent = false;
+                    logger.push(format!(
+                        "{mesg} is recorded in transit, but was sent after the snapshot of {}.",
+                        mesg.sender
+                    ));
+                }
+                for mesg in node
+                    .received_before_cut
+                    .iter()
+                    .filter(|m| m.sent_at > cut(&m.sender))
+                {
+                    consistent = false;
+                    logger.push(format!(
+                        "{mesg} was received before the snapshot of {}, but sent after the snapshot of {}.",
+                        node.name(),
+                        mesg.sender
+                    ));
+                }
             }
-
-            while self.has_messages() {
-                let mesg = self.pop_mesg().unwrap();
-                let mut response = self
-                    .node_by_name(mesg.destination.clone())
-                    .handle_message(mesg, logger);
-                if !response.is_empty() {
-                    self.add_mesg_iter(&mut response);
-                    for i in 0..3 {
-                        let node = self.random_node();
-                        let mesg = node.random_process(logger);
-                        self.add_mesg(mesg);
-                    }
+            match consistent {
+                true => {
+                    logger.push("Snapshot is a consistent cut.".to_string());
+                    Ok(())
                 }
+                false => Err(anyhow!("Snapshot is not a consistent cut.")),
             }
+        }
+    }
 
-            verify_snapshot(logger, self);
-
-            Ok(())
+    impl SteppedRun for Algorithm<AlgNode, Message> {
+        fn advance(&mut self, logger: &mut Vec<String>) -> Step {
+            self.snapshot_step(logger)
+        }
+        fn conclude(&mut self, logger: &mut Vec<String>) -> Result<()> {
+            self.log_metrics(logger);
+            verify_snapshot(logger, self)
+        }
+        fn store(&self, grid: &mut NodeGrid) {
+            grid.store_run(self);
+        }
+        fn in_transit(&self) -> usize {
+            self.messages.len()
         }
     }
 
-    fn verify_snapshot(logger: &mut Vec<String>, algorithm: &Algorithm<AlgNode, Message>) {
+    fn verify_snapshot(
+        logger: &mut Vec<String>,
+        algorithm: &Algorithm<AlgNode, Message>,
+    ) -> Result<()> {
         let nodes = &algorithm.nodes;
         logger.push(String::new());
-        if nodes.iter().all(|n| n.snapshot.is_some()) {
+        let mut cut = Ok(());
+        if algorithm.snapshot_recorded() {
             logger.push("Snapshot completed.".to_string());
             let snapshot_sum_of_states = nodes
                 .iter()
@@ -258,25 +619,19 @@ mod chandylamport {
             });
             logger.push(format!("Node total: {snapshot_sum_of_states}"));
             logger.push(format!("Message total: {snapshot_sum_of_messages}"));
+            cut = algorithm.check_cut(logger);
         } else {
-            logger.push("Snapshot did not complete.".to_string());
-        }
-        for node in nodes.iter() {
-            logger.push(format!(
-                "{:?}",
-                node.snapshot
-                    .as_ref()
-                    .map(Snapshot::to_string)
-                    .unwrap_or("None".to_string())
-            ));
+            algorithm.log_unrecorded(logger);
         }
-        logger.push(String::new());
+        algorithm.log_snapshots(logger);
+        cut
     }
 
     impl From<&Node> for AlgNode {
         fn from(node: &Node) -> Self {
             AlgNode {
                 node: node.clone(),
+                state: node.state,
                 ..Default::default()
             }
         }
@@ -286,6 +641,113 @@ mod chandylamport {
         fn name(&self) -> &str {
             &self.node.name
         }
+        fn result(&self) -> String {
+            let snapshot = self
+                .snapshot
+                .as_ref()
+                .map(Snapshot::to_string)
+                .unwrap_or("no snapshot".to_string());
+            format!("state={}, {snapshot}", self.state)
+        }
+        fn display_state(&self) -> Option<NodeDisplayState> {
+            self.snapshot.as_ref().map(|_| NodeDisplayState::Recorded)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::node::connection::Connection;
+
+        fn pair() -> Algorithm<AlgNode, Message> {
+            let nodes: Vec<Node> = [("a", "b"), ("b", "a")]
+                .into_iter()
+                .map(|(name, other)| Node {
+                    name: name.to_string(),
+                    connections: vec![Connection::new(other.to_string(), 1.0)],
+                    ..Default::default()
+                })
+                .collect();
+            let mut algorithm: Algorithm<AlgNode, Message> = Algorithm::new(&nodes);
+            algorithm.find_incoming();
+            algorithm
+        }
+
+        #[test]
+        fn recording_ends_with_basic_messages_in_transit() {
+            let mut algorithm = pair();
+            let mut logger = vec![];
+
+            let mut marks = algorithm
+                .node_by_name("a".to_string())
+                .create_snapshot(&mut logger);
+            algorithm.add_mesg_iter(&mut marks);
+            algorithm.add_mesg(Message {
+                sender: "a".to_string(),
+                destination: "b".to_string(),
+                kind: MesgKind::Increment,
+                ..Default::default()
+            });
+            while !algorithm.advance(&mut logger).is_last() {}
+
+            assert!(algorithm.snapshot_recorded());
+            // b answers the first <mark> with its own, followed by three basic messages.
+            assert_eq!(algorithm.messages.len(), 3);
+            assert!(algorithm.messages.iter().all(|m| m.kind != MesgKind::Mark));
+            assert!(
+                logger.contains(&"Snapshot recorded with 3 messages still in transit.".to_string())
+            );
+            // The increment was sent after a's <mark>, so it is not part of the snapshot.
+            assert!(
+                algorithm
+                    .nodes
+                    .iter()
+                    .all(|n| n.snapshot.as_ref().unwrap().messages.is_empty())
+            );
+            assert!(algorithm.check_cut(&mut logger).is_ok());
+        }
+
+        #[test]
+        fn detects_inconsistent_cut() {
+            // Delivering the last message first, an increment a sends after its
+            // snapshot overtakes its <mark>, and reaches b before b's snapshot.
+            let mut algorithm = pair();
+            algorithm.delivery = Some(Delivery::LifoStack);
+            algorithm.frozen = true;
+            let mut logger = vec![];
+            let mut marks = algorithm
+                .node_by_name("a".to_string())
+                .create_snapshot(&mut logger);
+            algorithm.add_mesg_iter(&mut marks);
+            let mesg = algorithm
+                .node_by_name("a".to_string())
+                .random_process(&mut StdRng::seed_from_u64(0), &mut logger)
+                .unwrap();
+            algorithm.add_mesg(mesg);
+            while !algorithm.advance(&mut logger).is_last() {}
+
+            assert!(algorithm.snapshot_recorded());
+            assert!(algorithm.check_cut(&mut logger).is_err());
+            assert!(logger.last().unwrap().ends_with(
+                "was received before the snapshot of b, but sent after the snapshot of a."
+            ));
+
+            // A message recorded in transit which its sender only sent after its
+            // snapshot is caught as well.
+            let mut algorithm = pair();
+            let mut marks = algorithm
+                .node_by_name("a".to_string())
+                .create_snapshot(&mut logger);
+            algorithm.add_mesg_iter(&mut marks);
+            while !algorithm.advance(&mut logger).is_last() {}
+            let late = algorithm
+                .node_by_name("a".to_string())
+                .random_process(&mut StdRng::seed_from_u64(0), &mut logger)
+                .unwrap();
+            let b = algorithm.node_by_name("b".to_string());
+            b.snapshot.as_mut().unwrap().messages.push(late);
+            assert!(algorithm.check_cut(&mut logger).is_err());
+        }
     }
 }
 
@@ -297,7 +759,6 @@ mod laiyang {
         collections::{HashMap, VecDeque},
         default,
         fmt::format,
-        u8,
     };
 
     use crate::{
@@ -305,132 +766,75 @@ mod laiyang {
         nodegrid::{NodeGrid, SelectedAlgorithm, algorithms::snapshots::*, algorithms::*},
     };
 
+    pub(in crate::nodegrid::algorithms) const PSEUDOCODE: &str = "\
+every basic message carries whether its sender took its snapshot
+
+initiator:
+    take snapshot
+    send <mark(n)> to every neighbour, n basic messages sent to it before
+
+on receiving <mark(n)> from q:
+    if no snapshot taken:
+        take snapshot and send marks
+    expect n pre-snapshot messages from q
+
+on receiving a basic message m from q:
+    if m was sent after the snapshot of q and no snapshot taken:
+        take snapshot and send marks
+    if m was sent before the snapshot of q:
+        count m as received from q
+        if snapshot taken:
+            save m in snapshot as in transit
+    handle m
+
+snapshot is recorded when every expected pre-snapshot message has arrived";
+
     #[derive(Debug, Default, Clone)]
     struct AlgNode {
         node: Node,
         state: isize,
-        mesg_received: HashMap<String, u8>,
-        mesg_sent: HashMap<String, u8>,
-        mesg_pre_snapshot: HashMap<String, u8>,
+        /// Nodes with a channel towards this node.
+        incoming: Vec<String>,
+        /// Basic messages received per channel that were sent before the snapshot
+        /// of their sender.
+        mesg_received: HashMap<String, usize>,
+        mesg_sent: HashMap<String, usize>,
+        /// Number of basic messages each sender sent before its snapshot, as told
+        /// by its control message.
+        mesg_pre_snapshot: HashMap<String, usize>,
         snapshot: Option<Snapshot<Message>>,
-        done: bool,
     }
     impl AlgNode {
-        fn create_snapshot(&mut self, logger: &mut Vec<String>) -> VecDeque<Message> {
-            self.snapshot = Some(Snapshot::new(self.state));
-            logger.push(format!(
-                "{} took {}",
-                self.name(),
-                self.snapshot.as_ref().unwrap()
-            ));
-
-            let mut outgoing = VecDeque::new();
-            self.send_marks(&mut outgoing);
-            log_sent_messages(&outgoing, logger);
-
-            outgoing
+        fn log_if_recorded(&self, logger: &mut Vec<String>) {
+            if self.recorded() {
+                logger.push(format!(
+                    "{} has received all pre-snapshot messages and stops recording.",
+                    self.name()
+                ));
+            }
         }
 
-        fn handle_message(&mut self, mesg: Message, logger: &mut Vec<String>) -> VecDeque<Message> {
-            logger.push(format!("{} received {mesg}", self.name()));
-            let sender = mesg.sender;
-
-            self.mesg_received
-                .entry(sender)
-                .and_modify(|c| *c += 1)
-                .or_insert(1);
-
-            //     let output = match mesg.kind {
-            //         MesgKind::Mark(mesg_count) => {
-            //             let mut output = VecDeque::new();
-            //             if self.snapshot.is_none() {
-            //                 output = self.create_snapshot(logger);
-            //             }
-            //             self.mesg_pre_snapshot.insert(mesg.sender, mesg_count);
-
-            //             logger.push(format!("{} notes it has received {mesg}", self.name(),));
-            //             output
-            //         }
-            //         MesgKind::Increment(post_snapshot) => {
-            //             let mut output = VecDeque::new();
-            //             if !post_snapshot {
-            //                 self.update_snapshot(mesg, logger);
-            //             } else {
-            //                 if self.snapshot.is_none() {
-            //                     logger.push(format!(
-            //                         "{} takes a snapshot, because the received message is true.",
-            //                         self.name()
-            //                     ));
-            //                     output = self.create_snapshot(logger);
-            //                     self.mesg_pre_snapshot.insert(mesg.sender, u8::MAX);
-            //                 }
-            //             }
-            //             self.state += 1;
-            //             output
-            //         }
-            //         MesgKind::Decrement(post_snapshot) => {
-            //             let mut output = VecDeque::new();
-            //             if !post_snapshot {
-            //                 self.update_snapshot(mesg, logger);
-            //             } else {
-            //                 if self.snapshot.is_none() {
-            //                     logger.push(format!(
-            //                         "{} takes a snapshot, because the received message is true.",
-            //                         self.name()
-            //                     ));
-            //                     output = self.create_snapshot(logger);
-            //                     self.mesg_pre_snapshot.insert(mesg.sender, u8::MAX);
-            //                 }
-            //             }
-            //             self.state -= 1;
-            //             output
-            //         }
-            //     };
-            //     if self.snapshot.is_some() && self.mesg_pre_snapshot == self.mesg_received {
-            //         self.done = true;
-            //     }
-            // output
-            todo!()
-        }
-
-        // fn update_snapshot(&mut self, mesg: Message, logger: &mut Vec<String>) {
-        //     if let Some(snapshot) = &mut self.snapshot {
-        //         if !self.received.contains(&mesg.sender) {
-        //             logger.push(format!("{} saves {mesg} in snapshot.", self.node.name));
-        //             snapshot.messages.push(mesg);
-        //         }
-        //     }
-        // }
-
-        fn random_process(&mut self, logger: &mut Vec<String>) -> Message {
-            let mesg = self.send_random();
-            match mesg.kind {
-                MesgKind::Decrement(_) => {
-                    self.state += 1;
-                    logger.push(format!("{}={} and send {mesg}", self.name(), self.state));
-                }
-                MesgKind::Increment(_) => {
-                    self.state -= 1;
-                    logger.push(format!("{}={} and send {mesg}", self.name(), self.state));
-                }
-                _ => {}
-            };
-            mesg
+        /// A message sent before its sender's snapshot but received after ours was
+        /// in transit when the snapshot was taken.
+        fn update_snapshot(&mut self, mesg: &Message, logger: &mut Vec<String>) {
+            if let Some(snapshot) = &mut self.snapshot {
+                log_routine(
+                    logger,
+                    format!("{} saves {mesg} in snapshot.", self.node.name),
+                );
+                snapshot.messages.push(mesg.clone());
+            }
         }
 
-        fn send_random(&mut self) -> Message {
-            let destination = self
-                .node
-                .connections
-                .iter()
-                .choose(&mut rand::rng())
-                .expect("Node has no connections.");
+        fn send_random(&mut self, rng: &mut StdRng) -> Option<Message> {
+            let destination = self.node.connections.iter().choose(rng)?;
             *self.mesg_sent.get_mut(&destination.other).unwrap() += 1;
-            Message::random(
+            Some(Message::random(
                 self.name_clone(),
                 destination.other.clone(),
                 self.snapshot.is_some(),
-            )
+                rng,
+            ))
         }
 
         fn send_marks(&mut self, outgoing: &mut VecDeque<Message>) {
@@ -447,6 +851,104 @@ mod laiyang {
         }
     }
 
+    impl SnapshotNode<Message> for AlgNode {
+        const ENDS_WHEN_RECORDED: bool = false;
+
+        fn snapshot(&self) -> Option<&Snapshot<Message>> {
+            self.snapshot.as_ref()
+        }
+
+        /// The node has taken its snapshot and received every message its
+        /// neighbours sent before theirs, so its part of the snapshot is final.
+        fn recorded(&self) -> bool {
+            self.snapshot.is_some()
+                && self.incoming.iter().all(|sender| {
+                    self.mesg_pre_snapshot.get(sender).is_some_and(|&count| {
+                        self.mesg_received.get(sender).copied().unwrap_or(0) == count
+                    })
+                })
+        }
+
+        fn random_process(
+            &mut self,
+            rng: &mut StdRng,
+            logger: &mut Vec<String>,
+        ) -> Option<Message> {
+            let mesg = self.send_random(rng)?;
+            match mesg.kind {
+                MesgKind::Decrement(_) => {
+                    self.state += 1;
+                    log_routine(
+                        logger,
+                        format!("{}={} and send {mesg}", self.name(), self.state),
+                    );
+                }
+                MesgKind::Increment(_) => {
+                    self.state -= 1;
+                    log_routine(
+                        logger,
+                        format!("{}={} and send {mesg}", self.name(), self.state),
+                    );
+                }
+                _ => {}
+            };
+            Some(mesg)
+        }
+
+        fn create_snapshot(&mut self, logger: &mut Vec<String>) -> VecDeque<Message> {
+            self.snapshot = Some(Snapshot::new(self.state));
+            logger.push(format!(
+                "{} took {}",
+                self.name(),
+                self.snapshot.as_ref().unwrap()
+            ));
+
+            let mut outgoing = VecDeque::new();
+            self.send_marks(&mut outgoing);
+            log_sent_messages(&outgoing, logger);
+
+            outgoing
+        }
+
+        fn handle_message(&mut self, mesg: Message, logger: &mut Vec<String>) -> VecDeque<Message> {
+            log_routine(logger, format!("{} received {mesg}", self.name()));
+            let mut output = VecDeque::new();
+
+            match mesg.kind {
+                MesgKind::Mark(count) => {
+                    if self.snapshot.is_none() {
+                        output = self.create_snapshot(logger);
+                    }
+                    logger.push(format!(
+                        "{} notes {} sent {count} messages before its snapshot.",
+                        self.name(),
+                        mesg.sender
+                    ));
+                    self.mesg_pre_snapshot.insert(mesg.sender, count);
+                }
+                MesgKind::Increment(post_snapshot) | MesgKind::Decrement(post_snapshot) => {
+                    if post_snapshot && self.snapshot.is_none() {
+                        logger.push(format!(
+                            "{} takes a snapshot, because the received message is true.",
+                            self.name()
+                        ));
+                        output = self.create_snapshot(logger);
+                    }
+                    if !post_snapshot {
+                        *self.mesg_received.entry(mesg.sender.clone()).or_insert(0) += 1;
+                        self.update_snapshot(&mesg, logger);
+                    }
+                    match mesg.kind {
+                        MesgKind::Increment(_) => self.state += 1,
+                        _ => self.state -= 1,
+                    }
+                }
+            }
+            self.log_if_recorded(logger);
+            output
+        }
+    }
+
     #[derive(Debug, Display, Default, Clone)]
     #[displaydoc("<{kind}> {sender}->{destination}")]
     struct Message {
@@ -454,11 +956,38 @@ mod laiyang {
         destination: String,
         kind: MesgKind,
     }
-    impl Mesg for Message {}
+    impl Mesg for Message {
+        fn sender(&self) -> &str {
+            &self.sender
+        }
+        fn destination(&self) -> &str {
+            &self.destination
+        }
+        fn kind(&self) -> String {
+            self.kind.to_string()
+        }
+    }
     impl NonFifo for Message {}
 
+    impl Transfer for Message {
+        const CHANNELS: Delivery = Delivery::RandomInsert;
+
+        fn amount(&self) -> isize {
+            match self.kind {
+                MesgKind::Increment(_) => 1,
+                MesgKind::Decrement(_) => -1,
+                MesgKind::Mark(_) => 0,
+            }
+        }
+    }
+
     impl Message {
-        fn random(sender: String, destination: String, post_snapshot: bool) -> Self {
+        fn random(
+            sender: String,
+            destination: String,
+            post_snapshot: bool,
+            rng: &mut StdRng,
+        ) -> Self {
             Self {
                 sender,
                 destination,
@@ -466,7 +995,7 @@ mod laiyang {
                     MesgKind::Increment(post_snapshot),
                     MesgKind::Decrement(post_snapshot),
                 ]
-                .choose(&mut rand::rng())
+                .choose(rng)
                 .unwrap()
                 .to_owned(),
             }
@@ -476,7 +1005,7 @@ mod laiyang {
     #[derive(Debug, Display, Clone, PartialEq, Eq)]
     enum MesgKind {
         /// mark
-        Mark(u8),
+        Mark(usize),
         /// increment
         Increment(bool),
         /// decrement
@@ -491,52 +1020,82 @@ mod laiyang {
 
     impl NodeGrid {
         pub fn lai_yang(&mut self, logger: &mut Vec<String>) -> Result<()> {
+            self.lai_yang_snapshot(logger).map(|_| ())
+        }
+
+        /// Runs Lai-Yang, returning the conserved total the snapshot recorded.
+        pub(in crate::nodegrid::algorithms::snapshots) fn lai_yang_snapshot(
+            &mut self,
+            logger: &mut Vec<String>,
+        ) -> Result<Option<isize>> {
+            let mut algorithm = self.lai_yang_algorithm(logger)?;
+            let result = algorithm.run(logger);
+            self.store_run(&algorithm);
+            result.map(|_| algorithm.recorded_total())
+        }
+
+        /// Sets up Lai-Yang to be run one step at a time.
+        pub(in crate::nodegrid::algorithms) fn lai_yang_stepped(
+            &self,
+            logger: &mut Vec<String>,
+        ) -> Result<Box<dyn SteppedRun>> {
+            Ok(Box::new(self.lai_yang_algorithm(logger)?))
+        }
+
+        /// Sets up Lai-Yang on the grid, with the initiator yet to take its
+        /// snapshot.
+        fn lai_yang_algorithm(
+            &self,
+            logger: &mut Vec<String>,
+        ) -> Result<Algorithm<AlgNode, Message>> {
             self.check_not_empty(logger)?;
             let mut algorithm: Algorithm<AlgNode, Message> = Algorithm::new(&self.nodes);
+            self.configure(&mut algorithm);
+            let mut incoming = incoming_channels(self.nodes.iter());
+            for node in algorithm.nodes.iter_mut() {
+                node.incoming = incoming.remove(node.name()).unwrap_or_default();
+            }
             logger.push(format!(
                 "Started Lai-Yang snapshot with {} nodes.",
                 algorithm.nodes.len()
             ));
-            algorithm.run(logger)
+            if algorithm.frozen {
+                logger.push("Random processing disabled.".to_string());
+            }
+            let initiator = algorithm.choose_initiator(logger);
+            algorithm.initiators.push_back(initiator);
+            Ok(algorithm)
         }
     }
-    impl Algorithm<AlgNode, Message> {
-        fn run(&mut self, logger: &mut Vec<String>) -> Result<()> {
-            let initiator = self.choose_initiator(logger);
-
-            for i in 0..5 {
-                let node = self.random_node();
-                let mesg = node.random_process(logger);
-                self.add_mesg(mesg);
-            }
 
-            let mut response = self.node_by_name(initiator).create_snapshot(logger);
-            self.add_mesg_iter(&mut response);
-
-            for i in 0..5 {
-                let node = self.random_node();
-                let mesg = node.random_process(logger);
-                self.add_mesg(mesg);
-            }
+    impl SteppedRun for Algorithm<AlgNode, Message> {
+        fn advance(&mut self, logger: &mut Vec<String>) -> Step {
+            self.snapshot_step(logger)
+        }
+        fn conclude(&mut self, logger: &mut Vec<String>) -> Result<()> {
+            self.log_metrics(logger);
+            self.verify_snapshot(logger);
+            Ok(())
+        }
+        fn store(&self, grid: &mut NodeGrid) {
+            grid.store_run(self);
+        }
+        fn in_transit(&self) -> usize {
+            self.messages.len()
+        }
+    }
 
-            while self.has_messages() {
-                let mesg = self.pop_mesg().unwrap();
-                let mut response = self
-                    .node_by_name(mesg.destination.clone())
-                    .handle_message(mesg, logger);
-                if !response.is_empty() {
-                    self.add_mesg_iter(&mut response);
-                    for i in 0..3 {
-                        let node = self.random_node();
-                        let mesg = node.random_process(logger);
-                        self.add_mesg(mesg);
-                    }
+    impl Algorithm<AlgNode, Message> {
+        fn verify_snapshot(&self, logger: &mut Vec<String>) {
+            logger.push(String::new());
+            match self.recorded_total() {
+                Some(total) => {
+                    logger.push("Snapshot completed.".to_string());
+                    logger.push(format!("Recorded total: {total}"));
                 }
+                None => self.log_unrecorded(logger),
             }
-
-            //         verify_snapshot(logger, self);
-
-            Ok(())
+            self.log_snapshots(logger);
         }
     }
 
@@ -548,6 +1107,7 @@ mod laiyang {
             }
             AlgNode {
                 node: node.clone(),
+                state: node.state,
                 mesg_sent,
                 ..Default::default()
             }
@@ -558,52 +1118,16 @@ mod laiyang {
         fn name(&self) -> &str {
             &self.node.name
         }
-    }
-
-    fn verify_snapshot(logger: &mut Vec<String>, algorithm: &Algorithm<AlgNode, Message>) {
-        let nodes = &algorithm.nodes;
-        logger.push(String::new());
-        if nodes.iter().all(|n| n.snapshot.is_some()) {
-            logger.push("Snapshot completed.".to_string());
-            let snapshot_sum_of_states = nodes
-                .iter()
-                .fold(0isize, |acc, n| acc + n.snapshot.as_ref().unwrap().state);
-            let snapshot_sum_of_messages = nodes.iter().fold(0isize, |acc, n| {
-                acc + n
-                    .snapshot
-                    .as_ref()
-                    .unwrap()
-                    .messages
-                    .iter()
-                    .filter(|m| {
-                        m.kind == MesgKind::Increment(false) || m.kind == MesgKind::Increment(true)
-                    })
-                    .count() as isize
-                    - n.snapshot
-                        .as_ref()
-                        .unwrap()
-                        .messages
-                        .iter()
-                        .filter(|m| {
-                            m.kind == MesgKind::Decrement(false)
-                                || m.kind == MesgKind::Decrement(true)
-                        })
-                        .count() as isize
-            });
-            logger.push(format!("Node total: {snapshot_sum_of_states}"));
-            logger.push(format!("Message total: {snapshot_sum_of_messages}"));
-        } else {
-            logger.push("Snapshot did not complete.".to_string());
+        fn result(&self) -> String {
+            let snapshot = self
+                .snapshot
+                .as_ref()
+                .map(Snapshot::to_string)
+                .unwrap_or("no snapshot".to_string());
+            format!("state={}, {snapshot}", self.state)
         }
-        for node in nodes.iter() {
-            logger.push(format!(
-                "{:?}",
-                node.snapshot
-                    .as_ref()
-                    .map(Snapshot::to_string)
-                    .unwrap_or("None".to_string())
-            ));
+        fn display_state(&self) -> Option<NodeDisplayState> {
+            self.snapshot.as_ref().map(|_| NodeDisplayState::Recorded)
         }
-        logger.push(String::new());
     }
 }
@@ -0,0 +1,324 @@
+use crate::nodegrid::algorithms::*;
+
+pub(super) use dijkstrascholten::PSEUDOCODE as DIJKSTRA_SCHOLTEN_PSEUDOCODE;
+
+mod dijkstrascholten {
+    use anyhow::{Result, anyhow};
+    use displaydoc::Display;
+    use rand::{Rng, rngs::StdRng, seq::IndexedRandom};
+    use std::collections::VecDeque;
+    use strum::EnumIs;
+
+    use crate::{
+        node::Node,
+        nodegrid::{NodeGrid, algorithms::*},
+    };
+
+    pub(in crate::nodegrid::algorithms) const PSEUDOCODE: &str = "\
+the initiator starts the basic computation, as root of the tree
+
+every basic message sent raises the deficit of the sender
+
+on receiving <basic> from q:
+    if not in the tree:
+        join it with q as parent
+    else:
+        send <signal> to q
+    do some work, possibly sending basic messages
+
+on receiving <signal>:
+    lower the deficit
+
+once idle with a deficit of 0:
+    if the root:
+        detect termination
+    else:
+        send <signal> to the parent and leave the tree";
+
+    /// Basic messages the computation may send per node, so it ends.
+    const WORK_PER_NODE: usize = 3;
+
+    #[derive(Debug, Default, Clone)]
+    struct AlgNode {
+        node: Node,
+        state: NodeState,
+        parent: Option<String>,
+        /// Basic messages sent and not yet signalled back.
+        deficit: usize,
+    }
+
+    #[derive(Debug, Display, Default, Clone, Copy, PartialEq, Eq, EnumIs)]
+    enum NodeState {
+        #[default]
+        /// idle
+        Idle,
+        /// in tree
+        InTree,
+        /// root
+        Root,
+        /// detected
+        Detected,
+    }
+
+    #[derive(Debug, Display, Default, Clone)]
+    #[displaydoc("<{kind}> {sender}->{destination}")]
+    struct Message {
+        sender: String,
+        destination: String,
+        kind: MesgKind,
+    }
+    impl Mesg for Message {
+        fn sender(&self) -> &str {
+            &self.sender
+        }
+        fn destination(&self) -> &str {
+            &self.destination
+        }
+        fn kind(&self) -> String {
+            self.kind.to_string()
+        }
+    }
+    impl NonFifo for Message {}
+
+    #[derive(Debug, Display, Default, Clone, Copy, PartialEq, Eq)]
+    enum MesgKind {
+        #[default]
+        /// basic
+        Basic,
+        /// signal
+        Signal,
+    }
+
+    impl From<&Node> for AlgNode {
+        fn from(node: &Node) -> Self {
+            AlgNode {
+                node: node.clone(),
+                ..Default::default()
+            }
+        }
+    }
+    impl NodeLike for AlgNode {
+        fn name(&self) -> &str {
+            &self.node.name
+        }
+        fn result(&self) -> String {
+            self.state.to_string()
+        }
+    }
+
+    impl AlgNode {
+        fn message(&self, destination: String, kind: MesgKind) -> Message {
+            Message {
+                sender: self.name_clone(),
+                destination,
+                kind,
+            }
+        }
+
+        /// Sends basic messages over outgoing connections, taken from the
+        /// `budget` left for the computation. Random work sends up to `most`
+        /// messages to random neighbours, while frozen work sends one to every
+        /// neighbour.
+        fn work(
+            &mut self,
+            most: usize,
+            rng: Option<&mut StdRng>,
+            budget: &mut usize,
+        ) -> VecDeque<Message> {
+            let neighbours: Vec<String> = self
+                .node
+                .connections
+                .iter()
+                .map(|c| c.other.clone())
+                .collect();
+            let destinations: Vec<String> = match rng {
+                Some(rng) => {
+                    let amount = rng.random_range(0..=most);
+                    (0..amount)
+                        .filter_map(|_| neighbours.choose(rng).cloned())
+                        .collect()
+                }
+                None => neighbours,
+            };
+            let sent: VecDeque<Message> = destinations
+                .into_iter()
+                .take(*budget)
+                .map(|other| self.message(other, MesgKind::Basic))
+                .collect();
+            *budget -= sent.len();
+            self.deficit += sent.len();
+            sent
+        }
+
+        fn handle_message(&mut self, mesg: Message, logger: &mut Vec<String>) -> VecDeque<Message> {
+            log_routine(logger, format!("{} received {mesg}", self.name()));
+            match mesg.kind {
+                MesgKind::Basic if self.state.is_idle() => {
+                    logger.push(format!(
+                        "{} joins the tree under {}.",
+                        self.name(),
+                        mesg.sender
+                    ));
+                    self.state = NodeState::InTree;
+                    self.parent = Some(mesg.sender);
+                    VecDeque::new()
+                }
+                MesgKind::Basic => VecDeque::from([self.message(mesg.sender, MesgKind::Signal)]),
+                MesgKind::Signal => {
+                    self.deficit -= 1;
+                    VecDeque::new()
+                }
+            }
+        }
+
+        /// Leaves the tree, or detects termination at the root, once every
+        /// basic message sent has been signalled back.
+        fn try_leave(&mut self, logger: &mut Vec<String>) -> Option<Message> {
+            if self.deficit > 0 {
+                return None;
+            }
+            match self.state {
+                NodeState::InTree => {
+                    let parent = self.parent.take().unwrap();
+                    logger.push(format!(
+                        "{} leaves the tree, signalling {parent}.",
+                        self.name()
+                    ));
+                    self.state = NodeState::Idle;
+                    Some(self.message(parent, MesgKind::Signal))
+                }
+                NodeState::Root => {
+                    self.state = NodeState::Detected;
+                    None
+                }
+                _ => None,
+            }
+        }
+    }
+
+    impl Algorithm<AlgNode, Message> {
+        /// Lets the node named `name` do its share of the basic computation.
+        fn work(&mut self, name: &str, most: usize, budget: &mut usize, logger: &mut Vec<String>) {
+            let rng = (!self.frozen).then_some(&mut self.rng);
+            let node = self.nodes.iter_mut().find(|n| n.name() == name).unwrap();
+            let mut sent = node.work(most, rng, budget);
+            log_sent_messages(&sent, logger);
+            self.add_mesg_iter(&mut sent);
+        }
+
+        fn try_leave(&mut self, name: &str, logger: &mut Vec<String>) {
+            if let Some(signal) = self.node_by_name(name.to_string()).try_leave(logger) {
+                self.add_mesg(signal);
+            }
+        }
+
+        fn run(&mut self, logger: &mut Vec<String>) -> Result<()> {
+            if self.frozen {
+                logger.push("Random processing disabled.".to_string());
+            }
+            let root = self.choose_initiator(logger);
+            self.node_by_name(root.clone()).state = NodeState::Root;
+            let mut budget = WORK_PER_NODE * self.nodes.len();
+            self.work(&root, WORK_PER_NODE, &mut budget, logger);
+            self.try_leave(&root, logger);
+
+            while self.has_messages() {
+                if self.limit_reached(logger) {
+                    break;
+                }
+                let mesg = self.pop_mesg().unwrap();
+                let destination = mesg.destination.clone();
+                let basic = mesg.kind == MesgKind::Basic;
+                let mut response = self
+                    .node_by_name(destination.clone())
+                    .handle_message(mesg, logger);
+                self.add_mesg_iter(&mut response);
+                if basic {
+                    self.work(&destination, 2, &mut budget, logger);
+                }
+                self.try_leave(&destination, logger);
+            }
+            self.log_metrics(logger);
+            self.verify_detection(&root, logger)
+        }
+
+        /// Checks that the root detected termination, and only once nothing was
+        /// left in transit and every node had left the tree.
+        fn verify_detection(&self, root: &str, logger: &mut Vec<String>) -> Result<()> {
+            let detected = self.nodes.iter().any(|n| n.state.is_detected());
+            if !detected {
+                logger.push("Termination was not detected.".to_string());
+                return Err(anyhow!("Termination was not detected."));
+            }
+            let in_tree: Vec<&str> = self
+                .nodes
+                .iter()
+                .filter(|n| n.state.is_in_tree())
+                .map(NodeLike::name)
+                .collect();
+            if self.has_messages() || !in_tree.is_empty() {
+                logger.push(format!(
+                    "Termination detected early, with {} messages in transit and {} still in the tree.",
+                    self.messages.len(),
+                    in_tree.join(", ")
+                ));
+                return Err(anyhow!("Termination was detected early."));
+            }
+            logger.push(format!("Termination detected by initiator {root}."));
+            Ok(())
+        }
+    }
+
+    impl NodeGrid {
+        pub fn dijkstra_scholten(&mut self, logger: &mut Vec<String>) -> Result<()> {
+            self.check_not_empty(logger)?;
+            let mut algorithm: Algorithm<AlgNode, Message> = Algorithm::new(&self.nodes);
+            self.configure(&mut algorithm);
+            logger.push(format!(
+                "Started Dijkstra-Scholten termination detection with {} nodes.",
+                algorithm.nodes.len()
+            ));
+            let result = algorithm.run(logger);
+            self.store_run(&algorithm);
+            result
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn initiator_detects_termination() -> Result<()> {
+            for seed in 0..10 {
+                let mut grid = NodeGrid::generate_connected(8, &mut StdRng::seed_from_u64(seed));
+                let mut logger = vec![];
+                grid.dijkstra_scholten(&mut logger)?;
+                assert!(
+                    logger
+                        .last()
+                        .unwrap()
+                        .starts_with("Termination detected by initiator")
+                );
+                let joined = logger
+                    .iter()
+                    .filter(|l| l.contains(" joins the tree"))
+                    .count();
+                let left = logger
+                    .iter()
+                    .filter(|l| l.contains(" leaves the tree"))
+                    .count();
+                assert_eq!(joined, left);
+                assert_eq!(
+                    grid.trace
+                        .iter()
+                        .filter(|r| r.event == TraceEvent::Sent && r.kind == "basic")
+                        .count()
+                        * 2,
+                    grid.messages_sent()
+                );
+            }
+            Ok(())
+        }
+    }
+}
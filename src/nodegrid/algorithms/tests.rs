@@ -0,0 +1,271 @@
+use super::*;
+use crate::{NODE_H_SPACING, NODE_V_SPACING, NODE_WIDTH, nodegrid::NodeGridDisplay};
+use rand::{SeedableRng, rngs::StdRng};
+use ratatui::{buffer::Buffer, layout::Rect};
+use strum::IntoEnumIterator;
+
+#[derive(Debug, Default, Clone)]
+struct PingNode {
+    name: String,
+    clock: LamportsClock,
+}
+
+impl From<&Node> for PingNode {
+    fn from(node: &Node) -> Self {
+        PingNode {
+            name: node.name.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+impl NodeLike for PingNode {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn result(&self) -> String {
+        String::new()
+    }
+    fn clock(&self) -> Option<LamportsClock> {
+        Some(self.clock)
+    }
+}
+
+#[derive(Debug, displaydoc::Display, Default, Clone)]
+#[displaydoc("<{kind}> {sender}->{destination}")]
+struct Ping {
+    sender: String,
+    destination: String,
+    kind: String,
+    time: Option<LamportsClock>,
+}
+impl Mesg for Ping {
+    fn sender(&self) -> &str {
+        &self.sender
+    }
+    fn destination(&self) -> &str {
+        &self.destination
+    }
+    fn kind(&self) -> String {
+        self.kind.clone()
+    }
+    fn timestamp(&self) -> Option<LamportsClock> {
+        self.time
+    }
+}
+impl Fifo for Ping {}
+
+impl Ping {
+    fn new(sender: &str, destination: &str, kind: &str) -> Self {
+        Ping {
+            sender: sender.to_string(),
+            destination: destination.to_string(),
+            kind: kind.to_string(),
+            time: None,
+        }
+    }
+}
+
+fn nodes(names: &[&str]) -> Vec<Node> {
+    names
+        .iter()
+        .map(|name| Node {
+            name: name.to_string(),
+            ..Default::default()
+        })
+        .collect()
+}
+
+#[test]
+fn weighted_initiator_favours_heavy_node() {
+    let mut algorithm: Algorithm<PingNode, Ping> = Algorithm::new(&nodes(&["a", "b", "c"]));
+    let weight = |name: &str| if name == "b" { 20.0 } else { 1.0 };
+    let chosen_b = (0..200)
+        .filter(|&seed| {
+            algorithm.rng = StdRng::seed_from_u64(seed);
+            algorithm.choose_initiator_weighted(weight, &mut vec![]) == "b"
+        })
+        .count();
+    assert!(chosen_b > 160, "b was chosen {chosen_b} of 200 times");
+
+    let mut logger = vec![];
+    let mut frozen: Algorithm<PingNode, Ping> = Algorithm::new(&nodes(&["a", "b", "c"]));
+    frozen.frozen = true;
+    frozen.choose_initiator_weighted(weight, &mut logger);
+    assert_eq!(
+        logger,
+        ["Initiator weights a=1, b=20, c=1.", "Choose b as initator."]
+    );
+}
+
+#[test]
+fn clock_badge_follows_the_clock() {
+    let mut grid = NodeGrid {
+        nodes: nodes(&["a"]),
+        ..Default::default()
+    };
+    let mut algorithm: Algorithm<PingNode, Ping> = Algorithm::new(&grid.nodes);
+    let badge = |grid: &NodeGrid| {
+        let display = NodeGridDisplay::new(grid.clone()).show_clocks(true);
+        let area = Rect::new(0, 0, 12, 9);
+        let mut buf = Buffer::empty(area);
+        display.render_scrolled(area, &mut buf);
+        let top = (NODE_H_SPACING + 1..NODE_H_SPACING + NODE_WIDTH - 1)
+            .map(|x| buf[(x, NODE_V_SPACING)].symbol())
+            .collect::<String>();
+        top.trim().to_string()
+    };
+    for expected in ["1", "2"] {
+        algorithm.node_by_name("a".to_string()).clock.tick();
+        grid.store_run(&algorithm);
+        assert_eq!(badge(&grid), expected);
+    }
+}
+
+#[test]
+fn vector_clocks_order_only_causally_related_events() {
+    let mut a = VectorClock::default();
+    let sent = a.tick("a");
+    let mut b = VectorClock::default();
+    b.tick("b");
+    assert!(!sent.happens_before(&b) && !b.happens_before(&sent));
+
+    b.merge(&sent);
+    let received = b.tick("b");
+    assert_eq!(received.to_string(), "VC(a=1, b=2)");
+    assert!(sent.happens_before(&received));
+    assert!(!received.happens_before(&sent));
+    assert!(!sent.happens_before(&sent));
+}
+
+/// Every ping is answered with a pong, which ends the exchange.
+fn ping_pong(algorithm: &mut Algorithm<PingNode, Ping>) {
+    while let Some(mesg) = algorithm.pop_mesg() {
+        if mesg.kind == "ping" {
+            algorithm.add_mesg(Ping::new(&mesg.destination, &mesg.sender, "pong"));
+        }
+    }
+}
+
+fn record(
+    event: TraceEvent,
+    sender: &str,
+    destination: &str,
+    kind: &str,
+    step: usize,
+) -> TraceRecord {
+    TraceRecord {
+        event,
+        sender: sender.to_string(),
+        destination: destination.to_string(),
+        kind: kind.to_string(),
+        step,
+    }
+}
+
+#[test]
+fn trace_records_message_events() {
+    let mut algorithm: Algorithm<PingNode, Ping> = Algorithm::new(&nodes(&["a", "b"]));
+    algorithm.add_mesg_iter(&mut VecDeque::from([
+        Ping::new("a", "b", "ping"),
+        Ping::new("b", "a", "ping"),
+    ]));
+    ping_pong(&mut algorithm);
+
+    assert_eq!(
+        algorithm.trace,
+        vec![
+            record(TraceEvent::Sent, "a", "b", "ping", 0),
+            record(TraceEvent::Sent, "b", "a", "ping", 0),
+            record(TraceEvent::Delivered, "a", "b", "ping", 0),
+            record(TraceEvent::Sent, "b", "a", "pong", 1),
+            record(TraceEvent::Delivered, "b", "a", "ping", 1),
+            record(TraceEvent::Sent, "a", "b", "pong", 2),
+            record(TraceEvent::Delivered, "b", "a", "pong", 2),
+            record(TraceEvent::Delivered, "a", "b", "pong", 3),
+        ]
+    );
+}
+
+#[test]
+fn inbox_lists_channels_in_delivery_order() {
+    let mut algorithm: Algorithm<PingNode, Ping> = Algorithm::new(&nodes(&["a", "b", "c"]));
+    algorithm.add_mesg_iter(&mut VecDeque::from([
+        Ping::new("c", "b", "first"),
+        Ping::new("a", "b", "second"),
+        Ping::new("c", "b", "third"),
+        Ping::new("b", "a", "fourth"),
+    ]));
+    let inboxes = algorithm.inboxes();
+    let channels: Vec<(&str, Vec<&str>)> = inboxes["b"]
+        .iter()
+        .map(|(sender, messages)| {
+            (
+                sender.as_str(),
+                messages.iter().map(String::as_str).collect(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        channels,
+        [
+            ("a", vec!["<second> a->b"]),
+            ("c", vec!["<first> c->b", "<third> c->b"]),
+        ]
+    );
+    assert_eq!(inboxes["a"]["b"], ["<fourth> b->a"]);
+    assert!(!inboxes.contains_key("c"));
+}
+
+/// Kinds of the pings in the order they are delivered, after sending three
+/// with timestamps 2, 1 and 2.
+fn delivered(delivery: Delivery) -> Vec<String> {
+    let mut algorithm: Algorithm<PingNode, Ping> = Algorithm::new(&nodes(&["a", "b"]));
+    algorithm.delivery = Some(delivery);
+    for (kind, time) in [("first", 2), ("second", 1), ("third", 2)] {
+        algorithm.add_mesg(Ping {
+            time: Some(LamportsClock(time)),
+            ..Ping::new("a", "b", kind)
+        });
+    }
+    std::iter::from_fn(|| algorithm.pop_mesg())
+        .map(|mesg| mesg.kind)
+        .collect()
+}
+
+#[test]
+fn delivery_orders_messages() {
+    assert_eq!(delivered(Delivery::FrontFifo), ["first", "second", "third"]);
+    assert_eq!(delivered(Delivery::LifoStack), ["third", "second", "first"]);
+    assert_eq!(
+        delivered(Delivery::PriorityByTimestamp),
+        ["second", "first", "third"]
+    );
+    let mut random = delivered(Delivery::RandomInsert);
+    random.sort();
+    assert_eq!(random, ["first", "second", "third"]);
+}
+
+#[test]
+fn every_algorithm_has_pseudocode() {
+    for algorithm in SelectedAlgorithm::iter() {
+        assert!(!algorithm.pseudocode().trim().is_empty(), "{algorithm}");
+    }
+}
+
+#[test]
+fn metrics_count_sent_messages_and_the_fullest_queue() {
+    let mut algorithm: Algorithm<PingNode, Ping> = Algorithm::new(&nodes(&["a", "b"]));
+    for kind in ["first", "second", "third"] {
+        algorithm.add_mesg(Ping::new("a", "b", kind));
+    }
+    algorithm.pop_mesg();
+    algorithm.pop_mesg();
+    algorithm.add_mesg(Ping::new("b", "a", "fourth"));
+    let mut logger = vec![];
+    algorithm.log_metrics(&mut logger);
+    assert_eq!(
+        logger,
+        ["Run summary: 4 messages sent, at most 3 in transit, 2 steps taken."]
+    );
+}
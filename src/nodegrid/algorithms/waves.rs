@@ -0,0 +1,534 @@
+use crate::nodegrid::algorithms::*;
+
+pub(super) use echo::PSEUDOCODE as ECHO_PSEUDOCODE;
+pub(super) use tarry::PSEUDOCODE as TARRY_PSEUDOCODE;
+
+mod echo {
+    use anyhow::{Result, anyhow};
+    use displaydoc::Display;
+    use std::collections::{BTreeSet, VecDeque};
+    use strum::EnumIs;
+
+    use crate::{
+        node::Node,
+        nodegrid::{NodeGrid, algorithms::*},
+    };
+
+    pub(in crate::nodegrid::algorithms) const PSEUDOCODE: &str = "\
+the initiator, with connections read as undirected edges:
+    send <wave> to every neighbour
+
+on a non-initiator first receiving <wave> from q:
+    take q as parent
+    send <wave> to every neighbour but the parent
+
+once a node received from every neighbour:
+    if it is the initiator:
+        decide
+    else:
+        send <echo> to the parent";
+
+    #[derive(Debug, Default, Clone)]
+    struct AlgNode {
+        node: Node,
+        state: NodeState,
+        /// Neighbours when connections are read as undirected edges.
+        neighbours: BTreeSet<String>,
+        parent: Option<String>,
+        initiator: bool,
+        /// Number of messages received, one from every neighbour in the end.
+        received: usize,
+    }
+
+    #[derive(Debug, Display, Default, Clone, Copy, PartialEq, Eq, EnumIs)]
+    enum NodeState {
+        #[default]
+        /// not reached
+        Unreached,
+        /// waiting
+        Waiting,
+        /// echoed
+        Echoed,
+        /// decided
+        Decided,
+    }
+
+    #[derive(Debug, Display, Default, Clone)]
+    #[displaydoc("<{kind}> {sender}->{destination}")]
+    struct Message {
+        sender: String,
+        destination: String,
+        kind: MesgKind,
+    }
+    impl Mesg for Message {
+        fn sender(&self) -> &str {
+            &self.sender
+        }
+        fn destination(&self) -> &str {
+            &self.destination
+        }
+        fn kind(&self) -> String {
+            self.kind.to_string()
+        }
+    }
+    impl NonFifo for Message {}
+
+    #[derive(Debug, Display, Default, Clone, Copy, PartialEq, Eq)]
+    enum MesgKind {
+        #[default]
+        /// wave
+        Wave,
+        /// echo
+        Echo,
+    }
+
+    impl From<&Node> for AlgNode {
+        fn from(node: &Node) -> Self {
+            AlgNode {
+                node: node.clone(),
+                ..Default::default()
+            }
+        }
+    }
+    impl NodeLike for AlgNode {
+        fn name(&self) -> &str {
+            &self.node.name
+        }
+        fn result(&self) -> String {
+            self.state.to_string()
+        }
+    }
+
+    impl StepNode<Message> for AlgNode {
+        const CHANNELS: Delivery = Delivery::RandomInsert;
+
+        fn initiate(&mut self, logger: &mut Vec<String>) -> VecDeque<Message> {
+            self.initiator = true;
+            self.state = NodeState::Waiting;
+            let output = self.send_wave();
+            self.check_done(output, logger)
+        }
+
+        fn receive(&mut self, mesg: Message, logger: &mut Vec<String>) -> VecDeque<Message> {
+            log_routine(logger, format!("{} received {mesg}", self.name()));
+            self.received += 1;
+            let mut output = VecDeque::new();
+            if self.state.is_unreached() {
+                logger.push(format!("{} takes {} as parent.", self.name(), mesg.sender));
+                self.parent = Some(mesg.sender);
+                self.state = NodeState::Waiting;
+                output = self.send_wave();
+            }
+            self.check_done(output, logger)
+        }
+
+        /// Checks that the initiator decided after the wave reached every node.
+        fn conclude(run: &Algorithm<Self, Message>, logger: &mut Vec<String>) -> Result<()> {
+            let unreached: Vec<&str> = run
+                .nodes
+                .iter()
+                .filter(|n| n.state.is_unreached())
+                .map(NodeLike::name)
+                .collect();
+            let decided = run.nodes.iter().any(|n| n.state.is_decided());
+            match (decided, unreached.is_empty()) {
+                (true, true) => {
+                    logger.push(format!("Wave reached all {} nodes.", run.nodes.len()));
+                    Ok(())
+                }
+                (_, false) => {
+                    logger.push(format!(
+                        "Wave did not complete, {} were not reached.",
+                        unreached.join(", ")
+                    ));
+                    Err(anyhow!("Wave did not complete."))
+                }
+                (false, true) => {
+                    logger.push("Wave did not complete, the initiator did not decide.".to_string());
+                    Err(anyhow!("Wave did not complete."))
+                }
+            }
+        }
+    }
+
+    impl AlgNode {
+        /// Sends the wave on to every neighbour but the parent.
+        fn send_wave(&self) -> VecDeque<Message> {
+            self.neighbours
+                .iter()
+                .filter(|n| Some(*n) != self.parent.as_ref())
+                .map(|n| self.message(n.clone(), MesgKind::Wave))
+                .collect()
+        }
+
+        fn message(&self, destination: String, kind: MesgKind) -> Message {
+            Message {
+                sender: self.name_clone(),
+                destination,
+                kind,
+            }
+        }
+
+        /// Echoes to the parent, or decides at the initiator, once every
+        /// neighbour has been heard from.
+        fn check_done(
+            &mut self,
+            mut output: VecDeque<Message>,
+            logger: &mut Vec<String>,
+        ) -> VecDeque<Message> {
+            if !self.state.is_waiting() || self.received < self.neighbours.len() {
+                return output;
+            }
+            match self.parent.clone() {
+                Some(parent) => {
+                    self.state = NodeState::Echoed;
+                    output.push_back(self.message(parent, MesgKind::Echo));
+                }
+                None => {
+                    self.state = NodeState::Decided;
+                    logger.push(format!("{} decides, every echo is back.", self.name()));
+                }
+            }
+            output
+        }
+    }
+
+    impl NodeGrid {
+        pub fn echo(&mut self, logger: &mut Vec<String>) -> Result<()> {
+            let mut algorithm = self.echo_algorithm(logger)?;
+            let result = algorithm.run(logger);
+            self.store_run(&algorithm);
+            result
+        }
+
+        /// Sets up Echo to be run one step at a time.
+        pub(in crate::nodegrid::algorithms) fn echo_stepped(
+            &self,
+            logger: &mut Vec<String>,
+        ) -> Result<Box<dyn SteppedRun>> {
+            Ok(Box::new(self.echo_algorithm(logger)?))
+        }
+
+        /// Sets up Echo on the grid, with the initiator yet to start the wave.
+        fn echo_algorithm(&self, logger: &mut Vec<String>) -> Result<Algorithm<AlgNode, Message>> {
+            self.check_not_empty(logger)?;
+            let mut algorithm: Algorithm<AlgNode, Message> = Algorithm::new(&self.nodes);
+            self.configure(&mut algorithm);
+            let neighbours = self.undirected_neighbours();
+            for node in algorithm.nodes.iter_mut() {
+                node.neighbours = neighbours[node.name()]
+                    .iter()
+                    .map(|n| n.to_string())
+                    .collect();
+            }
+            logger.push(format!(
+                "Started Echo wave with {} nodes.",
+                algorithm.nodes.len()
+            ));
+            let initiator = algorithm.choose_initiator(logger);
+            algorithm.initiators.push_back(initiator);
+            Ok(algorithm)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn wave_builds_spanning_tree() -> Result<()> {
+            let mut grid = NodeGrid::generate_connected(9, &mut StdRng::seed_from_u64(9));
+            let mut logger = vec![];
+            grid.echo(&mut logger)?;
+            assert_eq!(logger.last().unwrap(), "Wave reached all 9 nodes.");
+            let parents = logger.iter().filter(|l| l.contains(" as parent.")).count();
+            assert_eq!(parents, 8);
+            let edges: usize = grid.undirected_neighbours().values().map(|n| n.len()).sum();
+            assert_eq!(grid.messages_sent(), edges);
+            Ok(())
+        }
+
+        #[test]
+        fn split_grid_does_not_complete() {
+            let nodes: Vec<Node> = ["a", "b", "c"]
+                .into_iter()
+                .map(|name| Node {
+                    name: name.to_string(),
+                    ..Default::default()
+                })
+                .collect();
+            // Frozen, so the wave starts at a rather than the lone c.
+            let mut grid = NodeGrid {
+                nodes,
+                frozen: true,
+                ..Default::default()
+            };
+            grid.nodes[0].add_connection(&connection::Connection::new(
+                "b".to_string(),
+                connection::DEFAULT_WEIGHT,
+            ));
+            let mut logger = vec![];
+            assert!(grid.echo(&mut logger).is_err());
+            assert!(logger.last().unwrap().starts_with("Wave did not complete"));
+            assert_eq!(grid.result("c").unwrap(), "not reached");
+        }
+    }
+}
+
+mod tarry {
+    use anyhow::{Result, anyhow};
+    use displaydoc::Display;
+    use std::collections::{BTreeSet, VecDeque};
+    use strum::EnumIs;
+
+    use crate::{
+        node::Node,
+        nodegrid::{NodeGrid, algorithms::*},
+    };
+
+    pub(in crate::nodegrid::algorithms) const PSEUDOCODE: &str = "\
+the initiator, with connections read as undirected edges:
+    send <token> to a neighbour
+
+on a non-initiator first receiving <token> from q:
+    take q as parent
+
+on receiving <token>:
+    send <token> over a channel it wasn't sent over yet,
+    back to the sender or the parent only if no other is left
+
+once the initiator has sent <token> over every channel:
+    decide";
+
+    #[derive(Debug, Default, Clone)]
+    struct AlgNode {
+        node: Node,
+        state: NodeState,
+        /// Neighbours when connections are read as undirected edges.
+        neighbours: BTreeSet<String>,
+        /// Neighbours the token was sent to already.
+        used: BTreeSet<String>,
+        parent: Option<String>,
+    }
+
+    #[derive(Debug, Display, Default, Clone, Copy, PartialEq, Eq, EnumIs)]
+    enum NodeState {
+        #[default]
+        /// not reached
+        Unreached,
+        /// visited
+        Visited,
+        /// decided
+        Decided,
+    }
+
+    #[derive(Debug, Display, Default, Clone)]
+    #[displaydoc("<token> {sender}->{destination}")]
+    struct Message {
+        sender: String,
+        destination: String,
+    }
+    impl Mesg for Message {
+        fn sender(&self) -> &str {
+            &self.sender
+        }
+        fn destination(&self) -> &str {
+            &self.destination
+        }
+        fn kind(&self) -> String {
+            "token".to_string()
+        }
+    }
+    impl NonFifo for Message {}
+
+    impl From<&Node> for AlgNode {
+        fn from(node: &Node) -> Self {
+            AlgNode {
+                node: node.clone(),
+                ..Default::default()
+            }
+        }
+    }
+    impl NodeLike for AlgNode {
+        fn name(&self) -> &str {
+            &self.node.name
+        }
+        fn result(&self) -> String {
+            self.state.to_string()
+        }
+    }
+
+    impl StepNode<Message> for AlgNode {
+        const CHANNELS: Delivery = Delivery::RandomInsert;
+
+        fn initiate(&mut self, logger: &mut Vec<String>) -> VecDeque<Message> {
+            self.state = NodeState::Visited;
+            self.forward(None, logger)
+        }
+
+        fn receive(&mut self, mesg: Message, logger: &mut Vec<String>) -> VecDeque<Message> {
+            log_routine(logger, format!("{} received {mesg}", self.name()));
+            if self.state.is_unreached() {
+                logger.push(format!("{} takes {} as parent.", self.name(), mesg.sender));
+                self.parent = Some(mesg.sender.clone());
+                self.state = NodeState::Visited;
+            }
+            self.forward(Some(&mesg.sender), logger)
+        }
+
+        /// Logs the path the token took and the spanning tree it built, after
+        /// checking it came back to the initiator from every node.
+        fn conclude(run: &Algorithm<Self, Message>, logger: &mut Vec<String>) -> Result<()> {
+            let mut path: Vec<&str> = run
+                .nodes
+                .iter()
+                .filter(|n| n.state.is_visited() || n.state.is_decided())
+                .filter(|n| n.parent.is_none())
+                .map(NodeLike::name)
+                .collect();
+            path.extend(
+                run.trace
+                    .iter()
+                    .filter(|r| r.event == TraceEvent::Delivered)
+                    .map(|r| r.destination.as_str()),
+            );
+            logger.push(format!("Token traversed {}.", path.join(" -> ")));
+
+            let unreached: Vec<&str> = run
+                .nodes
+                .iter()
+                .filter(|n| n.state.is_unreached())
+                .map(NodeLike::name)
+                .collect();
+            if !unreached.is_empty() {
+                logger.push(format!(
+                    "Traversal did not complete, {} were not reached.",
+                    unreached.join(", ")
+                ));
+                return Err(anyhow!("Traversal did not complete."));
+            }
+            if !run.nodes.iter().any(|n| n.state.is_decided()) {
+                logger
+                    .push("Traversal did not complete, the initiator did not decide.".to_string());
+                return Err(anyhow!("Traversal did not complete."));
+            }
+            for node in run.nodes.iter() {
+                logger.push(match &node.parent {
+                    Some(parent) => format!("{} has parent {parent}.", node.name()),
+                    None => format!("{} is the root.", node.name()),
+                });
+            }
+            Ok(())
+        }
+    }
+
+    impl AlgNode {
+        /// Sends the token over an unused channel, preferring any other than
+        /// the one back to `sender`, and the one to the parent last. The
+        /// initiator decides once every channel is used.
+        fn forward(
+            &mut self,
+            sender: Option<&String>,
+            logger: &mut Vec<String>,
+        ) -> VecDeque<Message> {
+            let unused: Vec<&String> = self
+                .neighbours
+                .iter()
+                .filter(|n| !self.used.contains(*n) && Some(*n) != self.parent.as_ref())
+                .collect();
+            let next = unused
+                .iter()
+                .find(|n| Some(**n) != sender)
+                .or(unused.first())
+                .map(|n| n.to_string())
+                .or_else(|| {
+                    self.parent
+                        .clone()
+                        .filter(|parent| !self.used.contains(parent))
+                });
+            let Some(next) = next else {
+                if self.parent.is_none() {
+                    self.state = NodeState::Decided;
+                    logger.push(format!(
+                        "{} decides, the token has been over every channel.",
+                        self.name()
+                    ));
+                }
+                return VecDeque::new();
+            };
+            logger.push(format!("{} forwards the token to {next}.", self.name()));
+            self.used.insert(next.clone());
+            VecDeque::from([Message {
+                sender: self.name_clone(),
+                destination: next,
+            }])
+        }
+    }
+
+    impl NodeGrid {
+        pub fn tarry(&mut self, logger: &mut Vec<String>) -> Result<()> {
+            let mut algorithm = self.tarry_algorithm(logger)?;
+            let result = algorithm.run(logger);
+            self.store_run(&algorithm);
+            result
+        }
+
+        /// Sets up Tarry to be run one step at a time.
+        pub(in crate::nodegrid::algorithms) fn tarry_stepped(
+            &self,
+            logger: &mut Vec<String>,
+        ) -> Result<Box<dyn SteppedRun>> {
+            Ok(Box::new(self.tarry_algorithm(logger)?))
+        }
+
+        /// Sets up Tarry on the grid, with the initiator yet to send the token.
+        fn tarry_algorithm(&self, logger: &mut Vec<String>) -> Result<Algorithm<AlgNode, Message>> {
+            self.check_not_empty(logger)?;
+            let mut algorithm: Algorithm<AlgNode, Message> = Algorithm::new(&self.nodes);
+            self.configure(&mut algorithm);
+            let neighbours = self.undirected_neighbours();
+            for node in algorithm.nodes.iter_mut() {
+                node.neighbours = neighbours[node.name()]
+                    .iter()
+                    .map(|n| n.to_string())
+                    .collect();
+            }
+            logger.push(format!(
+                "Started Tarry traversal with {} nodes.",
+                algorithm.nodes.len()
+            ));
+            let initiator = algorithm.choose_initiator(logger);
+            algorithm.initiators.push_back(initiator);
+            Ok(algorithm)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn token_crosses_every_channel_once_each_way() -> Result<()> {
+            let mut grid = NodeGrid::generate_connected(9, &mut StdRng::seed_from_u64(9));
+            let mut logger = vec![];
+            grid.tarry(&mut logger)?;
+            let edges: usize = grid.undirected_neighbours().values().map(|n| n.len()).sum();
+            assert_eq!(grid.messages_sent(), edges);
+
+            let parents = logger.iter().filter(|l| l.contains(" has parent ")).count();
+            assert_eq!(parents, 8);
+            let path = logger
+                .iter()
+                .find(|l| l.starts_with("Token traversed "))
+                .unwrap();
+            let root = logger
+                .iter()
+                .find(|l| l.ends_with(" is the root."))
+                .unwrap();
+            let root = root.trim_end_matches(" is the root.");
+            assert!(path.starts_with(&format!("Token traversed {root} -> ")));
+            assert!(path.ends_with(&format!(" -> {root}.")));
+            Ok(())
+        }
+    }
+}
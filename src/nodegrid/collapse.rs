@@ -0,0 +1,112 @@
+use anyhow::{Result, anyhow};
+
+use crate::{
+    node::{Collapsed, Node, connection::Connection},
+    nodegrid::NodeGrid,
+};
+
+impl NodeGrid {
+    /// Merges the placed nodes named in `members` into a super-node called
+    /// `name`, at the place of the first member. Connections leaving or
+    /// entering the set are moved to the super-node, where the last of several
+    /// to the same node wins, and the ones within the set are dropped. The
+    /// members are kept so the super-node can be expanded.
+    pub(crate) fn collapse(&mut self, members: &[String], name: String) -> Result<()> {
+        let mut unique: Vec<String> = vec![];
+        for member in members {
+            if !unique.contains(member) {
+                unique.push(member.clone());
+            }
+        }
+        let members = &unique[..];
+        if members.len() < 2 {
+            Err(anyhow!("Collapse at least two nodes."))?;
+        }
+        if let Some(missing) = members.iter().find(|m| !self.node_exists(m)) {
+            Err(anyhow!("No node named {missing:?}."))?;
+        }
+        let taken = self.floating_nodes.iter().any(|n| n.name == name)
+            || self.node_exists(&name) && !members.contains(&name);
+        if taken {
+            Err(anyhow!("Name not unique."))?;
+        }
+        self.clear_results();
+        let id = self.next_id();
+        let (nodes, others): (Vec<Node>, Vec<Node>) = std::mem::take(&mut self.nodes)
+            .into_iter()
+            .partition(|n| members.contains(&n.name));
+        self.nodes = others;
+        let location = members
+            .iter()
+            .find_map(|m| nodes.iter().find(|n| &n.name == m))
+            .unwrap()
+            .location;
+        let mut super_node = Node {
+            name: name.clone(),
+            id,
+            location,
+            ..Default::default()
+        };
+        for connection in nodes.iter().flat_map(|n| n.connections.iter()) {
+            if !members.contains(&connection.other) {
+                super_node.add_connection(connection);
+            }
+        }
+        let mut incoming = vec![];
+        for node in self.nodes.iter_mut() {
+            let (removed, kept) = std::mem::take(&mut node.connections)
+                .into_iter()
+                .partition(|c| members.contains(&c.other));
+            node.connections = kept;
+            for connection in removed {
+                node.add_connection(&Connection::new(name.clone(), *connection.weight));
+                incoming.push((node.name.clone(), connection));
+            }
+        }
+        for member in members {
+            self.included.remove(member);
+            self.marked.remove(member);
+        }
+        super_node.collapsed = Some(Box::new(Collapsed {
+            members: nodes,
+            incoming,
+        }));
+        self.nodes.push(super_node);
+        Ok(())
+    }
+
+    /// Puts the members of the super-node `name` back in its place, with their
+    /// own connections. Connections made to the super-node since are lost.
+    /// Returns the names of the members.
+    pub(crate) fn expand(&mut self, name: &str) -> Result<Vec<String>> {
+        let index = self
+            .nodes
+            .iter()
+            .position(|n| n.name == name)
+            .ok_or_else(|| anyhow!("No node named {name:?}."))?;
+        let Some(collapsed) = self.nodes[index].collapsed.as_deref() else {
+            Err(anyhow!("{name} is not a super-node."))?
+        };
+        let taken = collapsed.members.iter().any(|m| {
+            self.nodes
+                .iter()
+                .any(|n| n.name != name && (n.name == m.name || n.location == m.location))
+        });
+        if taken {
+            Err(anyhow!("The place or name of a member of {name} is taken."))?;
+        }
+        self.clear_results();
+        let collapsed = self.nodes.remove(index).collapsed.unwrap();
+        for node in self.nodes.iter_mut() {
+            node.connections.retain(|c| c.other != name);
+        }
+        for (from, connection) in collapsed.incoming.iter() {
+            if let Some(node) = self.nodes.iter_mut().find(|n| &n.name == from) {
+                node.add_connection(connection);
+            }
+        }
+        let names = collapsed.members.iter().map(|n| n.name.clone()).collect();
+        self.nodes.extend(collapsed.members);
+        Ok(names)
+    }
+}
@@ -0,0 +1,79 @@
+use ratatui::{buffer::Buffer, layout::Rect, style::Style, widgets::Widget};
+
+use crate::{
+    COMPACT_NODE_WIDTH, COMPACT_SPACING,
+    location::Location,
+    node::{Node, NodeWidget},
+    nodegrid::NodeGrid,
+    theme::Theme,
+};
+
+impl NodeGrid {
+    /// Top left corner of the cell at `location` in the compact layout, where a
+    /// node takes a single row and neighbours are one cell apart.
+    pub(crate) fn place_compact(&self, location: &Location) -> (u16, u16) {
+        (
+            COMPACT_SPACING + location.x * (COMPACT_SPACING + COMPACT_NODE_WIDTH),
+            COMPACT_SPACING + location.y * (COMPACT_SPACING + 1),
+        )
+    }
+
+    pub(crate) fn render_compact_nodes(&self, buf: &mut Buffer, theme: &Theme) {
+        for (node, color) in self
+            .nodes
+            .iter()
+            .map(|n| (n, self.node_color(n, theme)))
+            .chain(self.floating_nodes.iter().map(|n| (n, theme.floating)))
+        {
+            let (x, y) = self.place_compact(&node.location);
+            NodeWidget::from(node, Style::default().fg(color))
+                .compact(true)
+                .render(Rect::new(x, y, COMPACT_NODE_WIDTH, 1), buf);
+        }
+    }
+
+    /// Draws connections between neighbouring cells as a single character in the
+    /// gap between them. Longer connections are left out.
+    pub(crate) fn render_compact_connections(&self, buf: &mut Buffer, theme: &Theme) {
+        for origin in self.nodes.iter() {
+            for connection in origin.connections.iter() {
+                let Some(other) = self.nodes.iter().find(|n| n.name == connection.other) else {
+                    continue;
+                };
+                let undirected = other.connections.iter().any(|c| c.other == origin.name);
+                if let Some((x, y, symbol)) = self.compact_edge(origin, other, undirected) {
+                    buf.set_string(
+                        x,
+                        y,
+                        symbol,
+                        self.connection_style(&origin.name, &other.name, theme),
+                    );
+                }
+            }
+        }
+    }
+
+    fn compact_edge(
+        &self,
+        from: &Node,
+        to: &Node,
+        undirected: bool,
+    ) -> Option<(u16, u16, &'static str)> {
+        let dx = to.location.x as i32 - from.location.x as i32;
+        let dy = to.location.y as i32 - from.location.y as i32;
+        let top_left = from.location.lowest(&to.location);
+        let (x, y) = self.place_compact(&top_left);
+        let (x, y, directed, plain) = match (dx, dy) {
+            (1, 0) => (x + COMPACT_NODE_WIDTH, y, "→", "─"),
+            (-1, 0) => (x + COMPACT_NODE_WIDTH, y, "←", "─"),
+            (0, 1) => (x + COMPACT_NODE_WIDTH / 2, y + 1, "↓", "│"),
+            (0, -1) => (x + COMPACT_NODE_WIDTH / 2, y + 1, "↑", "│"),
+            (1, 1) => (x + COMPACT_NODE_WIDTH, y + 1, "↘", "╲"),
+            (-1, -1) => (x + COMPACT_NODE_WIDTH, y + 1, "↖", "╲"),
+            (-1, 1) => (x + COMPACT_NODE_WIDTH, y + 1, "↙", "╱"),
+            (1, -1) => (x + COMPACT_NODE_WIDTH, y + 1, "↗", "╱"),
+            _ => return None,
+        };
+        Some((x, y, if undirected { plain } else { directed }))
+    }
+}
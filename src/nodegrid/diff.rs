@@ -0,0 +1,92 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::nodegrid::{EdgeChange, NodeGrid};
+
+/// Differences between two grids, with nodes and connections matched by name.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct GridDiff {
+    pub(crate) added_nodes: Vec<String>,
+    pub(crate) removed_nodes: Vec<String>,
+    pub(crate) connections: Vec<EdgeChange>,
+}
+
+impl GridDiff {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty() && self.removed_nodes.is_empty() && self.connections.is_empty()
+    }
+
+    /// One log line per difference.
+    pub(crate) fn lines(&self) -> Vec<String> {
+        if self.is_empty() {
+            return vec!["Grids are identical.".to_string()];
+        }
+        let mut lines: Vec<String> = self
+            .added_nodes
+            .iter()
+            .map(|n| format!("Added node {n}."))
+            .collect();
+        lines.extend(
+            self.removed_nodes
+                .iter()
+                .map(|n| format!("Removed node {n}.")),
+        );
+        lines.extend(self.connections.iter().map(|c| c.to_string()));
+        lines
+    }
+}
+
+impl NodeGrid {
+    /// What changed going from this grid to `other`.
+    pub(crate) fn diff(&self, other: &NodeGrid) -> GridDiff {
+        let names = |grid: &NodeGrid| -> BTreeSet<String> {
+            grid.nodes.iter().map(|n| n.name.clone()).collect()
+        };
+        let (old_names, new_names) = (names(self), names(other));
+        let (old_edges, new_edges) = (self.weighted_edges(), other.weighted_edges());
+
+        let mut connections = vec![];
+        for ((from, to), &new) in new_edges.iter() {
+            match old_edges.get(&(from.clone(), to.clone())) {
+                None => connections.push(EdgeChange::Created {
+                    from: from.clone(),
+                    to: to.clone(),
+                    weight: new,
+                }),
+                Some(&old) if old != new => connections.push(EdgeChange::Updated {
+                    from: from.clone(),
+                    to: to.clone(),
+                    old,
+                    new,
+                }),
+                Some(_) => {}
+            }
+        }
+        for ((from, to), &weight) in old_edges.iter() {
+            if !new_edges.contains_key(&(from.clone(), to.clone())) {
+                connections.push(EdgeChange::Removed {
+                    from: from.clone(),
+                    to: to.clone(),
+                    weight,
+                });
+            }
+        }
+
+        GridDiff {
+            added_nodes: new_names.difference(&old_names).cloned().collect(),
+            removed_nodes: old_names.difference(&new_names).cloned().collect(),
+            connections,
+        }
+    }
+
+    /// The weight of every connection, keyed by its endpoints.
+    fn weighted_edges(&self) -> BTreeMap<(String, String), f64> {
+        self.nodes
+            .iter()
+            .flat_map(|n| {
+                n.connections
+                    .iter()
+                    .map(|c| ((n.name.clone(), c.other.clone()), *c.weight))
+            })
+            .collect()
+    }
+}
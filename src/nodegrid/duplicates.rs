@@ -0,0 +1,92 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use crate::nodegrid::NodeGrid;
+
+/// What to do with nodes sharing a name, when a grid is loaded.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum Duplicates {
+    /// Refuse the grid.
+    #[default]
+    Error,
+    /// Give every later node with the name a numbered suffix, `a-2`, `a-3`...
+    Rename,
+    /// Fold every later node with the name into the first one, joining their
+    /// connections.
+    Merge,
+}
+
+impl NodeGrid {
+    /// Names used by more than one node, in the order they first repeat.
+    fn duplicate_names(&self) -> Vec<String> {
+        let mut seen = BTreeSet::new();
+        let mut duplicates = vec![];
+        for node in self.nodes.iter() {
+            if !seen.insert(node.name.as_str()) && !duplicates.contains(&node.name) {
+                duplicates.push(node.name.clone());
+            }
+        }
+        duplicates
+    }
+
+    /// Handles nodes sharing a name as `policy` says, logging each one.
+    /// Connections to a duplicated name keep pointing at the first node with
+    /// that name, as there's no telling which one was meant.
+    pub(crate) fn resolve_duplicates(
+        &mut self,
+        policy: Duplicates,
+        logger: &mut Vec<String>,
+    ) -> Result<()> {
+        let duplicates = self.duplicate_names();
+        if duplicates.is_empty() {
+            return Ok(());
+        }
+        match policy {
+            Duplicates::Error => {
+                return Err(anyhow!("Duplicate node names {}.", duplicates.join(", ")));
+            }
+            Duplicates::Rename => {
+                let mut taken: BTreeSet<String> =
+                    self.nodes.iter().map(|n| n.name.clone()).collect();
+                let mut seen = BTreeSet::new();
+                for node in self.nodes.iter_mut() {
+                    if seen.insert(node.name.clone()) {
+                        continue;
+                    }
+                    let name = (2..)
+                        .map(|i| format!("{}-{i}", node.name))
+                        .find(|name| !taken.contains(name))
+                        .unwrap();
+                    logger.push(format!("Renamed duplicate {} to {name}.", node.name));
+                    taken.insert(name.clone());
+                    node.name = name;
+                }
+            }
+            Duplicates::Merge => {
+                for name in duplicates.iter() {
+                    let mut indices = self
+                        .nodes
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, n)| &n.name == name)
+                        .map(|(i, _)| i);
+                    let first = indices.next().unwrap();
+                    let later: Vec<usize> = indices.collect();
+                    for &index in later.iter().rev() {
+                        let node = self.nodes.remove(index);
+                        for connection in node.connections.iter() {
+                            self.nodes[first].add_connection(connection);
+                        }
+                    }
+                    self.nodes[first].connections.retain(|c| &c.other != name);
+                    logger.push(format!(
+                        "Merged {} duplicates of {name} into one node.",
+                        later.len() + 1
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
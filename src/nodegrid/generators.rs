@@ -0,0 +1,177 @@
+use anyhow::{Result, anyhow};
+use rand::{Rng, seq::SliceRandom};
+use strum::Display;
+
+use crate::{
+    location::Location,
+    node::{
+        Node,
+        connection::{Connection, DEFAULT_WEIGHT},
+    },
+    nodegrid::{NodeGrid, SelectedAlgorithm},
+};
+
+/// Most nodes a generated grid can have, so it is made and drawn quickly.
+pub(crate) const MAX_GENERATED_NODES: usize = 1024;
+
+/// Shape of the input graph an algorithm is meant to run on.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Topology {
+    #[strum(to_string = "ring")]
+    Ring,
+    #[strum(to_string = "two-way ring")]
+    BidirectionalRing,
+    #[strum(to_string = "connected graph")]
+    Connected,
+}
+
+impl SelectedAlgorithm {
+    pub(crate) fn example_topology(self) -> Topology {
+        match self {
+            SelectedAlgorithm::ChandyLamport => Topology::Connected,
+            SelectedAlgorithm::LaiYang => Topology::Connected,
+            SelectedAlgorithm::ChangRoberts => Topology::Ring,
+            SelectedAlgorithm::Franklin => Topology::BidirectionalRing,
+            SelectedAlgorithm::Peterson => Topology::Ring,
+            SelectedAlgorithm::Luby => Topology::Connected,
+            SelectedAlgorithm::Echo => Topology::Connected,
+            SelectedAlgorithm::Tarry => Topology::Connected,
+            SelectedAlgorithm::RicartAgrawala => Topology::Connected,
+            SelectedAlgorithm::DijkstraScholten => Topology::Connected,
+            SelectedAlgorithm::ChandyMisra => Topology::Connected,
+            SelectedAlgorithm::CausalOrder => Topology::Connected,
+        }
+    }
+}
+
+impl NodeGrid {
+    /// An example input of `size` nodes for `algorithm`.
+    pub(crate) fn generate_for(
+        algorithm: SelectedAlgorithm,
+        size: usize,
+        rng: &mut impl Rng,
+    ) -> Result<NodeGrid> {
+        if size < 2 {
+            return Err(anyhow!("An example graph needs at least 2 nodes."));
+        }
+        Ok(match algorithm.example_topology() {
+            Topology::Ring => Self::generate_ring(size, rng),
+            Topology::BidirectionalRing => Self::generate_bidirectional_ring(size, rng),
+            Topology::Connected => Self::generate_connected(size, rng),
+        })
+    }
+
+    /// A unidirectional ring with shuffled ids. The nodes are laid out over two
+    /// rows, so every connection is drawn between neighbouring cells.
+    pub(crate) fn generate_ring(size: usize, rng: &mut impl Rng) -> NodeGrid {
+        let mut ids: Vec<usize> = (1..=size).collect();
+        ids.shuffle(rng);
+        let top_row = size.div_ceil(2);
+
+        let mut grid = NodeGrid::default();
+        for (index, id) in ids.into_iter().enumerate() {
+            let location = if index < top_row {
+                Location::new(index as u16, 0)
+            } else {
+                Location::new((size - 1 - index) as u16, 1)
+            };
+            let mut node = generated_node(index, id, location);
+            // A single node has no successor but itself.
+            if size > 1 {
+                node.add_connection(&Connection::new(
+                    format!("n{}", (index + 1) % size),
+                    DEFAULT_WEIGHT,
+                ));
+            }
+            grid.nodes.push(node);
+        }
+        grid
+    }
+
+    /// A ring as made by `generate_ring`, with the connections going both ways.
+    pub(crate) fn generate_bidirectional_ring(size: usize, rng: &mut impl Rng) -> NodeGrid {
+        let mut grid = Self::generate_ring(size, rng);
+        grid.add_reverse_connections();
+        grid
+    }
+
+    /// A random connected graph with undirected connections, laid out as a square
+    /// lattice. Connections only run between neighbouring cells: a random spanning
+    /// tree, plus a few extra edges.
+    pub(crate) fn generate_connected(size: usize, rng: &mut impl Rng) -> NodeGrid {
+        let columns = (size as f64).sqrt().ceil() as usize;
+        let mut grid = NodeGrid::default();
+        for index in 0..size {
+            let location = Location::new((index % columns) as u16, (index / columns) as u16);
+            grid.nodes.push(generated_node(index, index + 1, location));
+        }
+
+        let mut candidates = vec![];
+        for index in 0..size {
+            let right = index % columns + 1 < columns;
+            if right && index + 1 < size {
+                candidates.push((index, index + 1));
+            }
+            if index + columns < size {
+                candidates.push((index, index + columns));
+            }
+            if right && index + columns + 1 < size {
+                candidates.push((index, index + columns + 1));
+            }
+        }
+        candidates.shuffle(rng);
+
+        let mut component: Vec<usize> = (0..size).collect();
+        for (a, b) in candidates {
+            let (root_a, root_b) = (component[a], component[b]);
+            if root_a != root_b || rng.random_bool(0.2) {
+                component.iter_mut().for_each(|c| {
+                    if *c == root_b {
+                        *c = root_a
+                    }
+                });
+                grid.link_indices(a, b);
+            }
+        }
+        grid
+    }
+
+    /// `rows` by `cols` nodes on a lattice, each connected in both directions to
+    /// its neighbours above, below, left and right.
+    pub(crate) fn make_lattice(rows: usize, cols: usize) -> Result<NodeGrid> {
+        let size = rows
+            .checked_mul(cols)
+            .filter(|size| (1..=MAX_GENERATED_NODES).contains(size))
+            .ok_or_else(|| anyhow!("A lattice needs between 1 and {MAX_GENERATED_NODES} nodes."))?;
+        let mut grid = NodeGrid::default();
+        for index in 0..size {
+            let location = Location::new((index % cols) as u16, (index / cols) as u16);
+            grid.nodes.push(generated_node(index, index + 1, location));
+        }
+        for index in 0..size {
+            if index % cols + 1 < cols {
+                grid.link_indices(index, index + 1);
+            }
+            if index + cols < size {
+                grid.link_indices(index, index + cols);
+            }
+        }
+        Ok(grid)
+    }
+
+    /// Connects the nodes at indices `a` and `b` in both directions.
+    fn link_indices(&mut self, a: usize, b: usize) {
+        let (name_a, name_b) = (self.nodes[a].name.clone(), self.nodes[b].name.clone());
+        self.nodes[a].add_connection(&Connection::new(name_b, DEFAULT_WEIGHT));
+        self.nodes[b].add_connection(&Connection::new(name_a, DEFAULT_WEIGHT));
+    }
+}
+
+fn generated_node(index: usize, id: usize, location: Location) -> Node {
+    Node {
+        name: format!("n{index}"),
+        id,
+        location,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,287 @@
+use anyhow::{Result, anyhow};
+
+use crate::{
+    SelectedAlgorithm,
+    node::connection::{Connection, DEFAULT_WEIGHT},
+    nodegrid::NodeGrid,
+};
+
+/// Changes to the grid which resolve a lint issue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, displaydoc::Display)]
+pub(crate) enum Fix {
+    /// connect the nodes into a ring, in their current order
+    MakeRing,
+    /// add the missing reverse of every connection
+    MakeUndirected,
+    /// remove connections to nodes which don't exist
+    PruneDangling,
+    /// connect every other component to the first one
+    JoinComponents,
+}
+
+impl Fix {
+    /// Key applying the fix in the lint popup.
+    pub(crate) fn key(self) -> char {
+        match self {
+            Fix::MakeRing => 'r',
+            Fix::MakeUndirected => 'u',
+            Fix::PruneDangling => 'p',
+            Fix::JoinComponents => 'j',
+        }
+    }
+
+    pub(crate) fn from_key(key: char) -> Option<Fix> {
+        [
+            Fix::MakeRing,
+            Fix::MakeUndirected,
+            Fix::PruneDangling,
+            Fix::JoinComponents,
+        ]
+        .into_iter()
+        .find(|f| f.key() == key)
+    }
+}
+
+/// Something keeping the grid from being a valid input for an algorithm.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Issue {
+    pub(crate) problem: String,
+    pub(crate) fix: Option<Fix>,
+}
+
+impl Issue {
+    fn new(problem: String, fix: Option<Fix>) -> Self {
+        Issue { problem, fix }
+    }
+}
+
+impl NodeGrid {
+    /// Every issue with the grid as input for `algorithm`, with the fix for
+    /// each, where there is one. Besides the preconditions this finds issues
+    /// which let the algorithm run, but not to a useful end.
+    pub(crate) fn lint(&self, algorithm: SelectedAlgorithm) -> Vec<Issue> {
+        let mut issues = self.unmet_preconditions(algorithm);
+        let dangling = self.dangling_connections();
+        if !dangling.is_empty() {
+            issues.push(Issue::new(
+                format!("Connections to missing nodes {}.", dangling.join(", ")),
+                Some(Fix::PruneDangling),
+            ));
+        }
+        let snapshot = matches!(
+            algorithm,
+            SelectedAlgorithm::ChandyLamport | SelectedAlgorithm::LaiYang
+        );
+        if snapshot && self.components().len() == 1 {
+            let stuck = self.nodes_not_reaching_all();
+            if !stuck.is_empty() {
+                issues.push(Issue::new(
+                    format!("Markers from {} can't reach every node.", stuck.join(", ")),
+                    Some(Fix::MakeUndirected),
+                ));
+            }
+        }
+        issues
+    }
+
+    /// The issues keeping `algorithm` from running on the grid at all.
+    pub(crate) fn unmet_preconditions(&self, algorithm: SelectedAlgorithm) -> Vec<Issue> {
+        let mut issues = vec![];
+        if self.nodes.is_empty() {
+            issues.push(Issue::new("No nodes in grid.".to_string(), None));
+        }
+        match algorithm {
+            SelectedAlgorithm::ChandyLamport | SelectedAlgorithm::LaiYang => {
+                // Every node sends basic messages, so it needs a channel to
+                // send them over.
+                let dead_ends: Vec<&str> = self
+                    .nodes
+                    .iter()
+                    .filter(|n| n.connections.is_empty())
+                    .map(|n| n.name.as_str())
+                    .collect();
+                if !dead_ends.is_empty() {
+                    issues.push(Issue::new(
+                        format!("No outgoing connection from {}.", dead_ends.join(", ")),
+                        (self.nodes.len() > 1).then_some(Fix::MakeUndirected),
+                    ));
+                }
+                let components = self.components().len();
+                if components > 1 {
+                    issues.push(Issue::new(
+                        format!("Grid is split into {components} parts."),
+                        Some(Fix::JoinComponents),
+                    ));
+                }
+            }
+            SelectedAlgorithm::ChangRoberts
+            | SelectedAlgorithm::Franklin
+            | SelectedAlgorithm::Peterson => {
+                let dead_ends: Vec<&str> = self
+                    .nodes
+                    .iter()
+                    .filter(|n| self.nodes.len() > 1 && n.connections.is_empty())
+                    .map(|n| n.name.as_str())
+                    .collect();
+                if !dead_ends.is_empty() {
+                    issues.push(Issue::new(
+                        format!("No outgoing connection from {}.", dead_ends.join(", ")),
+                        Some(Fix::MakeRing),
+                    ));
+                }
+                if !self.nodes.is_empty() && !self.is_ring() {
+                    issues.push(Issue::new(
+                        "Graph is not a ring.".to_string(),
+                        Some(Fix::MakeRing),
+                    ));
+                } else if algorithm == SelectedAlgorithm::Franklin
+                    && self.is_ring()
+                    && !self.is_bidirectional_ring()
+                {
+                    issues.push(Issue::new(
+                        "Ring is not connected in both directions.".to_string(),
+                        Some(Fix::MakeUndirected),
+                    ));
+                }
+            }
+            SelectedAlgorithm::Luby
+            | SelectedAlgorithm::Echo
+            | SelectedAlgorithm::Tarry
+            | SelectedAlgorithm::RicartAgrawala
+            | SelectedAlgorithm::DijkstraScholten
+            | SelectedAlgorithm::ChandyMisra
+            | SelectedAlgorithm::CausalOrder => (),
+        }
+        issues
+    }
+
+    /// Lines listing the issues found linting for `algorithm` with the keys
+    /// of their fixes, followed by whether the grid is a tree.
+    pub(crate) fn lint_report(&self, algorithm: SelectedAlgorithm) -> Vec<String> {
+        let issues = self.lint(algorithm);
+        let mut lines = match issues.is_empty() {
+            true => vec![format!("Grid is a valid input for {algorithm}.")],
+            false => vec![format!("Issues running {algorithm}:")],
+        };
+        for issue in issues.iter() {
+            lines.push(format!("- {}", issue.problem));
+            if let Some(fix) = issue.fix {
+                lines.push(format!("  <{}> {fix}", fix.key()));
+            }
+        }
+        lines.push(match self.tree_check() {
+            Ok(()) => "Grid is a tree.".to_string(),
+            Err(reason) => format!("Grid is not a tree: {reason}."),
+        });
+        lines
+    }
+
+    /// Applies `fix`, returning a line describing what changed.
+    pub(crate) fn apply_fix(&mut self, fix: Fix) -> Result<String> {
+        if self.nodes.is_empty() {
+            return Err(anyhow!("No nodes in grid."));
+        }
+        let changed = match fix {
+            Fix::MakeRing => {
+                if self.nodes.len() < 2 {
+                    return Err(anyhow!("A ring needs at least 2 nodes."));
+                }
+                let dropped = self.ring_fix_drops();
+                let names: Vec<String> = self.nodes.iter().map(|n| n.name.clone()).collect();
+                for (index, node) in self.nodes.iter_mut().enumerate() {
+                    let next = names[(index + 1) % names.len()].clone();
+                    let weight = node
+                        .index_connection(&next)
+                        .map_or(DEFAULT_WEIGHT, |i| *node.connections[i].weight);
+                    node.connections = vec![Connection::new(next, weight)];
+                }
+                match dropped {
+                    0 => format!("Connected {} nodes into a ring.", names.len()),
+                    _ => format!(
+                        "Connected {} nodes into a ring, dropping {dropped} other connections.",
+                        names.len()
+                    ),
+                }
+            }
+            Fix::MakeUndirected => {
+                format!(
+                    "Added {} reverse connections.",
+                    self.add_reverse_connections()
+                )
+            }
+            Fix::PruneDangling => {
+                let names: Vec<String> = self.nodes.iter().map(|n| n.name.clone()).collect();
+                let mut removed = 0;
+                for node in self.nodes.iter_mut() {
+                    let before = node.connections.len();
+                    node.connections.retain(|c| names.contains(&c.other));
+                    removed += before - node.connections.len();
+                }
+                format!("Removed {removed} connections to missing nodes.")
+            }
+            Fix::JoinComponents => {
+                let components: Vec<String> = self
+                    .components()
+                    .iter()
+                    .filter_map(|c| c.first().map(|n| n.to_string()))
+                    .collect();
+                for other in components.iter().skip(1) {
+                    self.link(&components[0], other);
+                }
+                format!(
+                    "Joined {} parts to {}.",
+                    components.len() - 1,
+                    components[0]
+                )
+            }
+        };
+        self.clear_results();
+        Ok(changed)
+    }
+
+    /// Number of connections making the nodes into a ring removes, those
+    /// which don't lead to the next node in grid order.
+    pub(crate) fn ring_fix_drops(&self) -> usize {
+        let names: Vec<&str> = self.nodes.iter().map(|n| n.name.as_str()).collect();
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| {
+                let next = names[(index + 1) % names.len()];
+                node.connections.iter().filter(|c| c.other != next).count()
+            })
+            .sum()
+    }
+
+    /// Connects the nodes named `a` and `b` in both directions.
+    fn link(&mut self, a: &str, b: &str) {
+        for (from, to) in [(a, b), (b, a)] {
+            if let Some(node) = self.nodes.iter_mut().find(|n| n.name == from) {
+                node.add_connection(&Connection::new(to.to_string(), DEFAULT_WEIGHT));
+            }
+        }
+    }
+
+    /// Connections to nodes which aren't placed, as `from->to`.
+    fn dangling_connections(&self) -> Vec<String> {
+        self.nodes
+            .iter()
+            .flat_map(|n| n.connections.iter().map(move |c| (n, c)))
+            .filter(|(_, c)| !self.node_exists(&c.other))
+            .map(|(n, c)| format!("{}->{}", n.name, c.other))
+            .collect()
+    }
+
+    /// Nodes from which some placed node can't be reached over outgoing
+    /// connections.
+    fn nodes_not_reaching_all(&self) -> Vec<&str> {
+        self.nodes
+            .iter()
+            .filter(|n| {
+                let reachable = self.reachable(&n.name);
+                self.nodes.iter().any(|o| !reachable.contains(&o.name))
+            })
+            .map(|n| n.name.as_str())
+            .collect()
+    }
+}
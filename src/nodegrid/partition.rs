@@ -0,0 +1,78 @@
+use rand::{Rng, seq::IteratorRandom};
+use std::collections::BTreeSet;
+
+use crate::nodegrid::NodeGrid;
+
+impl NodeGrid {
+    /// Cuts `percent` of the links between nodes, in both directions, for the
+    /// runs to come. Messages sent over a cut link are dropped. Zero lifts the
+    /// partition.
+    pub(crate) fn partition<R: Rng>(
+        &mut self,
+        percent: u64,
+        rng: &mut R,
+        logger: &mut Vec<String>,
+    ) {
+        self.partition.clear();
+        if percent == 0 {
+            logger.push("Partition lifted.".to_string());
+            return;
+        }
+        let links: BTreeSet<(String, String)> = self
+            .nodes
+            .iter()
+            .flat_map(|n| {
+                n.connections
+                    .iter()
+                    .map(move |c| (n.name.clone(), c.other.clone()))
+            })
+            .map(|(a, b)| if a < b { (a, b) } else { (b, a) })
+            .collect();
+        let amount = (links.len() as u64 * percent.min(100)).div_ceil(100) as usize;
+        let mut cut = links.into_iter().choose_multiple(rng, amount);
+        cut.sort();
+        for (a, b) in cut.iter() {
+            self.partition.insert((a.clone(), b.clone()));
+            self.partition.insert((b.clone(), a.clone()));
+        }
+        logger.push(format!(
+            "Partitioned the grid, cutting {}.",
+            cut.iter()
+                .map(|(a, b)| format!("{a}-{b}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        let isolated = self.isolated_nodes();
+        if !isolated.is_empty() {
+            logger.push(format!("Isolated nodes {}.", isolated.join(", ")));
+        }
+    }
+
+    /// Nodes with connections, all of which are cut by the partition.
+    pub(crate) fn isolated_nodes(&self) -> Vec<String> {
+        let links: Vec<(&str, &str)> = self
+            .nodes
+            .iter()
+            .flat_map(|n| {
+                n.connections
+                    .iter()
+                    .map(move |c| (n.name.as_str(), c.other.as_str()))
+            })
+            .collect();
+        self.nodes
+            .iter()
+            .map(|n| n.name.as_str())
+            .filter(|name| {
+                let touching: Vec<_> = links
+                    .iter()
+                    .filter(|(a, b)| a == name || b == name)
+                    .collect();
+                !touching.is_empty()
+                    && touching
+                        .iter()
+                        .all(|(a, b)| self.partition.contains(&(a.to_string(), b.to_string())))
+            })
+            .map(String::from)
+            .collect()
+    }
+}
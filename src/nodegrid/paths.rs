@@ -0,0 +1,85 @@
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+
+use crate::{node::Node, nodegrid::NodeGrid};
+
+impl NodeGrid {
+    /// Placed nodes ordered by id, the order of the distance matrix.
+    fn nodes_by_id(&self) -> Vec<&Node> {
+        let mut nodes: Vec<&Node> = self.nodes.iter().collect();
+        nodes.sort_by_key(|n| n.id);
+        nodes
+    }
+
+    /// Lengths of the shortest paths between every pair of nodes ordered by
+    /// id, following connections in their direction and summing weights.
+    /// Unreachable pairs are infinite. Fails if there's a negative cycle, as
+    /// paths through it have no shortest length.
+    pub(crate) fn all_pairs_shortest_paths(&self) -> Result<Vec<Vec<f64>>> {
+        let nodes = self.nodes_by_id();
+        let index: HashMap<&str, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.name.as_str(), i))
+            .collect();
+        let mut distances = vec![vec![f64::INFINITY; nodes.len()]; nodes.len()];
+        for (i, node) in nodes.iter().enumerate() {
+            distances[i][i] = 0.0;
+            for connection in node.connections.iter() {
+                if let Some(&j) = index.get(connection.other.as_str()) {
+                    distances[i][j] = distances[i][j].min(*connection.weight);
+                }
+            }
+        }
+        for k in 0..nodes.len() {
+            for i in 0..nodes.len() {
+                for j in 0..nodes.len() {
+                    let through = distances[i][k] + distances[k][j];
+                    if through < distances[i][j] {
+                        distances[i][j] = through;
+                    }
+                }
+            }
+        }
+        if let Some(i) = (0..nodes.len()).find(|&i| distances[i][i] < 0.0) {
+            Err(anyhow!("{} is on a negative cycle.", nodes[i].name))?;
+        }
+        Ok(distances)
+    }
+
+    /// The distance matrix as aligned lines, with a header of node names.
+    pub(crate) fn distance_matrix(&self) -> Result<Vec<String>> {
+        let distances = self.all_pairs_shortest_paths()?;
+        let names: Vec<&str> = self.nodes_by_id().iter().map(|n| n.name.as_str()).collect();
+        let cells: Vec<Vec<String>> = distances
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|d| match d.is_finite() {
+                        true => d.to_string(),
+                        false => "∞".to_string(),
+                    })
+                    .collect()
+            })
+            .collect();
+        let width = names
+            .iter()
+            .map(|n| n.chars().count())
+            .chain(cells.iter().flatten().map(|c| c.chars().count()))
+            .max()
+            .unwrap_or(0);
+        let line = |first: &str, rest: &[&str]| {
+            std::iter::once(first)
+                .chain(rest.iter().copied())
+                .map(|c| format!("{c:>width$}"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let mut lines = vec![line("", &names)];
+        for (name, row) in names.iter().zip(cells.iter()) {
+            let row: Vec<&str> = row.iter().map(String::as_str).collect();
+            lines.push(line(name, &row));
+        }
+        Ok(lines)
+    }
+}
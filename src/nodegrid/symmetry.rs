@@ -0,0 +1,99 @@
+use anyhow::{Result, anyhow};
+use std::collections::{HashMap, HashSet};
+
+use crate::nodegrid::NodeGrid;
+
+/// Largest grid searched for automorphisms, as the search is brute force.
+pub(crate) const AUTOMORPHISM_LIMIT: usize = 10;
+
+/// Directed adjacency of the grid by node index, for the automorphism search.
+struct Adjacency {
+    edges: HashSet<(usize, usize)>,
+    degrees: Vec<(usize, usize)>,
+}
+
+impl Adjacency {
+    fn edge(&self, from: usize, to: usize) -> bool {
+        self.edges.contains(&(from, to))
+    }
+
+    /// Extends `mapping` of the first nodes to all of them, preserving every
+    /// connection. Only mappings other than the identity are accepted.
+    fn extend(&self, mapping: &mut Vec<usize>, used: &mut [bool]) -> bool {
+        let node = mapping.len();
+        if node == self.degrees.len() {
+            return mapping.iter().enumerate().any(|(i, &m)| i != m);
+        }
+        for image in 0..self.degrees.len() {
+            if used[image] || self.degrees[node] != self.degrees[image] {
+                continue;
+            }
+            let consistent = (0..node).chain([node]).all(|other| {
+                let other_image = if other == node { image } else { mapping[other] };
+                self.edge(node, other) == self.edge(image, other_image)
+                    && self.edge(other, node) == self.edge(other_image, image)
+            });
+            if !consistent {
+                continue;
+            }
+            mapping.push(image);
+            used[image] = true;
+            if self.extend(mapping, used) {
+                return true;
+            }
+            mapping.pop();
+            used[image] = false;
+        }
+        false
+    }
+}
+
+impl NodeGrid {
+    /// A mapping of nodes onto other nodes which keeps every connection, if
+    /// there is one besides the identity, as the (node, image) pairs of the
+    /// nodes which move. Nodes mapped onto each other can't be told apart by
+    /// structure alone. Fails on grids over `AUTOMORPHISM_LIMIT` nodes.
+    pub(crate) fn nontrivial_automorphism(&self) -> Result<Option<Vec<(String, String)>>> {
+        if self.nodes.len() > AUTOMORPHISM_LIMIT {
+            Err(anyhow!(
+                "Grid has {} nodes, symmetry is only searched up to {AUTOMORPHISM_LIMIT}.",
+                self.nodes.len()
+            ))?;
+        }
+        let index: HashMap<&str, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.name.as_str(), i))
+            .collect();
+        let mut edges = HashSet::new();
+        for (i, node) in self.nodes.iter().enumerate() {
+            for connection in node.connections.iter() {
+                if let Some(&j) = index.get(connection.other.as_str()) {
+                    edges.insert((i, j));
+                }
+            }
+        }
+        let degrees = (0..self.nodes.len())
+            .map(|i| {
+                let incoming = edges.iter().filter(|e| e.1 == i).count();
+                let outgoing = edges.iter().filter(|e| e.0 == i).count();
+                (incoming, outgoing)
+            })
+            .collect();
+        let adjacency = Adjacency { edges, degrees };
+        let mut mapping = vec![];
+        let mut used = vec![false; self.nodes.len()];
+        if !adjacency.extend(&mut mapping, &mut used) {
+            return Ok(None);
+        }
+        Ok(Some(
+            mapping
+                .iter()
+                .enumerate()
+                .filter(|(i, m)| i != *m)
+                .map(|(i, &m)| (self.nodes[i].name.clone(), self.nodes[m].name.clone()))
+                .collect(),
+        ))
+    }
+}
@@ -0,0 +1,1579 @@
+use super::*;
+use crate::COMPACT_NODE_WIDTH;
+use crate::node::connection::WEIGHT_STEP;
+use crate::theme::Theme;
+use rand::{SeedableRng, rngs::StdRng};
+use strum::IntoEnumIterator;
+
+/// Builds a grid of nodes named after `names`, with ids and locations following
+/// their order, and a directed connection for every pair in `edges`.
+fn grid(names: &[&str], edges: &[(&str, &str)]) -> NodeGrid {
+    let mut grid = NodeGrid::default();
+    for (id, name) in names.iter().enumerate() {
+        grid.nodes.push(Node {
+            name: name.to_string(),
+            id: id + 1,
+            location: Location::new(id as u16, 0),
+            ..Default::default()
+        });
+    }
+    for (from, to) in edges {
+        let node = grid.nodes.iter_mut().find(|n| n.name == *from).unwrap();
+        node.add_connection(&Connection::new(to.to_string(), 1.0));
+    }
+    grid
+}
+
+/// A unidirectional ring `n0 -> n1 -> ... -> n0`.
+fn ring(n: usize) -> NodeGrid {
+    let names: Vec<String> = (0..n).map(|i| format!("n{i}")).collect();
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    let edges: Vec<(&str, &str)> = (0..n).map(|i| (names[i], names[(i + 1) % n])).collect();
+    grid(&names, &edges)
+}
+
+#[test]
+fn results_are_kept_after_run() -> Result<()> {
+    let mut grid = ring(4);
+    let mut logger = vec![];
+    grid.run_algorithm(SelectedAlgorithm::ChangRoberts, &mut logger)?;
+
+    assert_eq!(grid.result("n3").map(String::as_str), Some("leader"));
+    assert_eq!(grid.result("n0").map(String::as_str), Some("passive"));
+    assert!(grid.result("missing").is_none());
+
+    grid.new_node("n4".to_string())?;
+    assert!(grid.result("n3").is_none());
+    Ok(())
+}
+
+#[test]
+fn connect_reports_updated_edges() -> Result<()> {
+    let mut grid = grid(&["a", "b"], &[]);
+    grid.pick("a".to_string())?;
+
+    let created = grid.connect(&Connection::new("b".to_string(), 1.0))?;
+    assert_eq!(
+        created,
+        vec![EdgeChange::Created {
+            from: "a".to_string(),
+            to: "b".to_string(),
+            weight: 1.0
+        }]
+    );
+
+    let updated = grid.connect(&Connection::new("b".to_string(), 2.5))?;
+    assert_eq!(
+        updated,
+        vec![EdgeChange::Updated {
+            from: "a".to_string(),
+            to: "b".to_string(),
+            old: 1.0,
+            new: 2.5
+        }]
+    );
+    assert_eq!(
+        updated[0].to_string(),
+        "Updated connection a->b weight 1 -> 2.5."
+    );
+    assert_eq!(grid.floating_nodes[0].connections.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn chang_roberts_on_bidirectional_ring() -> Result<()> {
+    let mut grid = grid(
+        &["n0", "n1", "n2", "n3", "n4"],
+        &[
+            ("n0", "n1"),
+            ("n0", "n4"),
+            ("n1", "n0"),
+            ("n1", "n2"),
+            ("n2", "n3"),
+            ("n2", "n1"),
+            ("n3", "n2"),
+            ("n3", "n4"),
+            ("n4", "n0"),
+            ("n4", "n3"),
+        ],
+    );
+    let successors = grid.ring_successors().expect("Grid is a ring.");
+    assert_eq!(successors["n0"], "n1");
+    assert_eq!(successors["n2"], "n3");
+    assert_eq!(successors["n4"], "n0");
+
+    let mut logger = vec![];
+    grid.run_algorithm(SelectedAlgorithm::ChangRoberts, &mut logger)?;
+    assert_eq!(grid.result("n4").map(String::as_str), Some("leader"));
+    Ok(())
+}
+
+#[test]
+fn nodes_are_colored_by_the_state_they_ended_in() -> Result<()> {
+    let theme = Theme::default();
+    assert_ne!(theme.leader, theme.node);
+    let mut grid = ring(4);
+    let mut logger = vec![];
+    grid.run_algorithm(SelectedAlgorithm::ChangRoberts, &mut logger)?;
+    assert_eq!(grid.display_states.len(), 4);
+    for node in grid.nodes.iter() {
+        let expected = match grid.result(&node.name).map(String::as_str) {
+            Some("leader") => theme.leader,
+            _ => theme.passive,
+        };
+        assert_eq!(grid.node_color(node, &theme), expected);
+    }
+
+    grid.run_algorithm(SelectedAlgorithm::ChandyLamport, &mut logger)?;
+    assert!(
+        grid.nodes
+            .iter()
+            .all(|n| grid.node_color(n, &theme) == theme.recorded)
+    );
+
+    grid.clear_results();
+    assert!(
+        grid.nodes
+            .iter()
+            .all(|n| grid.node_color(n, &theme) == theme.node)
+    );
+    Ok(())
+}
+
+#[test]
+fn ring_successors_rejects_other_shapes() {
+    assert!(ring(3).ring_successors().is_some());
+    assert!(
+        grid(&["a", "b", "c"], &[("a", "b"), ("b", "a"), ("c", "a")])
+            .ring_successors()
+            .is_none()
+    );
+    assert!(NodeGrid::default().ring_successors().is_none());
+}
+
+#[test]
+fn ring_elections_refuse_other_shapes() {
+    assert!(ring(4).is_ring());
+    let path = grid(&["a", "b", "c"], &[("a", "b"), ("b", "c"), ("c", "b")]);
+    assert!(!path.is_ring());
+    for election in [
+        NodeGrid::chang_roberts,
+        NodeGrid::franklin,
+        NodeGrid::peterson,
+    ] {
+        let mut grid = path.clone();
+        let mut logger = vec![];
+        let result = election(&mut grid, &mut logger);
+        assert_eq!(result.unwrap_err().to_string(), "Graph is not a ring.");
+        assert_eq!(logger.last().unwrap(), "Graph is not a ring.");
+        assert!(grid.results.is_empty());
+    }
+}
+
+#[test]
+fn self_loops_are_refused_or_dropped() -> Result<()> {
+    let mut grid = grid(&["a", "b"], &[("a", "b")]);
+    grid.pick("a".to_string())?;
+    let looped = Connection::new("a".to_string(), 1.0);
+    assert!(grid.connect(&looped).is_err());
+    assert!(grid.connect_reverse(&looped).is_err());
+    let mut edited = grid.floating_nodes[0].clone();
+    edited.add_connection(&looped);
+    let error = grid.overwrite(serde_json::to_string(&edited)?).unwrap_err();
+    assert_eq!(error.to_string(), "Can't connect a to itself.");
+    assert_eq!(grid.floating_nodes[0].connections.len(), 1);
+
+    grid.commit()?;
+    let b = grid.nodes.iter().position(|n| n.name == "b").unwrap();
+    grid.nodes[b]
+        .connections
+        .push(Connection::new("b".to_string(), 1.0));
+    let mut logger = vec![];
+    assert_eq!(grid.drop_self_loops(&mut logger), ["b"]);
+    assert_eq!(logger, ["Dropped the connections of b to themselves."]);
+    assert!(grid.nodes[b].connections.is_empty());
+    assert!(grid.drop_self_loops(&mut logger).is_empty());
+    assert_eq!(logger.len(), 1);
+
+    grid.nodes[b]
+        .connections
+        .push(Connection::new("x".to_string(), 1.0));
+    assert!(grid.rename_node("b", "x").is_err());
+
+    let ring = NodeGrid::generate_ring(1, &mut StdRng::seed_from_u64(1));
+    assert!(ring.nodes[0].connections.is_empty());
+    let mut single = self::grid(&["a"], &[]);
+    assert!(single.apply_fix(Fix::MakeRing).is_err());
+    Ok(())
+}
+
+#[test]
+fn connect_nearest_picks_closest_node() -> Result<()> {
+    let mut grid = grid(&["far", "near", "mid"], &[]);
+    grid.nodes[0].location = Location::new(5, 5);
+    grid.nodes[1].location = Location::new(1, 1);
+    grid.nodes[2].location = Location::new(3, 0);
+
+    grid.new_node("x".to_string())?;
+    let changes = grid.connect_nearest()?;
+    assert_eq!(
+        changes,
+        vec![EdgeChange::Created {
+            from: "x".to_string(),
+            to: "near".to_string(),
+            weight: DEFAULT_WEIGHT
+        }]
+    );
+
+    let mut empty = NodeGrid::default();
+    empty.new_node("x".to_string())?;
+    assert!(empty.connect_nearest().is_err());
+    Ok(())
+}
+
+#[test]
+fn generated_chang_roberts_example_is_ring() -> Result<()> {
+    let grid = NodeGrid::generate_for(
+        SelectedAlgorithm::ChangRoberts,
+        7,
+        &mut StdRng::seed_from_u64(7),
+    )?;
+    assert_eq!(grid.nodes.len(), 7);
+    assert!(grid.ring_successors().is_some());
+
+    let mut ids: Vec<usize> = grid.nodes.iter().map(|n| n.id).collect();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 7);
+    Ok(())
+}
+
+#[test]
+fn tree_check_on_tree() {
+    let tree = grid(
+        &["a", "b", "c", "d"],
+        &[("a", "b"), ("b", "a"), ("b", "c"), ("d", "b")],
+    );
+    assert!(tree.is_tree());
+}
+
+#[test]
+fn tree_check_on_cycle() {
+    let cycle = grid(
+        &["a", "b", "c", "d"],
+        &[("a", "b"), ("b", "c"), ("c", "a"), ("c", "d")],
+    );
+    assert_eq!(cycle.tree_check(), Err(NotATree::Cycle));
+}
+
+#[test]
+fn tree_check_on_forest() {
+    let forest = grid(&["a", "b", "c", "d"], &[("a", "b"), ("c", "d")]);
+    assert_eq!(forest.tree_check(), Err(NotATree::Disconnected));
+    assert_eq!(NodeGrid::default().tree_check(), Err(NotATree::Empty));
+}
+
+#[test]
+fn anonymized_keeps_structure() {
+    let original = grid(
+        &["alice", "bob", "carol"],
+        &[
+            ("alice", "bob"),
+            ("bob", "carol"),
+            ("carol", "alice"),
+            ("carol", "bob"),
+        ],
+    );
+    let anonymized = original.anonymized();
+
+    let names: HashMap<&str, &str> = original
+        .nodes
+        .iter()
+        .zip(anonymized.nodes.iter())
+        .map(|(o, a)| (o.name.as_str(), a.name.as_str()))
+        .collect();
+    assert_eq!(names["alice"], "n0");
+    assert_eq!(names["carol"], "n2");
+
+    for (original, anonymized) in original.nodes.iter().zip(anonymized.nodes.iter()) {
+        let renamed: Vec<Connection> = original
+            .connections
+            .iter()
+            .map(|c| Connection::new(names[c.other.as_str()].to_string(), *c.weight))
+            .collect();
+        assert_eq!(renamed, anonymized.connections);
+        assert_eq!(original.location, anonymized.location);
+    }
+}
+
+#[test]
+fn clear_connections_removes_reciprocal_edges() -> Result<()> {
+    let edges = [("a", "b"), ("a", "c"), ("b", "a"), ("c", "b")];
+    let mut grid = grid(&["a", "b", "c"], &edges);
+    grid.pick("a".to_string())?;
+    assert_eq!(grid.clear_connections(false)?, 2);
+    assert!(grid.floating_nodes[0].connections.is_empty());
+    assert_eq!(grid.nodes[0].connections.len(), 1);
+
+    let mut grid = self::grid(&["a", "b", "c"], &edges);
+    grid.pick("a".to_string())?;
+    assert_eq!(grid.clear_connections(true)?, 3);
+    assert!(grid.floating_nodes[0].connections.is_empty());
+    assert!(
+        grid.nodes
+            .iter()
+            .all(|n| n.index_connection(&"a".to_string()).is_none())
+    );
+
+    assert!(NodeGrid::default().clear_connections(true).is_err());
+    Ok(())
+}
+
+#[test]
+fn chang_roberts_stays_within_bound() -> Result<()> {
+    for n in [2, 5, 9] {
+        let mut grid = ring(n);
+        grid.run_algorithm(SelectedAlgorithm::ChangRoberts, &mut vec![])?;
+        let bound = SelectedAlgorithm::ChangRoberts
+            .message_bound(n, grid.channel_count())
+            .unwrap();
+        assert!(grid.messages_sent() > 0);
+        assert!(grid.messages_sent() <= bound);
+    }
+    Ok(())
+}
+
+#[test]
+fn runs_can_pick_the_initiator_by_weight() -> Result<()> {
+    let edges = [("a", "b"), ("b", "a"), ("b", "c"), ("c", "b")];
+    let mut grid = grid(&["a", "b", "c"], &edges);
+    grid.frozen = true;
+    let mut logger = vec![];
+    grid.run_algorithm(SelectedAlgorithm::Echo, &mut logger)?;
+    assert!(logger.contains(&"Choose a as initator.".to_string()));
+
+    grid.weighted_initiator = true;
+    let mut logger = vec![];
+    grid.run_algorithm(SelectedAlgorithm::Echo, &mut logger)?;
+    assert!(logger.contains(&"Initiator weights a=2, b=4, c=2.".to_string()));
+    assert!(logger.contains(&"Choose b as initator.".to_string()));
+    Ok(())
+}
+
+#[test]
+fn waves_and_causal_order_stay_within_bound() -> Result<()> {
+    for algorithm in [
+        SelectedAlgorithm::Echo,
+        SelectedAlgorithm::Tarry,
+        SelectedAlgorithm::CausalOrder,
+    ] {
+        let mut grid = NodeGrid::generate_connected(7, &mut StdRng::seed_from_u64(5));
+        grid.run_algorithm(algorithm, &mut vec![])?;
+        let bound = algorithm
+            .message_bound(grid.nodes.len(), grid.channel_count())
+            .unwrap();
+        assert!(grid.messages_sent() > 0);
+        assert!(grid.messages_sent() <= bound, "{algorithm} exceeds {bound}");
+    }
+    Ok(())
+}
+
+#[test]
+fn connections_show_messages_sent_over_them() {
+    let mut grid = grid(&["a", "b", "c"], &[("a", "b"), ("b", "c"), ("c", "b")]);
+    let sent = |sender: &str, destination: &str| TraceRecord {
+        event: TraceEvent::Sent,
+        sender: sender.to_string(),
+        destination: destination.to_string(),
+        kind: "ping".to_string(),
+        step: 0,
+    };
+    grid.trace = vec![
+        sent("a", "b"),
+        sent("a", "b"),
+        sent("a", "b"),
+        sent("c", "b"),
+    ];
+    let theme = Theme {
+        busy: ratatui::style::Color::LightMagenta,
+        ..Default::default()
+    };
+    let display = NodeGridDisplay::new(grid).message_counts(true).theme(theme);
+    let area = Rect::new(0, 0, 30, 9);
+    let mut buf = Buffer::empty(area);
+    display.render_scrolled(area, &mut buf);
+
+    // In the middle of the spacing between the nodes.
+    let y = NODE_V_SPACING + NODE_HEIGHT / 2;
+    let middle =
+        |column: u16| NODE_H_SPACING + column * (NODE_H_SPACING + NODE_WIDTH) + NODE_WIDTH + 1;
+    assert_eq!(buf[(middle(0), y)].symbol(), "3");
+    assert_eq!(buf[(middle(0), y)].fg, theme.busy);
+    assert_eq!(buf[(middle(1), y)].symbol(), "1");
+    assert_eq!(buf[(middle(1), y)].fg, ratatui::style::Color::Reset);
+}
+
+#[test]
+fn rings_are_symmetric_and_paths_with_a_tail_are_not() -> Result<()> {
+    let mapping = ring(5).nontrivial_automorphism()?.unwrap();
+    assert_eq!(mapping.len(), 5);
+
+    // The cycle a -> b -> e -> a with the tail b -> c -> d: a, c and e have
+    // the same degrees, but sit differently around the cycle.
+    let grid = grid(
+        &["a", "b", "c", "d", "e"],
+        &[("a", "b"), ("b", "c"), ("c", "d"), ("b", "e"), ("e", "a")],
+    );
+    assert_eq!(grid.nontrivial_automorphism()?, None);
+
+    assert!(ring(11).nontrivial_automorphism().is_err());
+    Ok(())
+}
+
+#[test]
+fn collapsed_nodes_keep_external_connections() -> Result<()> {
+    let edges = [
+        ("a", "b"),
+        ("b", "a"),
+        ("a", "c"),
+        ("b", "c"),
+        ("b", "d"),
+        ("c", "a"),
+        ("d", "b"),
+    ];
+    let mut grid = grid(&["a", "b", "c", "d"], &edges);
+    let original = grid.clone();
+    let targets = |node: &Node| -> BTreeSet<String> {
+        node.connections.iter().map(|c| c.other.clone()).collect()
+    };
+    let members = ["a".to_string(), "b".to_string()];
+    grid.collapse(&members, "ab".to_string())?;
+
+    let super_node = grid.nodes.iter().find(|n| n.name == "ab").unwrap();
+    let external: BTreeSet<String> = original.nodes[..2]
+        .iter()
+        .flat_map(targets)
+        .filter(|other| !members.contains(other))
+        .collect();
+    assert_eq!(targets(super_node), external);
+    assert_eq!(super_node.location, Location::new(0, 0));
+    for name in ["c", "d"] {
+        let node = grid.nodes.iter().find(|n| n.name == name).unwrap();
+        assert_eq!(targets(node), BTreeSet::from(["ab".to_string()]));
+    }
+    assert!(grid.collapse(&members, "ab".to_string()).is_err());
+
+    assert_eq!(grid.expand("ab")?, ["a", "b"]);
+    for node in original.nodes.iter() {
+        let restored = grid.nodes.iter().find(|n| n.name == node.name).unwrap();
+        assert_eq!(targets(restored), targets(node));
+        assert_eq!(restored.location, node.location);
+    }
+    assert!(grid.expand("a").is_err());
+
+    let twice = ["a".to_string(), "a".to_string()];
+    assert!(grid.collapse(&twice, "aa".to_string()).is_err());
+    grid.new_node("x".to_string())?;
+    assert!(grid.collapse(&members, "x".to_string()).is_err());
+    Ok(())
+}
+
+#[test]
+fn bordered_nodes_are_drawn_in_a_box() {
+    let display = NodeGridDisplay::new(grid(&["a"], &[])).node_border(NodeBorder::Rounded);
+    let area = Rect::new(0, 0, 12, 9);
+    let mut buf = Buffer::empty(area);
+    display.render_scrolled(area, &mut buf);
+    let (x, y) = (NODE_H_SPACING, NODE_V_SPACING);
+    let right = x + NODE_WIDTH - 1;
+    let bottom = y + NODE_HEIGHT - 1;
+    assert_eq!(buf[(x, y)].symbol(), "╭");
+    assert_eq!(buf[(right, y)].symbol(), "╮");
+    assert_eq!(buf[(x, bottom)].symbol(), "╰");
+    assert_eq!(buf[(right, bottom)].symbol(), "╯");
+    assert_eq!(buf[(x + 1, y)].symbol(), "─");
+    assert_eq!(buf[(x, y + 1)].symbol(), "│");
+    let name: String = (x + 1..right).map(|i| buf[(i, y + 1)].symbol()).collect();
+    assert_eq!(name.trim(), "a");
+}
+
+#[test]
+fn shortest_paths_match_hand_computed_distances() -> Result<()> {
+    // a -1-> b -2-> c, a -5-> c, c -1-> a, and d unreachable.
+    let mut grid = grid(&["a", "b", "c", "d"], &[]);
+    for (from, to, weight) in [
+        ("a", "b", 1.0),
+        ("b", "c", 2.0),
+        ("a", "c", 5.0),
+        ("c", "a", 1.0),
+    ] {
+        let node = grid.nodes.iter_mut().find(|n| n.name == from).unwrap();
+        node.add_connection(&Connection::new(to.to_string(), weight));
+    }
+    let inf = f64::INFINITY;
+    assert_eq!(
+        grid.all_pairs_shortest_paths()?,
+        [
+            [0.0, 1.0, 3.0, inf],
+            [3.0, 0.0, 2.0, inf],
+            [1.0, 2.0, 0.0, inf],
+            [inf, inf, inf, 0.0],
+        ]
+    );
+    assert_eq!(grid.distance_matrix()?[2], "b 3 0 2 ∞");
+
+    grid.nodes[2].add_connection(&Connection::new("a".to_string(), -4.0));
+    assert!(grid.all_pairs_shortest_paths().is_err());
+    Ok(())
+}
+
+#[test]
+fn weight_keys_adjust_current_connection() -> Result<()> {
+    let mut grid = grid(&["a", "b", "c"], &[("a", "b"), ("a", "c")]);
+    grid.pick("a".to_string())?;
+    let weight = |grid: &NodeGrid, other: &str| {
+        let index = grid.floating_nodes[0].index_connection(&other.to_string());
+        *grid.floating_nodes[0].connections[index.unwrap()].weight
+    };
+
+    grid.connect(&grid.adjusted_connection(WEIGHT_STEP)?)?;
+    assert_eq!(weight(&grid, "b"), DEFAULT_WEIGHT + WEIGHT_STEP);
+
+    assert_eq!(grid.cycle_connection()?, "c");
+    assert!(
+        grid.highlighted
+            .contains(&("a".to_string(), "c".to_string()))
+    );
+    grid.connect(&grid.adjusted_connection(-WEIGHT_STEP)?)?;
+    assert_eq!(weight(&grid, "c"), DEFAULT_WEIGHT - WEIGHT_STEP);
+    for _ in 0..3 {
+        grid.connect(&grid.adjusted_connection(-WEIGHT_STEP)?)?;
+    }
+    assert_eq!(weight(&grid, "c"), -1.0);
+    grid.connect(&grid.adjusted_connection(WEIGHT_STEP)?)?;
+    assert_eq!(weight(&grid, "c"), -0.5);
+    assert_eq!(weight(&grid, "b"), DEFAULT_WEIGHT + WEIGHT_STEP);
+    Ok(())
+}
+
+#[test]
+fn inspect_tabulates_connection_directions() -> Result<()> {
+    let mut grid = grid(
+        &["a", "b", "c", "d"],
+        &[("a", "b"), ("b", "a"), ("a", "c"), ("d", "a")],
+    );
+    grid.pick("a".to_string())?;
+    let inspection = grid.inspect_floating()?;
+    let table: Vec<&str> = inspection
+        .lines()
+        .skip_while(|l| *l != "Connections:")
+        .skip(1)
+        .take(4)
+        .collect();
+    assert_eq!(
+        table,
+        [
+            "    Node | Weight | Direction",
+            "    b    |      1 | both",
+            "    c    |      1 | out",
+            "    d    |      1 | in",
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn note_survives_save_and_load() -> Result<()> {
+    let mut grid = grid(&["a", "b"], &[("a", "b")]);
+    grid.pick("a".to_string())?;
+    grid.annotate("coordinator".to_string())?;
+    assert!(grid.inspect_floating()?.contains("Note: coordinator"));
+    grid.commit()?;
+
+    let saved = serde_json::to_string(&grid)?;
+    let mut loaded: NodeGrid = serde_json::from_str(&saved)?;
+    loaded.pick("a".to_string())?;
+    assert_eq!(loaded.get_floating_note()?, "coordinator");
+    assert!(loaded.inspect_floating()?.contains("Note: coordinator"));
+
+    loaded.commit()?;
+    loaded.pick("b".to_string())?;
+    assert!(!loaded.inspect_floating()?.contains("Note:"));
+    Ok(())
+}
+
+#[test]
+fn run_on_included_subgraph() -> Result<()> {
+    let mut grid = grid(
+        &["alpha", "beta", "gamma", "delta", "epsilon"],
+        &[
+            ("alpha", "beta"),
+            ("beta", "gamma"),
+            ("gamma", "alpha"),
+            ("gamma", "delta"),
+            ("delta", "epsilon"),
+            ("epsilon", "alpha"),
+        ],
+    );
+    for name in ["alpha", "beta", "gamma"] {
+        grid.pick(name.to_string())?;
+        assert!(grid.toggle_included()?);
+        grid.commit()?;
+    }
+
+    let mut logger = vec![];
+    grid.run_algorithm(SelectedAlgorithm::ChangRoberts, &mut logger)?;
+    assert_eq!(logger[0], "Running on included nodes alpha, beta, gamma.");
+    assert_eq!(logger[1], "Excluded nodes delta, epsilon.");
+    for line in logger.iter().skip(2) {
+        assert!(
+            !line.contains("delta") && !line.contains("epsilon"),
+            "{line}"
+        );
+    }
+    assert_eq!(grid.result("gamma").map(String::as_str), Some("leader"));
+    assert!(grid.result("delta").is_none());
+    Ok(())
+}
+
+#[test]
+fn undirected_connect_to_missing_node_is_rejected() -> Result<()> {
+    let mut grid = grid(&["a", "b"], &[]);
+    grid.pick("a".to_string())?;
+    let missing = Connection::new("c".to_string(), 1.0);
+    assert!(grid.connect_undirected(&missing).is_err());
+    assert!(grid.connect_reverse(&missing).is_err());
+    assert!(grid.floating_nodes[0].connections.is_empty());
+    assert!(grid.nodes.iter().all(|n| n.connections.is_empty()));
+
+    grid.connect_undirected(&Connection::new("b".to_string(), 1.0))?;
+    assert_eq!(grid.floating_nodes[0].connections.len(), 1);
+    assert!(grid.nodes[0].index_connection(&"a".to_string()).is_some());
+    Ok(())
+}
+
+#[test]
+fn diff_reports_node_and_weight_changes() {
+    let old = grid(&["a", "b"], &[("a", "b"), ("b", "a")]);
+    let mut new = grid(&["a", "b", "c"], &[("a", "b"), ("b", "a"), ("c", "a")]);
+    new.nodes[0].connections[0] = Connection::new("b".to_string(), 3.0);
+
+    let diff = old.diff(&new);
+    assert_eq!(diff.added_nodes, vec!["c".to_string()]);
+    assert!(diff.removed_nodes.is_empty());
+    assert_eq!(
+        diff.connections,
+        vec![
+            EdgeChange::Updated {
+                from: "a".to_string(),
+                to: "b".to_string(),
+                old: 1.0,
+                new: 3.0
+            },
+            EdgeChange::Created {
+                from: "c".to_string(),
+                to: "a".to_string(),
+                weight: 1.0
+            },
+        ]
+    );
+
+    let reverse = new.diff(&old);
+    assert_eq!(reverse.removed_nodes, vec!["c".to_string()]);
+    assert_eq!(
+        reverse.lines().last().map(String::as_str),
+        Some("Removed connection c->a with weight 1.")
+    );
+    assert_eq!(old.diff(&old).lines(), vec!["Grids are identical."]);
+}
+
+#[test]
+fn long_edges_are_labelled_at_both_ends() {
+    let grid = grid(&["a", "b", "c"], &[("a", "c")]);
+    let area = Rect::new(0, 0, 40, 10);
+    let rows = |labels: bool| -> Vec<String> {
+        let mut buf = Buffer::empty(area);
+        NodeGridDisplay::new(grid.clone())
+            .long_edge_labels(labels)
+            .render(area, &mut buf);
+        (0..area.height)
+            .map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect())
+            .collect()
+    };
+
+    let labelled = rows(true);
+    assert!(labelled.iter().any(|r| r.contains("&c")));
+    assert!(labelled.iter().any(|r| r.contains("a&")));
+
+    let plain = rows(false);
+    assert!(plain.iter().any(|r| r.contains("&c")));
+    assert!(!plain.iter().any(|r| r.contains("a&")));
+}
+
+#[test]
+fn long_edges_show_messages_sent_over_them() {
+    let mut grid = grid(&["a", "b", "c"], &[("a", "c")]);
+    grid.trace = vec![TraceRecord {
+        event: TraceEvent::Sent,
+        sender: "a".to_string(),
+        destination: "c".to_string(),
+        kind: "ping".to_string(),
+        step: 0,
+    }];
+    let area = Rect::new(0, 0, 40, 10);
+    let rows = |weights: bool| -> Vec<String> {
+        let mut buf = Buffer::empty(area);
+        NodeGridDisplay::new(grid.clone())
+            .message_counts(true)
+            .edge_weights(weights)
+            .render(area, &mut buf);
+        (0..area.height)
+            .map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect())
+            .collect()
+    };
+
+    assert!(rows(false).iter().any(|r| r.contains("&c 1")));
+    assert!(rows(true).iter().any(|r| r.contains("&c 1 1")));
+}
+
+#[test]
+fn connection_weights_are_written_next_to_them() {
+    let mut grid = grid(&["a", "b", "c", "d"], &[("b", "a"), ("a", "d")]);
+    grid.nodes[0].add_connection(&Connection::new("b".to_string(), 2.5));
+    let area = Rect::new(0, 0, 50, 10);
+    let rows = |weights: bool| -> Vec<String> {
+        let mut buf = Buffer::empty(area);
+        NodeGridDisplay::new(grid.clone())
+            .edge_weights(weights)
+            .render_scrolled(area, &mut buf);
+        (0..area.height)
+            .map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect())
+            .collect()
+    };
+
+    let weighted = rows(true);
+    assert!(weighted.iter().any(|r| r.contains("2.5/1")));
+    assert!(weighted.iter().any(|r| r.contains("&d 1")));
+    assert!(!rows(false).iter().any(|r| r.contains("2.5")));
+}
+
+#[test]
+fn snapshot_algorithms_agree_on_total() -> Result<()> {
+    let mut grid = NodeGrid::generate_connected(6, &mut StdRng::seed_from_u64(7));
+    for _ in 0..20 {
+        let mut logger = vec![];
+        grid.compare_snapshots(&mut logger)?;
+        assert_eq!(
+            logger.last().map(String::as_str),
+            Some("Pass: both snapshots record a total of 0.")
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn compact_nodes_take_less_space() {
+    let area = Rect::new(0, 0, 20, 10);
+    let drawn_cells = |compact: bool| -> (u16, u16) {
+        let mut buf = Buffer::empty(area);
+        NodeGridDisplay::new(grid(&["node"], &[]))
+            .compact(compact)
+            .render(area, &mut buf);
+        let cells: Vec<(u16, u16)> = area
+            .positions()
+            .filter(|&p| buf[p] != ratatui::buffer::Cell::EMPTY)
+            .map(|p| (p.x, p.y))
+            .collect();
+        let width =
+            cells.iter().map(|c| c.0).max().unwrap() - cells.iter().map(|c| c.0).min().unwrap();
+        let height =
+            cells.iter().map(|c| c.1).max().unwrap() - cells.iter().map(|c| c.1).min().unwrap();
+        (width + 1, height + 1)
+    };
+    let (width, height) = drawn_cells(false);
+    let (compact_width, compact_height) = drawn_cells(true);
+    assert!(compact_width < width || compact_height < height);
+    assert!(compact_width <= width && compact_height <= height);
+    assert_eq!(compact_height, 1);
+}
+
+#[test]
+fn compact_connections_fill_the_gap() {
+    let grid = grid(&["a", "b"], &[("a", "b")]);
+    let area = Rect::new(0, 0, 20, 5);
+    let mut buf = Buffer::empty(area);
+    NodeGridDisplay::new(grid)
+        .compact(true)
+        .render(area, &mut buf);
+    let (x, y) = NodeGrid::default().place_compact(&Location::new(0, 0));
+    assert_eq!(buf[(x + COMPACT_NODE_WIDTH, y)].symbol(), "→");
+}
+
+#[test]
+fn failed_run_returns_error() {
+    let mut logger = vec![];
+    let result = NodeGrid::default().run_algorithm(SelectedAlgorithm::ChangRoberts, &mut logger);
+    assert!(result.is_err());
+    assert_eq!(logger.last().unwrap(), "ChangRoberts did not complete.");
+}
+
+#[test]
+fn seeded_states_are_reproducible() -> Result<()> {
+    let states = |seed: u64| -> (Vec<isize>, Vec<String>) {
+        let mut grid = ring(5);
+        let mut logger = vec![];
+        grid.seed_states(10, &mut StdRng::seed_from_u64(seed), &mut logger);
+        (grid.nodes.iter().map(|n| n.state).collect(), logger)
+    };
+    assert_eq!(states(3), states(3));
+    let (assigned, logger) = states(3);
+    assert!(assigned.iter().all(|s| (-10..=10).contains(s)));
+    assert_eq!(
+        logger[1],
+        format!("Initial total: {}", assigned.iter().sum::<isize>())
+    );
+
+    // The snapshots conserve the seeded total.
+    let mut grid = ring(5);
+    grid.seed_states(10, &mut StdRng::seed_from_u64(3), &mut vec![]);
+    let mut logger = vec![];
+    grid.compare_snapshots(&mut logger)?;
+    assert_eq!(
+        logger.last().unwrap(),
+        &format!(
+            "Pass: both snapshots record a total of {}.",
+            grid.initial_total()
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn spanning_tree_covers_reachable_nodes() {
+    let mut grid = ring(5);
+    grid.nodes.push(Node {
+        name: "island".to_string(),
+        location: Location::new(0, 4),
+        ..Default::default()
+    });
+    let edges = grid.spanning_tree(&grid.nodes[0].name.clone());
+    assert_eq!(edges.len(), 5 - 1);
+    let children: BTreeSet<&str> = edges.iter().map(|(_, to)| to.as_str()).collect();
+    assert_eq!(children.len(), edges.len());
+    assert!(!children.contains("island"));
+
+    let grid = NodeGrid::generate_connected(8, &mut StdRng::seed_from_u64(3));
+    assert_eq!(grid.spanning_tree(&grid.nodes[0].name).len(), 8 - 1);
+    assert!(grid.spanning_tree("missing").is_empty());
+}
+
+#[test]
+fn milestones_leave_out_routine_messages() -> Result<()> {
+    let mut grid = ring(4);
+    let mut logger = vec!["Earlier line.".to_string()];
+    grid.run_algorithm(SelectedAlgorithm::ChandyLamport, &mut logger)?;
+    let is_routine = |line: &String| {
+        line.contains("->") && (line.contains(" received <") || line.contains("Sent <"))
+    };
+    assert!(logger.iter().any(is_routine));
+
+    Verbosity::Milestones.apply(&mut logger, 1);
+    assert_eq!(logger[0], "Earlier line.");
+    assert!(!logger.iter().any(is_routine));
+    assert!(
+        logger
+            .iter()
+            .any(|l| l.starts_with("Started Chandy-Lamport"))
+    );
+    assert!(logger.iter().any(|l| l.contains(" took Snapshot(")));
+    assert!(logger.iter().any(|l| l == "Snapshot completed."));
+    Ok(())
+}
+
+#[test]
+fn frozen_snapshots_are_deterministic() -> Result<()> {
+    let run = |algorithm: SelectedAlgorithm| -> Result<Vec<String>> {
+        let mut grid = ring(4);
+        grid.frozen = true;
+        let mut logger = vec![];
+        grid.run_algorithm(algorithm, &mut logger)?;
+        Ok(logger)
+    };
+    for algorithm in [SelectedAlgorithm::ChandyLamport, SelectedAlgorithm::LaiYang] {
+        let logger = run(algorithm)?;
+        assert_eq!(logger, run(algorithm)?);
+        assert_eq!(logger[1], "Random processing disabled.");
+        assert!(!logger.iter().any(|l| l.contains(" and send ")));
+        assert!(logger.iter().any(|l| l == "Snapshot completed."));
+    }
+    Ok(())
+}
+
+#[test]
+fn partition_isolating_a_node_stops_snapshot() -> Result<()> {
+    for algorithm in [SelectedAlgorithm::ChandyLamport, SelectedAlgorithm::LaiYang] {
+        let mut grid = ring(4);
+        grid.frozen = true;
+        for (from, to) in [("n1", "n2"), ("n2", "n3")] {
+            grid.partition.insert((from.to_string(), to.to_string()));
+            grid.partition.insert((to.to_string(), from.to_string()));
+        }
+        assert_eq!(grid.isolated_nodes(), ["n2"]);
+        let mut logger = vec![];
+        grid.run_algorithm(algorithm, &mut logger)?;
+        assert!(logger.contains(&"Snapshot did not complete.".to_string()));
+        // Nodes waiting on a marker from n2 don't finish either.
+        assert!(logger.contains(&"No snapshot recorded by n0, n2, n3.".to_string()));
+        assert!(grid.trace.iter().any(|r| r.event == TraceEvent::Dropped));
+    }
+
+    let mut grid = ring(4);
+    let mut logger = vec![];
+    grid.partition(50, &mut StdRng::seed_from_u64(1), &mut logger);
+    assert_eq!(grid.partition.len(), 4);
+    grid.partition(0, &mut StdRng::seed_from_u64(1), &mut logger);
+    assert!(grid.partition.is_empty());
+    Ok(())
+}
+
+#[test]
+fn message_limit_truncates_run() -> Result<()> {
+    let mut grid = ring(6);
+    grid.message_limit = Some(3);
+    let mut logger = vec![];
+    grid.run_algorithm(SelectedAlgorithm::ChandyLamport, &mut logger)?;
+    assert!(
+        logger.contains(&"Stopped after delivering 3 messages, the run is truncated.".to_string())
+    );
+    assert!(logger.contains(&"Snapshot did not complete.".to_string()));
+    let delivered = grid
+        .trace
+        .iter()
+        .filter(|r| r.event == TraceEvent::Delivered);
+    assert_eq!(delivered.count(), 3);
+    Ok(())
+}
+
+#[test]
+fn traffic_sets_the_random_messages_of_snapshots() -> Result<()> {
+    let sent = |traffic: Option<usize>| -> Result<usize> {
+        let mut grid = ring(6);
+        grid.seed = Some(4);
+        grid.traffic = traffic;
+        grid.run_algorithm(SelectedAlgorithm::LaiYang, &mut vec![])?;
+        Ok(grid
+            .trace
+            .iter()
+            .filter(|r| r.event == TraceEvent::Sent)
+            .count())
+    };
+    let quiet = sent(Some(1))?;
+    let busy = sent(Some(10))?;
+    assert!(busy > quiet, "{busy} messages sent busy, {quiet} quiet");
+    // 6 markers, and at least the 2 * 6 sent around the start.
+    assert!(quiet >= 18);
+    Ok(())
+}
+
+#[test]
+fn lai_yang_counts_heavy_traffic() -> Result<()> {
+    let mut grid = ring(3);
+    grid.seed = Some(4);
+    grid.traffic = Some(MAX_TRAFFIC);
+    let mut logger = vec![];
+    grid.run_algorithm(SelectedAlgorithm::LaiYang, &mut logger)?;
+    assert!(logger.contains(&"Snapshot completed.".to_string()));
+
+    // Intensities over the cap fall back to the default traffic.
+    grid.traffic = Some(usize::MAX);
+    grid.run_algorithm(SelectedAlgorithm::LaiYang, &mut vec![])?;
+    Ok(())
+}
+
+#[test]
+fn renaming_updates_connections() -> Result<()> {
+    let mut grid = grid(&["a", "b", "c"], &[("a", "b"), ("b", "a"), ("c", "b")]);
+    grid.marked.insert("b".to_string());
+    grid.rename_node("b", "hub")?;
+
+    let names: BTreeSet<&str> = grid.nodes.iter().map(|n| n.name.as_str()).collect();
+    assert_eq!(names, BTreeSet::from(["a", "c", "hub"]));
+    let targets = |name: &str| -> Vec<&str> {
+        let node = grid.nodes.iter().find(|n| n.name == name).unwrap();
+        node.connections.iter().map(|c| c.other.as_str()).collect()
+    };
+    assert_eq!(targets("a"), ["hub"]);
+    assert_eq!(targets("c"), ["hub"]);
+    assert_eq!(targets("hub"), ["a"]);
+    let dangling = grid
+        .nodes
+        .iter()
+        .flat_map(|n| n.connections.iter())
+        .filter(|c| !names.contains(c.other.as_str()));
+    assert_eq!(dangling.count(), 0);
+    assert!(grid.marked.contains("hub"));
+
+    assert!(grid.rename_node("a", "c").is_err());
+    assert!(grid.rename_node("b", "d").is_err());
+
+    // Cut and deleted connections follow the rename, so neither points at a
+    // node which no longer exists.
+    grid.partition.insert(("a".to_string(), "hub".to_string()));
+    grid.delete_node("c")?;
+    grid.rename_node("hub", "b")?;
+    assert!(grid.partition.contains(&("a".to_string(), "b".to_string())));
+    assert_eq!(grid.restore_deleted()?, ["c"]);
+    let c = grid.floating_nodes.iter().find(|n| n.name == "c").unwrap();
+    assert!(c.index_connection(&"b".to_string()).is_some());
+    grid.commit()?;
+    grid.rename_node("a", "x")?;
+    grid.delete_node("b")?;
+    grid.rename_node("x", "a")?;
+    assert_eq!(grid.restore_deleted()?, ["b"]);
+    let a = grid.nodes.iter().find(|n| n.name == "a").unwrap();
+    assert!(a.index_connection(&"b".to_string()).is_some());
+    let b = grid.floating_nodes.iter().find(|n| n.name == "b").unwrap();
+    assert!(b.index_connection(&"a".to_string()).is_some());
+    Ok(())
+}
+
+#[test]
+fn deleted_node_is_restored_with_its_connections() -> Result<()> {
+    let mut grid = grid(&["a", "b", "c"], &[("a", "b"), ("b", "a"), ("c", "b")]);
+    let targets = |grid: &NodeGrid, name: &str| -> Vec<String> {
+        let mut nodes = grid.nodes.iter().chain(grid.floating_nodes.iter());
+        let node = nodes.find(|n| n.name == name).unwrap();
+        node.connections.iter().map(|c| c.other.clone()).collect()
+    };
+
+    grid.pick("b".to_string())?;
+    grid.delete();
+    assert!(grid.nodes.iter().all(|n| n.name != "b"));
+    assert!(targets(&grid, "a").is_empty() && targets(&grid, "c").is_empty());
+
+    assert_eq!(grid.restore_deleted()?, ["b"]);
+    grid.commit()?;
+    assert_eq!(targets(&grid, "a"), ["b"]);
+    assert_eq!(targets(&grid, "b"), ["a"]);
+    assert_eq!(targets(&grid, "c"), ["b"]);
+    assert_eq!(grid.nodes[2].location, Location::new(1, 0));
+    assert!(grid.restore_deleted().is_err());
+    Ok(())
+}
+
+#[test]
+fn fifo_algorithms_refuse_other_delivery_orders() -> Result<()> {
+    let mut grid = NodeGrid::generate_connected(4, &mut StdRng::seed_from_u64(3));
+    let mut logger = vec![];
+    grid.delivery = Some(Delivery::LifoStack);
+    let error = grid
+        .run_algorithm(SelectedAlgorithm::ChandyLamport, &mut logger)
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "ChandyLamport needs FIFO channels, it can't deliver messages last sent first."
+    );
+    assert!(grid.trace.is_empty());
+
+    grid.run_algorithm(SelectedAlgorithm::LaiYang, &mut logger)?;
+    grid.delivery = Some(Delivery::FrontFifo);
+    grid.run_algorithm(SelectedAlgorithm::ChandyLamport, &mut logger)?;
+    Ok(())
+}
+
+#[test]
+fn adjacency_list_is_imported_with_weights() -> Result<()> {
+    let grid = NodeGrid::from_adjacency("a: b 2.0, c 1.5\n\nb: a\nd: c -1\n")?;
+    let names: Vec<&str> = grid.nodes.iter().map(|n| n.name.as_str()).collect();
+    assert_eq!(names, ["a", "b", "c", "d"]);
+    assert_eq!(
+        grid.nodes[0].connections[1],
+        Connection::new("c".to_string(), 1.5)
+    );
+    assert_eq!(*grid.nodes[1].connections[0].weight, 1.0);
+    assert!(grid.nodes[2].connections.is_empty());
+    assert_eq!(grid.nodes[3].location, Location::new(1, 1));
+    assert_eq!(grid.nodes[3].id, 4);
+
+    let error = NodeGrid::from_adjacency("a: b x").unwrap_err();
+    assert_eq!(error.to_string(), "Malformed weight \"x\" on line 1.");
+    assert!(NodeGrid::from_adjacency("a b").is_err());
+    assert!(NodeGrid::from_adjacency("a: a").is_err());
+    Ok(())
+}
+
+#[test]
+fn diagonal_moves_stop_at_the_edge() -> Result<()> {
+    let mut grid = grid(&["a"], &[]);
+    grid.pick("a".to_string())?;
+    grid.move_node(1, 1);
+    assert_eq!(grid.floating_nodes[0].location, Location::new(1, 1));
+    grid.move_node(-1, 1);
+    grid.move_node(-1, 1);
+    assert_eq!(grid.floating_nodes[0].location, Location::new(0, 3));
+    grid.move_node(1, -1);
+    assert_eq!(grid.floating_nodes[0].location, Location::new(1, 2));
+    Ok(())
+}
+
+#[test]
+fn deleting_a_node_leaves_no_dangling_connections() -> Result<()> {
+    let mut grid = grid(&["a", "b", "c"], &[("a", "b"), ("b", "c"), ("c", "b")]);
+    grid.pick("c".to_string())?;
+
+    grid.delete_node("b")?;
+    assert!(grid.nodes.iter().all(|n| n.name != "b"));
+    let dangling = grid.nodes.iter().chain(grid.floating_nodes.iter());
+    assert!(
+        dangling
+            .flat_map(|n| n.connections.iter())
+            .all(|c| c.other != "b")
+    );
+    assert!(grid.delete_node("b").is_err());
+
+    assert_eq!(grid.restore_deleted()?, ["b"]);
+    // The picked node gets its connection back as well as the placed ones.
+    let c = grid.floating_nodes.iter().find(|n| n.name == "c").unwrap();
+    assert!(c.index_connection(&"b".to_string()).is_some());
+    assert!(grid.nodes[0].index_connection(&"b".to_string()).is_some());
+    Ok(())
+}
+
+#[test]
+fn only_the_node_in_the_critical_section_is_tinted() {
+    let mut grid = ring(4);
+    grid.marked.insert("n1".to_string());
+    grid.critical = Some("n2".to_string());
+    let tinted: Vec<&str> = grid
+        .nodes
+        .iter()
+        .filter(|n| grid.node_color(n, &Theme::default()) == ratatui::style::Color::Magenta)
+        .map(|n| n.name.as_str())
+        .collect();
+    assert_eq!(tinted, ["n2"]);
+
+    grid.new_node("n4".to_string()).unwrap();
+    assert!(grid.critical.is_none());
+}
+
+#[test]
+fn duplicate_names_are_handled_by_policy() -> Result<()> {
+    // The second `a` only connects to `c`.
+    let imported = || {
+        let mut grid = grid(&["a", "b", "a", "c"], &[("b", "a"), ("c", "a")]);
+        grid.nodes[0].add_connection(&Connection::new("b".to_string(), 1.0));
+        grid.nodes[2].add_connection(&Connection::new("c".to_string(), 1.0));
+        grid
+    };
+    let names =
+        |grid: &NodeGrid| -> Vec<String> { grid.nodes.iter().map(|n| n.name.clone()).collect() };
+    let targets = |grid: &NodeGrid, name: &str| -> Vec<String> {
+        let node = grid.nodes.iter().find(|n| n.name == name).unwrap();
+        node.connections.iter().map(|c| c.other.clone()).collect()
+    };
+
+    let mut grid = imported();
+    let error = grid.resolve_duplicates(Duplicates::Error, &mut vec![]);
+    assert_eq!(error.unwrap_err().to_string(), "Duplicate node names a.");
+
+    let mut logger = vec![];
+    let mut grid = imported();
+    grid.resolve_duplicates(Duplicates::Rename, &mut logger)?;
+    assert_eq!(names(&grid), ["a", "b", "a-2", "c"]);
+    assert_eq!(targets(&grid, "a-2"), ["c"]);
+    assert_eq!(logger, ["Renamed duplicate a to a-2."]);
+
+    let mut logger = vec![];
+    let mut grid = imported();
+    grid.resolve_duplicates(Duplicates::Merge, &mut logger)?;
+    assert_eq!(names(&grid), ["a", "b", "c"]);
+    assert_eq!(targets(&grid, "a"), ["b", "c"]);
+    assert_eq!(logger, ["Merged 2 duplicates of a into one node."]);
+    Ok(())
+}
+
+#[test]
+fn reachable_follows_connection_direction() {
+    let grid = grid(
+        &["a", "b", "c", "d", "e"],
+        &[("a", "b"), ("b", "c"), ("c", "d"), ("e", "d")],
+    );
+    let names = |set: BTreeSet<String>| set.into_iter().collect::<Vec<_>>();
+    assert_eq!(names(grid.reachable("b")), ["b", "c", "d"]);
+    assert_eq!(names(grid.reachable("d")), ["d"]);
+    assert_eq!(names(grid.reachable("a")), ["a", "b", "c", "d"]);
+}
+
+#[test]
+fn pinned_node_is_saved_and_marked() -> Result<()> {
+    let mut grid = grid(&["a", "b"], &[]);
+    grid.pick("a".to_string())?;
+    assert!(grid.toggle_pinned()?);
+    grid.commit()?;
+
+    let saved = serde_json::to_string(&grid)?;
+    assert_eq!(saved.matches("pinned").count(), 1);
+    let loaded: NodeGrid = serde_json::from_str(&saved)?;
+    assert!(loaded.nodes.iter().find(|n| n.name == "a").unwrap().pinned);
+
+    let area = Rect::new(0, 0, 30, 8);
+    let mut buf = Buffer::empty(area);
+    NodeGridDisplay::new(loaded).render(area, &mut buf);
+    let (x, y) = grid.place_location(&Location::new(0, 0));
+    assert_eq!(buf[(x + NODE_WIDTH / 2, y)].symbol(), "◆");
+    let (x, y) = grid.place_location(&Location::new(1, 0));
+    assert_ne!(buf[(x + NODE_WIDTH / 2, y)].symbol(), "◆");
+    Ok(())
+}
+
+#[test]
+fn aligning_leaves_pinned_nodes_in_place() -> Result<()> {
+    let mut grid = grid(&["a", "b", "c", "d"], &[("a", "b")]);
+    for (node, (x, y)) in grid.nodes.iter_mut().zip([(7, 2), (0, 0), (9, 2), (0, 2)]) {
+        node.location = Location::new(x, y);
+    }
+    grid.pick("b".to_string())?;
+    grid.toggle_pinned()?;
+    grid.commit()?;
+
+    assert_eq!(grid.align()?, 3);
+    let location = |name: &str| grid.nodes.iter().find(|n| n.name == name).unwrap().location;
+    assert_eq!(location("b"), Location::new(0, 0));
+    assert_eq!(location("a"), Location::new(1, 0));
+    assert_eq!(location("c"), Location::new(0, 1));
+    assert_eq!(location("d"), Location::new(1, 1));
+    assert_eq!(grid.align()?, 0);
+    Ok(())
+}
+
+#[test]
+fn degree_badge_shows_in_and_out() {
+    let grid = grid(&["a", "b", "c"], &[("b", "a"), ("c", "a"), ("a", "b")]);
+    assert_eq!(grid.degree("a"), (2, 1));
+    let (x, y) = grid.place_location(&Location::new(0, 0));
+    let area = Rect::new(0, 0, 40, 8);
+    let bottom_edge = |show_degree: bool| -> String {
+        let mut buf = Buffer::empty(area);
+        NodeGridDisplay::new(grid.clone())
+            .show_degree(show_degree)
+            .render(area, &mut buf);
+        (x..x + NODE_WIDTH)
+            .map(|x| buf[(x, y + NODE_HEIGHT - 1)].symbol().to_string())
+            .collect()
+    };
+    assert!(bottom_edge(true).contains("2/1"));
+    assert!(!bottom_edge(false).contains("2/1"));
+}
+
+#[test]
+fn heavy_nodes_are_drawn_larger() {
+    // A star: the hub at (1, 1) is connected both ways to four leaves.
+    let names = ["hub", "n", "e", "s", "w"];
+    let edges: Vec<(&str, &str)> = names[1..]
+        .iter()
+        .flat_map(|&leaf| [("hub", leaf), (leaf, "hub")])
+        .collect();
+    let mut star = grid(&names, &edges);
+    let spots = [(1, 1), (1, 0), (2, 1), (1, 2), (0, 1)];
+    for (node, (x, y)) in star.nodes.iter_mut().zip(spots) {
+        node.location = Location::new(x, y);
+    }
+    assert_eq!(star.incident_weight("hub"), 8.0);
+    let (weights, average) = star.incident_weights();
+    for name in names {
+        assert_eq!(weights[name], star.incident_weight(name));
+    }
+    assert_eq!(average, 16.0 / 5.0);
+
+    let area = Rect::new(0, 0, 40, 20);
+    let mut buf = Buffer::empty(area);
+    NodeGridDisplay::new(star.clone())
+        .weighted_size(true)
+        .render(area, &mut buf);
+    let drawn = |location: Location| -> (u16, u16) {
+        let (x, y) = star.place_location(&location);
+        let around = Rect::new(x - 1, y - 1, NODE_WIDTH + 2, NODE_HEIGHT + 2);
+        let width = (around.left()..around.right())
+            .filter(|&x| {
+                buf[(x, y + 1)]
+                    .style()
+                    .add_modifier
+                    .contains(ratatui::style::Modifier::REVERSED)
+            })
+            .count();
+        let height = (around.top()..around.bottom())
+            .filter(|&y| buf[(x + 1, y)] != ratatui::buffer::Cell::EMPTY)
+            .count();
+        (width as u16, height as u16)
+    };
+    assert_eq!(
+        drawn(Location::new(1, 1)),
+        (NODE_WIDTH + 2, NODE_HEIGHT + 2)
+    );
+    assert_eq!(drawn(Location::new(1, 0)), (NODE_WIDTH, NODE_HEIGHT));
+}
+
+#[test]
+fn lattice_connects_orthogonal_neighbours() -> Result<()> {
+    let lattice = NodeGrid::make_lattice(2, 3)?;
+    assert_eq!(lattice.nodes.len(), 6);
+    assert_eq!(lattice.channel_count(), 2 * 7);
+    let corner = lattice
+        .nodes
+        .iter()
+        .find(|n| n.location == Location::new(0, 0));
+    assert_eq!(corner.unwrap().connections.len(), 2);
+    let edge = lattice
+        .nodes
+        .iter()
+        .find(|n| n.location == Location::new(1, 1));
+    assert_eq!(edge.unwrap().connections.len(), 3);
+
+    // Only the middle of a 3x3 lattice is interior.
+    let lattice = NodeGrid::make_lattice(3, 3)?;
+    let neighbours = lattice.undirected_neighbours();
+    let middle = lattice
+        .nodes
+        .iter()
+        .find(|n| n.location == Location::new(1, 1));
+    assert_eq!(neighbours[middle.unwrap().name.as_str()].len(), 4);
+
+    assert!(NodeGrid::make_lattice(0, 3).is_err());
+    assert!(NodeGrid::make_lattice(usize::MAX, 2).is_err());
+    assert!(NodeGrid::make_lattice(1, MAX_GENERATED_NODES + 1).is_err());
+    assert!(NodeGrid::make_lattice(1, MAX_GENERATED_NODES).is_ok());
+    Ok(())
+}
+
+#[test]
+fn preconditions_catch_unfit_grids() {
+    let empty = NodeGrid::default();
+    let split = grid(&["a", "b", "c"], &[("a", "b"), ("b", "a")]);
+    let path = grid(&["a", "b", "c"], &[("a", "b"), ("b", "c")]);
+    let mut both_ways = ring(4);
+    both_ways.add_reverse_connections();
+    for algorithm in SelectedAlgorithm::iter() {
+        let error = algorithm.preconditions(&empty).unwrap_err().to_string();
+        assert!(error.contains("No nodes in grid."), "{error}");
+        assert!(algorithm.preconditions(&both_ways).is_ok());
+    }
+    assert_eq!(
+        SelectedAlgorithm::Franklin
+            .preconditions(&ring(4))
+            .unwrap_err()
+            .to_string(),
+        "Cannot run Franklin: Ring is not connected in both directions."
+    );
+    let mut logger = vec![];
+    assert!(ring(4).franklin(&mut logger).is_err());
+    assert_eq!(
+        logger.last().unwrap(),
+        "Ring is not connected in both directions."
+    );
+
+    for algorithm in [SelectedAlgorithm::ChandyLamport, SelectedAlgorithm::LaiYang] {
+        let error = algorithm.preconditions(&split).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Cannot run {algorithm}: No outgoing connection from c. Grid is split into 2 parts."
+            )
+        );
+        let error = algorithm.preconditions(&path).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("Cannot run {algorithm}: No outgoing connection from c.")
+        );
+        assert!(algorithm.preconditions(&grid(&["a"], &[])).is_err());
+        // Run anyway, the dead end sends no basic messages.
+        let mut seeded = path.clone();
+        for seed in 0..10 {
+            let _ = seeded.run_algorithm_seeded(algorithm, seed, &mut vec![]);
+        }
+    }
+
+    let error = SelectedAlgorithm::ChangRoberts
+        .preconditions(&path)
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Cannot run ChangRoberts: No outgoing connection from c. Graph is not a ring."
+    );
+
+    assert!(SelectedAlgorithm::Luby.preconditions(&split).is_ok());
+}
+
+#[test]
+fn custom_theme_colors_nodes() {
+    let grid = grid(&["a", "b"], &[]);
+    let theme = Theme {
+        node: ratatui::style::Color::White,
+        ..Default::default()
+    };
+    let mut buf = Buffer::empty(Rect::new(0, 0, 30, 9));
+    grid.render_nodes(&mut buf, false, false, false, NodeBorder::Solid, &theme);
+    let (x, y) = (NODE_H_SPACING + 1, NODE_V_SPACING + 1);
+    assert_eq!(buf[(x, y)].fg, ratatui::style::Color::White);
+
+    let mut buf = Buffer::empty(Rect::new(0, 0, 30, 9));
+    grid.render_nodes(
+        &mut buf,
+        false,
+        false,
+        false,
+        NodeBorder::Solid,
+        &Theme::default(),
+    );
+    assert_eq!(buf[(x, y)].fg, ratatui::style::Color::Green);
+}
+
+#[test]
+fn make_ring_fixes_lint_for_chang_roberts() -> Result<()> {
+    let mut grid = grid(&["a", "b", "c", "d"], &[("a", "b"), ("b", "c"), ("c", "b")]);
+    let issues = grid.lint(SelectedAlgorithm::ChangRoberts);
+    assert_eq!(
+        issues
+            .iter()
+            .map(|i| i.problem.as_str())
+            .collect::<Vec<_>>(),
+        ["No outgoing connection from d.", "Graph is not a ring."]
+    );
+    assert!(issues.iter().all(|i| i.fix == Some(Fix::MakeRing)));
+    assert!(
+        grid.lint_report(SelectedAlgorithm::ChangRoberts)
+            .contains(&"  <r> connect the nodes into a ring, in their current order".to_string())
+    );
+
+    grid.nodes[0].connections[0] = Connection::new("b".to_string(), 2.5);
+    assert_eq!(grid.ring_fix_drops(), 1);
+    assert_eq!(
+        grid.apply_fix(Fix::MakeRing)?,
+        "Connected 4 nodes into a ring, dropping 1 other connections."
+    );
+    assert!(grid.lint(SelectedAlgorithm::ChangRoberts).is_empty());
+    assert!(SelectedAlgorithm::ChangRoberts.preconditions(&grid).is_ok());
+    assert_eq!(*grid.nodes[0].connections[0].weight, 2.5);
+
+    let mut single = self::grid(&["a"], &[]);
+    assert!(single.apply_fix(Fix::MakeRing).is_err());
+    assert!(single.nodes[0].connections.is_empty());
+    Ok(())
+}
+
+#[test]
+fn lint_fixes_snapshot_inputs() -> Result<()> {
+    let mut grid = grid(&["a", "b", "c", "d"], &[("a", "b"), ("b", "c"), ("a", "x")]);
+    let problems = |grid: &NodeGrid| -> Vec<String> {
+        grid.lint(SelectedAlgorithm::ChandyLamport)
+            .into_iter()
+            .map(|i| i.problem)
+            .collect()
+    };
+    assert_eq!(
+        problems(&grid),
+        [
+            "No outgoing connection from c, d.",
+            "Grid is split into 2 parts.",
+            "Connections to missing nodes a->x."
+        ]
+    );
+    grid.apply_fix(Fix::PruneDangling)?;
+    grid.apply_fix(Fix::JoinComponents)?;
+    assert_eq!(
+        problems(&grid),
+        [
+            "No outgoing connection from c.",
+            "Markers from b, c can't reach every node."
+        ]
+    );
+    grid.apply_fix(Fix::MakeUndirected)?;
+    assert!(problems(&grid).is_empty());
+    Ok(())
+}
+
+#[test]
+fn seeded_runs_repeat_exactly() -> Result<()> {
+    let connected = NodeGrid::generate_connected(9, &mut StdRng::seed_from_u64(1));
+    let ring = NodeGrid::generate_bidirectional_ring(7, &mut StdRng::seed_from_u64(1));
+    for algorithm in SelectedAlgorithm::iter() {
+        let grid = match algorithm.is_election() {
+            true => &ring,
+            false => &connected,
+        };
+        let run = |seed| -> Result<Vec<String>> {
+            let mut logger = vec![];
+            let mut grid = grid.clone();
+            grid.run_algorithm_seeded(algorithm, seed, &mut logger)?;
+            assert!(grid.seed.is_none());
+            Ok(logger)
+        };
+        let first = run(7)?;
+        assert_eq!(first[0], "Random choices are seeded with 7.");
+        assert_eq!(first, run(7)?, "{algorithm} differs between runs");
+    }
+
+    // The basic messages of a snapshot follow the seed.
+    let runs: BTreeSet<Vec<String>> = (0..5)
+        .map(|seed| {
+            let mut logger = vec![];
+            let mut grid = connected.clone();
+            grid.run_algorithm_seeded(SelectedAlgorithm::LaiYang, seed, &mut logger)
+                .map(|_| logger)
+        })
+        .collect::<Result<_>>()?;
+    assert!(runs.len() > 1);
+    Ok(())
+}
+
+#[test]
+fn floating_nodes_get_distinct_ids() -> Result<()> {
+    let mut grid = grid(&["a"], &[]);
+    grid.new_node("b".to_string())?;
+    grid.new_node("c".to_string())?;
+    let ids: BTreeSet<usize> = grid
+        .nodes
+        .iter()
+        .chain(grid.floating_nodes.iter())
+        .map(|n| n.id)
+        .collect();
+    assert_eq!(ids.len(), 3);
+    Ok(())
+}
+
+#[test]
+fn floating_node_names_are_unique() -> Result<()> {
+    let mut grid = NodeGrid::default();
+    grid.new_node("a".to_string())?;
+    let error = grid.new_node("a".to_string()).unwrap_err();
+    assert_eq!(error.to_string(), "Name not unique.");
+    assert_eq!(grid.floating_nodes.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn disconnect_removes_one_connection() -> Result<()> {
+    let mut grid = grid(
+        &["a", "b", "c"],
+        &[("a", "b"), ("b", "a"), ("a", "c"), ("c", "a")],
+    );
+    grid.pick("a".to_string())?;
+    assert_eq!(
+        grid.disconnect("b")?,
+        [EdgeChange::Removed {
+            from: "a".to_string(),
+            to: "b".to_string(),
+            weight: DEFAULT_WEIGHT
+        }]
+    );
+    assert!(grid.disconnect("b").is_err());
+    assert_eq!(grid.disconnect_undirected("c")?.len(), 2);
+    assert!(grid.floating_nodes[0].connections.is_empty());
+    let b = grid.nodes.iter().find(|n| n.name == "b").unwrap();
+    assert_eq!(b.connections.len(), 1);
+    assert!(
+        grid.nodes
+            .iter()
+            .all(|n| n.name != "c" || n.connections.is_empty())
+    );
+    Ok(())
+}
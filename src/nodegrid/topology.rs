@@ -0,0 +1,237 @@
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+use crate::{node::connection::Connection, nodegrid::NodeGrid};
+
+/// Why a grid is not a tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, displaydoc::Display)]
+pub(crate) enum NotATree {
+    /// the grid is empty
+    Empty,
+    /// a cycle is present
+    Cycle,
+    /// the grid is disconnected
+    Disconnected,
+}
+
+impl NodeGrid {
+    /// Neighbours of every node when connections are read as undirected edges.
+    /// Connections to nodes which don't exist are ignored.
+    pub(crate) fn undirected_neighbours(&self) -> HashMap<&str, BTreeSet<&str>> {
+        let mut neighbours: HashMap<&str, BTreeSet<&str>> = self
+            .nodes
+            .iter()
+            .map(|n| (n.name.as_str(), BTreeSet::new()))
+            .collect();
+        for node in self.nodes.iter() {
+            for connection in node.connections.iter() {
+                let other = connection.other.as_str();
+                if other == node.name || !neighbours.contains_key(other) {
+                    continue;
+                }
+                neighbours
+                    .get_mut(node.name.as_str())
+                    .unwrap()
+                    .insert(other);
+                neighbours
+                    .get_mut(other)
+                    .unwrap()
+                    .insert(node.name.as_str());
+            }
+        }
+        neighbours
+    }
+
+    /// The connected components of the undirected view of the grid.
+    pub(crate) fn components(&self) -> Vec<BTreeSet<&str>> {
+        let neighbours = self.undirected_neighbours();
+        let mut seen = HashSet::new();
+        let mut components = vec![];
+        for node in self.nodes.iter() {
+            if seen.contains(node.name.as_str()) {
+                continue;
+            }
+            let mut component = BTreeSet::new();
+            let mut stack = vec![node.name.as_str()];
+            while let Some(current) = stack.pop() {
+                if !seen.insert(current) {
+                    continue;
+                }
+                component.insert(current);
+                stack.extend(neighbours[current].iter().filter(|n| !seen.contains(*n)));
+            }
+            components.push(component);
+        }
+        components
+    }
+
+    /// Edges of a breadth first spanning tree from `root`, as (parent, child)
+    /// pairs. Only the nodes reachable over outgoing connections are spanned.
+    pub(crate) fn spanning_tree(&self, root: &str) -> Vec<(String, String)> {
+        let mut edges = vec![];
+        if !self.node_exists(root) {
+            return edges;
+        }
+        let mut seen = HashSet::from([root]);
+        let mut queue = VecDeque::from([root]);
+        while let Some(current) = queue.pop_front() {
+            let node = self.nodes.iter().find(|n| n.name == current).unwrap();
+            for connection in node.connections.iter() {
+                let other = connection.other.as_str();
+                if self.node_exists(other) && seen.insert(other) {
+                    edges.push((current.to_string(), other.to_string()));
+                    queue.push_back(other);
+                }
+            }
+        }
+        edges
+    }
+
+    /// Names of the nodes reachable from `root` over outgoing connections,
+    /// including `root` itself. Floating nodes are followed as well.
+    pub(crate) fn reachable(&self, root: &str) -> BTreeSet<String> {
+        let mut reachable = BTreeSet::new();
+        let mut stack = vec![root];
+        while let Some(current) = stack.pop() {
+            let Some(node) = self
+                .nodes
+                .iter()
+                .chain(self.floating_nodes.iter())
+                .find(|n| n.name == current)
+            else {
+                continue;
+            };
+            if reachable.insert(current.to_string()) {
+                stack.extend(node.connections.iter().map(|c| c.other.as_str()));
+            }
+        }
+        reachable
+    }
+
+    /// Checks whether the undirected view of the grid is connected and acyclic.
+    pub(crate) fn tree_check(&self) -> Result<(), NotATree> {
+        if self.nodes.is_empty() {
+            return Err(NotATree::Empty);
+        }
+        let neighbours = self.undirected_neighbours();
+        let edges = neighbours.values().map(BTreeSet::len).sum::<usize>() / 2;
+        let components = self.components().len();
+        if edges > self.nodes.len() - components {
+            Err(NotATree::Cycle)
+        } else if components > 1 {
+            Err(NotATree::Disconnected)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn is_tree(&self) -> bool {
+        self.tree_check().is_ok()
+    }
+
+    /// Whether the grid is a single ring through every node, see
+    /// `ring_successors`.
+    pub(crate) fn is_ring(&self) -> bool {
+        self.ring_successors().is_some()
+    }
+
+    /// Whether the grid is a ring with every connection also present in the
+    /// other direction, so messages can travel both ways around it.
+    pub(crate) fn is_bidirectional_ring(&self) -> bool {
+        self.ring_successors().is_some_and(|successors| {
+            successors.iter().all(|(node, successor)| {
+                node == successor
+                    || self
+                        .nodes
+                        .iter()
+                        .find(|n| &n.name == successor)
+                        .is_some_and(|n| n.index_connection(node).is_some())
+            })
+        })
+    }
+
+    /// Adds the reverse of every connection which doesn't have one yet, with
+    /// the same weight. Returns how many were added.
+    pub(crate) fn add_reverse_connections(&mut self) -> usize {
+        let reverses: Vec<(String, Connection)> = self
+            .nodes
+            .iter()
+            .flat_map(|n| {
+                n.connections
+                    .iter()
+                    .map(|c| (c.other.clone(), Connection::new(n.name.clone(), *c.weight)))
+            })
+            .collect();
+        let mut added = 0;
+        for (name, reverse) in reverses {
+            let Some(node) = self.nodes.iter_mut().find(|n| n.name == name) else {
+                continue;
+            };
+            if node.index_connection(&reverse.other).is_none() {
+                node.add_connection(&reverse);
+                added += 1;
+            }
+        }
+        added
+    }
+
+    /// The successor of every node when the grid is read as a ring, or `None` if
+    /// it isn't one. Both unidirectional rings and rings with connections in both
+    /// directions are accepted; for the latter the direction of the first node's
+    /// first connection is followed. A lone node without connections is a ring
+    /// on its own, and its own successor.
+    pub(crate) fn ring_successors(&self) -> Option<HashMap<String, String>> {
+        let start = self.nodes.first()?;
+        let mut successors = HashMap::new();
+        if let [node] = &self.nodes[..]
+            && node.connections.is_empty()
+        {
+            successors.insert(node.name.clone(), node.name.clone());
+            return Some(successors);
+        }
+
+        if self.nodes.iter().all(|n| n.connections.len() == 1) {
+            for node in self.nodes.iter() {
+                successors.insert(node.name.clone(), node.connections[0].other.clone());
+            }
+        } else {
+            let mut previous = start.name.clone();
+            let mut current = start.connections.first()?.other.clone();
+            successors.insert(previous.clone(), current.clone());
+            for _ in 0..self.nodes.len() {
+                if current == start.name {
+                    break;
+                }
+                let node = self.nodes.iter().find(|n| n.name == current)?;
+                if node.connections.len() != 2 || node.index_connection(&previous).is_none() {
+                    return None;
+                }
+                let next = node.connections.iter().find(|c| c.other != previous)?;
+                successors.insert(current.clone(), next.other.clone());
+                previous = current;
+                current = next.other.clone();
+            }
+            if start.connections.len() != 2 || start.index_connection(&previous).is_none() {
+                return None;
+            }
+        }
+
+        self.covers_ring(&successors).then_some(successors)
+    }
+
+    /// Whether following `successors` from any node visits every node exactly once
+    /// before returning to it.
+    fn covers_ring(&self, successors: &HashMap<String, String>) -> bool {
+        let Some(start) = self.nodes.first() else {
+            return false;
+        };
+        let mut visited = HashSet::new();
+        let mut current = &start.name;
+        while visited.insert(current.clone()) {
+            match successors.get(current) {
+                Some(next) => current = next,
+                None => return false,
+            }
+        }
+        current == &start.name && visited.len() == self.nodes.len()
+    }
+}
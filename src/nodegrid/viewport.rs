@@ -0,0 +1,167 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Position, Rect},
+    style::Style,
+};
+
+use crate::{
+    COMPACT_NODE_WIDTH, COMPACT_SPACING, NODE_H_SPACING, NODE_HEIGHT, NODE_V_SPACING, NODE_WIDTH,
+    location::Location,
+    nodegrid::{NodeGrid, NodeGridDisplay},
+};
+
+impl NodeGrid {
+    /// The bottom right most location used by any node.
+    pub(crate) fn extent(&self) -> Location {
+        self.nodes.iter().chain(self.floating_nodes.iter()).fold(
+            Location::default(),
+            |extent, node| Location {
+                x: extent.x.max(node.location.x),
+                y: extent.y.max(node.location.y),
+            },
+        )
+    }
+
+    /// Area taken by a node at `location`, before scrolling.
+    fn cell(&self, location: &Location, compact: bool) -> Rect {
+        if compact {
+            let (x, y) = self.place_compact(location);
+            Rect::new(x, y, COMPACT_NODE_WIDTH, 1)
+        } else {
+            let (x, y) = self.place_location(location);
+            Rect::new(x, y, NODE_WIDTH, NODE_HEIGHT)
+        }
+    }
+
+    /// Whether the grid only fits in `area` when drawn compact. Grids which
+    /// don't fit either way are drawn compact as well.
+    pub(crate) fn needs_compact(&self, area: Rect) -> bool {
+        let (width, height) = self.drawn_size(false);
+        width > area.width || height > area.height
+    }
+
+    /// Size of the area needed to draw every node, including the spacing after
+    /// the last ones.
+    fn drawn_size(&self, compact: bool) -> (u16, u16) {
+        let last = self.cell(&self.extent(), compact);
+        match compact {
+            true => (
+                last.right() + COMPACT_SPACING,
+                last.bottom() + COMPACT_SPACING,
+            ),
+            false => (
+                last.right() + NODE_H_SPACING,
+                last.bottom() + NODE_V_SPACING,
+            ),
+        }
+    }
+}
+
+impl NodeGridDisplay<'_> {
+    /// Scrolls so the cell at `location` is in the middle of `area`, whether or
+    /// not a node is placed there. Locations past the last node are clamped.
+    /// Returns the location which was centered.
+    pub(crate) fn center_on(&mut self, location: &Location, area: Rect, compact: bool) -> Location {
+        let extent = self.grid.extent();
+        let location = Location::new(location.x.min(extent.x), location.y.min(extent.y));
+        let cell = self.grid.cell(&location, compact);
+        self.offset = (
+            (cell.x + cell.width / 2).saturating_sub(area.width / 2),
+            (cell.y + cell.height / 2).saturating_sub(area.height / 2),
+        );
+        location
+    }
+
+    /// Position on the canvas of the grid shown at `position`, when the grid is
+    /// drawn in `area`.
+    fn source(&self, position: Position, area: Rect) -> Option<Position> {
+        if !area.contains(position) {
+            return None;
+        }
+        Some(Position::new(
+            position.x - area.x + self.offset.0,
+            position.y - area.y + self.offset.1,
+        ))
+    }
+
+    /// Name of the placed node drawn at `position`, when the grid is drawn in
+    /// `area`.
+    pub(crate) fn node_at(&self, position: Position, area: Rect, compact: bool) -> Option<String> {
+        let source = self.source(position, area)?;
+        self.grid
+            .nodes
+            .iter()
+            .find(|n| self.grid.cell(&n.location, compact).contains(source))
+            .map(|n| n.name.clone())
+    }
+
+    /// Location of the cell at `position`, the inverse of placing a node. The
+    /// spacing right of and below a node counts as part of its cell.
+    pub(crate) fn location_at(
+        &self,
+        position: Position,
+        area: Rect,
+        compact: bool,
+    ) -> Option<Location> {
+        let source = self.source(position, area)?;
+        let (h_spacing, v_spacing, width, height) = match compact {
+            true => (COMPACT_SPACING, COMPACT_SPACING, COMPACT_NODE_WIDTH, 1),
+            false => (NODE_H_SPACING, NODE_V_SPACING, NODE_WIDTH, NODE_HEIGHT),
+        };
+        Some(Location::new(
+            source.x.saturating_sub(h_spacing) / (h_spacing + width),
+            source.y.saturating_sub(v_spacing) / (v_spacing + height),
+        ))
+    }
+
+    /// Draws the grid on a canvas of its own, and copies the part visible
+    /// through `area` after scrolling by `offset`.
+    pub(crate) fn render_scrolled(&self, area: Rect, buf: &mut Buffer) {
+        let (width, height) = self.grid.drawn_size(self.compact);
+        let canvas_area = Rect::new(
+            0,
+            0,
+            width.max(self.offset.0 + area.right()),
+            height.max(self.offset.1 + area.bottom()),
+        );
+        let mut canvas = Buffer::empty(canvas_area);
+        canvas.set_style(canvas_area, Style::default().bg(self.theme.background));
+        if self.compact {
+            if !self.hide_connections {
+                self.grid
+                    .render_compact_connections(&mut canvas, &self.theme);
+            }
+            self.grid.render_compact_nodes(&mut canvas, &self.theme);
+        } else {
+            if !self.hide_connections {
+                self.grid.render_connections(
+                    &mut canvas,
+                    self.channels,
+                    self.long_edge_labels,
+                    self.message_counts,
+                    self.edge_weights,
+                    &self.theme,
+                );
+            }
+            self.grid.render_nodes(
+                &mut canvas,
+                self.show_degree,
+                self.show_clocks,
+                self.weighted_size,
+                self.node_border,
+                &self.theme,
+            );
+            self.grid.render_floating_nodes(
+                &mut canvas,
+                self.show_degree,
+                self.weighted_size,
+                self.node_border,
+                &self.theme,
+            );
+        }
+        for position in area.positions() {
+            let source = (position.x + self.offset.0, position.y + self.offset.1);
+            buf[position] = canvas[source].clone();
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{App, NodeGrid, SelectedAlgorithm, node::connection::Connection};
+
+/// A high-level action of the user, as stored in a recorded script.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Action {
+    NewNode(String),
+    Pick(String),
+    Move {
+        x: i8,
+        y: i8,
+    },
+    Commit,
+    Delete,
+    Connect {
+        connection: Connection,
+        undirected: bool,
+    },
+    /// Remove the connections of the selection to `other`.
+    Disconnect {
+        other: String,
+        undirected: bool,
+    },
+    Run(SelectedAlgorithm),
+    /// Rename the selected node.
+    Rename(String),
+}
+
+impl App<'_> {
+    /// Notes that `action` was performed: it is appended to the script if
+    /// recording, and changes to the grid count towards the next backup.
+    pub(crate) fn record(&mut self, action: Action) {
+        if !matches!(action, Action::Run(_)) {
+            self.autosave_tick();
+        }
+        if let Some(recording) = self.recording.as_mut() {
+            recording.push(action);
+        }
+    }
+
+    pub(crate) fn start_recording(&mut self) {
+        self.recording = Some(vec![]);
+        self.log(&mut vec!["Started recording actions.".to_string()]);
+    }
+
+    /// Stops recording, keeping the recorded actions around for export.
+    pub(crate) fn stop_recording(&mut self) {
+        self.script = self.recording.take().unwrap_or_default();
+        self.log(&mut vec![format!(
+            "Stopped recording after {} actions.",
+            self.script.len()
+        )]);
+    }
+
+    /// Applies `actions` in order, stopping at the first one that fails.
+    pub(crate) fn replay(&mut self, actions: &[Action]) -> Result<()> {
+        for action in actions {
+            match action {
+                Action::NewNode(name) => self.add_node(name.clone())?,
+                Action::Pick(name) => self.pick_node(name.clone())?,
+                Action::Move { x, y } => self.move_node(*x, *y),
+                Action::Commit => self.commit_selection()?,
+                Action::Delete => self.delete_selection(),
+                Action::Connect {
+                    connection,
+                    undirected,
+                } => self.connect_selection(connection, *undirected)?,
+                Action::Disconnect { other, undirected } => {
+                    self.disconnect_selection(other, *undirected)?
+                }
+                Action::Rename(name) => self.rename_selection(name)?,
+                Action::Run(algorithm) => {
+                    // A failed run was recorded as well, and is logged again.
+                    let _ = self.run_algorithm(*algorithm);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn export_script(&self, path: &PathBuf) -> Result<()> {
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(path)?;
+        let mut writer = io::BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, &self.script)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Replays the script at `path` on an empty grid.
+    pub(crate) fn replay_script(&mut self, path: &PathBuf) -> Result<()> {
+        let file = fs::OpenOptions::new().read(true).open(path)?;
+        let reader = io::BufReader::new(file);
+        let actions: Vec<Action> = serde_json::from_reader(reader)?;
+        self.node_display.grid = NodeGrid::default();
+        self.replay(&actions)
+    }
+}
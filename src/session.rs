@@ -0,0 +1,179 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    App, Delivery, Duplicates, NodeBorder, NodeGrid, SIDEBAR_DEFAULT_WIDTH, SIDEBAR_MAX_WIDTH,
+    SIDEBAR_MIN_WIDTH, SelectedAlgorithm, SidebarState, Verbosity,
+};
+
+/// Everything needed to resume work where it was left off: the grid, the log,
+/// the algorithms and the view settings. Fields missing from a saved session
+/// keep their default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Session {
+    grid: NodeGrid,
+    log: Vec<String>,
+    /// Algorithm highlighted in the selector.
+    selected: Option<SelectedAlgorithm>,
+    last_algorithm: Option<SelectedAlgorithm>,
+    /// Seed of the last seeded command.
+    seed: Option<u64>,
+    verbosity: Verbosity,
+    frozen: bool,
+    delivery: Option<Delivery>,
+    message_limit: Option<usize>,
+    traffic: Option<usize>,
+    run_seed: Option<u64>,
+    duplicates: Duplicates,
+    sidebar_shown: bool,
+    sidebar_width: u16,
+    show_channels: bool,
+    long_edge_labels: bool,
+    message_counts: bool,
+    edge_weights: bool,
+    hide_connections: bool,
+    compact: bool,
+    show_degree: bool,
+    show_clocks: bool,
+    weighted_size: bool,
+    node_border: NodeBorder,
+    offset: (u16, u16),
+}
+
+/// A grid as saved to a file, with the log of the investigation on it.
+/// Files without a log, like those saved before it was kept, load as well.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct GridFile {
+    #[serde(flatten)]
+    pub(crate) grid: NodeGrid,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) log: Vec<String>,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session {
+            grid: NodeGrid::default(),
+            log: vec![],
+            selected: None,
+            last_algorithm: None,
+            seed: None,
+            verbosity: Verbosity::Full,
+            frozen: false,
+            delivery: None,
+            message_limit: None,
+            traffic: None,
+            run_seed: None,
+            duplicates: Duplicates::default(),
+            sidebar_shown: false,
+            sidebar_width: SIDEBAR_DEFAULT_WIDTH,
+            show_channels: false,
+            long_edge_labels: false,
+            message_counts: false,
+            edge_weights: false,
+            hide_connections: false,
+            compact: false,
+            show_degree: false,
+            show_clocks: false,
+            weighted_size: false,
+            node_border: NodeBorder::default(),
+            offset: (0, 0),
+        }
+    }
+}
+
+impl App<'_> {
+    fn session(&self) -> Session {
+        Session {
+            grid: self.node_display.grid.clone(),
+            log: self.sidebar.log.clone(),
+            selected: SelectedAlgorithm::from_repr(self.sidebar.selector_scroll_state),
+            last_algorithm: self.last_algorithm,
+            seed: self.seed,
+            verbosity: self.verbosity,
+            frozen: self.frozen,
+            delivery: self.delivery,
+            message_limit: self.message_limit,
+            traffic: self.traffic,
+            run_seed: self.run_seed,
+            duplicates: self.duplicates,
+            sidebar_shown: self.sidebar_state.is_shown(),
+            sidebar_width: self.sidebar.width,
+            show_channels: self.show_channels,
+            long_edge_labels: self.long_edge_labels,
+            message_counts: self.message_counts,
+            edge_weights: self.edge_weights,
+            hide_connections: self.hide_connections,
+            compact: self.compact,
+            show_degree: self.show_degree,
+            show_clocks: self.show_clocks,
+            weighted_size: self.weighted_size,
+            node_border: self.node_border,
+            offset: self.node_display.offset,
+        }
+    }
+
+    pub(crate) fn resume(&mut self, mut session: Session) {
+        let mut dropped = vec![];
+        session.grid.drop_self_loops(&mut dropped);
+        self.node_display.grid = session.grid;
+        self.node_display.offset = session.offset;
+        self.sidebar.log = session.log;
+        self.sidebar.log.append(&mut dropped);
+        self.sidebar.selector_scroll_state = session.selected.map_or(0, |a| a as usize);
+        self.last_algorithm = session.last_algorithm;
+        self.last_run_size = self.node_display.grid.run_size();
+        self.seed = session.seed;
+        self.verbosity = session.verbosity;
+        self.frozen = session.frozen;
+        self.delivery = session.delivery;
+        self.message_limit = session.message_limit;
+        self.traffic = session.traffic;
+        self.run_seed = session.run_seed;
+        self.duplicates = session.duplicates;
+        self.sidebar_state = match session.sidebar_shown {
+            true => SidebarState::Shown,
+            false => SidebarState::Hidden,
+        };
+        self.sidebar.width = session
+            .sidebar_width
+            .clamp(SIDEBAR_MIN_WIDTH, SIDEBAR_MAX_WIDTH);
+        self.show_channels = session.show_channels;
+        self.long_edge_labels = session.long_edge_labels;
+        self.message_counts = session.message_counts;
+        self.edge_weights = session.edge_weights;
+        self.hide_connections = session.hide_connections;
+        self.compact = session.compact;
+        self.show_degree = session.show_degree;
+        self.show_clocks = session.show_clocks;
+        self.weighted_size = session.weighted_size;
+        self.node_border = session.node_border;
+    }
+
+    pub(crate) fn save_session(&self, path: &PathBuf) -> Result<()> {
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(path)?;
+        let mut writer = io::BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, &self.session())?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub(crate) fn load_session(&mut self, path: &PathBuf) -> Result<()> {
+        let file = fs::OpenOptions::new().read(true).open(path)?;
+        let reader = io::BufReader::new(file);
+        let session: Session = serde_json::from_reader(reader)?;
+        self.resume(session);
+        Ok(())
+    }
+}
@@ -61,3 +61,1115 @@ fn handle_key_event() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn popup_stays_within_small_area() -> Result<()> {
+    let area = Rect::new(0, 0, 12, 5);
+    for popup in [
+        popup_area(area, 60, 20),
+        popup_area_small(area, 60, PopupSize::Small as u16),
+    ] {
+        assert_eq!(popup.intersection(area), popup);
+    }
+
+    let large = popup_area(Rect::new(0, 0, 200, 30), 60, 20);
+    assert!(large.height >= POPUP_MIN_HEIGHT);
+    assert!(large.width <= POPUP_MAX_LENGTH);
+
+    let mut app = App::default();
+    app.open_popup(PopupState::Large);
+    let mut buf = Buffer::empty(area);
+    (&app).render(area, &mut buf);
+
+    Ok(())
+}
+
+#[test]
+fn edit_popup_needs_a_single_picked_node() -> Result<()> {
+    let mut app = App::default();
+    app.node_display.grid = connected_pair();
+    app.open_popup(PopupState::Edit);
+    assert_eq!(app.state, AppState::Default);
+
+    app.node_display.grid.pick("a".to_string())?;
+    app.open_popup(PopupState::Edit);
+    assert_eq!(app.state, AppState::Popup(PopupState::Edit));
+    Ok(())
+}
+
+#[test]
+fn weights_popup_updates_the_edited_weights() -> Result<()> {
+    let mut app = App::default();
+    app.node_display.grid = connected_pair();
+    app.handle_selection_key_event(KeyCode::Char('W').into())?;
+    assert_eq!(app.state, AppState::Default);
+
+    app.node_display.grid.pick("a".to_string())?;
+    app.handle_selection_key_event(KeyCode::Char('W').into())?;
+    assert_eq!(app.state, AppState::Popup(PopupState::Weights));
+    assert_eq!(app.textarea.lines(), ["b 1"]);
+
+    for bad in ["b NaN", "b", "c 2.0", "b 2.5 junk"] {
+        assert!(app.apply_weights(&[bad.to_string()]).is_err());
+    }
+    app.apply_weights(&["b 2.5".to_string()])?;
+    let node = &app.node_display.grid.floating_nodes[0];
+    assert_eq!(*node.connections[0].weight, 2.5);
+    assert_eq!(
+        app.sidebar.log.last().unwrap(),
+        "Updated connection a->b weight 1 -> 2.5."
+    );
+    Ok(())
+}
+
+#[test]
+fn connect_input_names_the_part_which_is_wrong() -> Result<()> {
+    assert_eq!(
+        parse_connect_input("1.0 n")?,
+        Connection::new("n".to_string(), 1.0)
+    );
+    for (input, error) in [
+        ("n", "Weight must be a number, got \"n\"."),
+        ("abc n", "Weight must be a number, got \"abc\"."),
+        ("NaN n", "Weight must be a number, got \"NaN\"."),
+        ("2.5", "Destination name required."),
+        ("", "Weight must be a number, got \"\"."),
+    ] {
+        assert_eq!(parse_connect_input(input).unwrap_err().to_string(), error);
+    }
+
+    let mut app = App::default();
+    app.node_display.grid = connected_pair();
+    app.node_display.grid.pick("a".to_string())?;
+    let to_self = Connection::new("a".to_string(), 2.0);
+    for undirected in [false, true] {
+        let error = app.connect_selection(&to_self, undirected).unwrap_err();
+        assert_eq!(error.to_string(), "Can't connect a to itself.");
+    }
+    let node = &app.node_display.grid.floating_nodes[0];
+    assert_eq!(node.connections, [Connection::new("b".to_string(), 1.0)]);
+    Ok(())
+}
+
+#[test]
+fn log_wraps_at_display_width() {
+    let mut app = App::default();
+    app.sidebar.log = vec!["ab日本語\\ncd".to_string()];
+    let lines: Vec<String> = app
+        .sidebar
+        .create_wrapped_lines(5)
+        .iter()
+        .map(|l| l.to_string())
+        .collect();
+    assert_eq!(lines, ["ab日", "本語", "cd"]);
+}
+
+/// Two neighbouring nodes `a` and `b` with connections in both directions.
+fn connected_pair() -> NodeGrid {
+    let mut grid = NodeGrid::default();
+    for (id, (name, other)) in [("a", "b"), ("b", "a")].into_iter().enumerate() {
+        grid.nodes.push(node::Node {
+            name: name.to_string(),
+            id: id + 1,
+            connections: vec![Connection::new(other.to_string(), 1.0)],
+            location: Location::new(id as u16, 0),
+            ..Default::default()
+        });
+    }
+    grid
+}
+
+#[test]
+fn dragging_between_nodes_prefills_connection() -> Result<()> {
+    let mut app = App {
+        screen: Rect::new(0, 0, 60, 20),
+        ..Default::default()
+    };
+    app.node_display.grid = connected_pair();
+    let mouse = |kind, node: u16| MouseEvent {
+        kind,
+        column: NODE_H_SPACING + node * (NODE_H_SPACING + NODE_WIDTH) + 1,
+        row: NODE_V_SPACING + 1,
+        modifiers: KeyModifiers::NONE,
+    };
+
+    app.handle_mouse_event(mouse(MouseEventKind::Down(MouseButton::Left), 0))?;
+    app.handle_mouse_event(mouse(MouseEventKind::Drag(MouseButton::Left), 0))?;
+    app.handle_mouse_event(mouse(MouseEventKind::Up(MouseButton::Left), 0))?;
+    assert_eq!(app.state, AppState::Default);
+    assert!(app.sidebar.log.last().unwrap().contains("onto itself"));
+
+    app.handle_mouse_event(mouse(MouseEventKind::Down(MouseButton::Left), 0))?;
+    app.handle_mouse_event(mouse(MouseEventKind::Up(MouseButton::Left), 3))?;
+    assert_eq!(app.state, AppState::Default);
+    assert!(app.sidebar.log.last().unwrap().contains("empty space"));
+
+    app.handle_mouse_event(mouse(MouseEventKind::Down(MouseButton::Left), 0))?;
+    app.handle_mouse_event(mouse(MouseEventKind::Up(MouseButton::Left), 1))?;
+    assert_eq!(app.state, AppState::Popup(PopupState::Connect));
+    assert_eq!(app.textarea.lines(), ["1.0 b"]);
+    assert_eq!(app.node_display.grid.floating_nodes[0].name, "a");
+    Ok(())
+}
+
+#[test]
+fn clicking_a_node_picks_it_and_dragging_moves_it() -> Result<()> {
+    let mut app = App {
+        screen: Rect::new(0, 0, 60, 20),
+        ..Default::default()
+    };
+    app.node_display.grid = connected_pair();
+    let mouse = |kind, column: u16, row: u16| MouseEvent {
+        kind,
+        column: NODE_H_SPACING + column * (NODE_H_SPACING + NODE_WIDTH) + 1,
+        row: NODE_V_SPACING + row * (NODE_V_SPACING + NODE_HEIGHT) + 1,
+        modifiers: KeyModifiers::NONE,
+    };
+
+    app.handle_mouse_event(mouse(MouseEventKind::Down(MouseButton::Left), 1, 0))?;
+    app.handle_mouse_event(mouse(MouseEventKind::Up(MouseButton::Left), 1, 0))?;
+    assert_eq!(app.state, AppState::Selection);
+    assert_eq!(app.node_display.grid.floating_nodes[0].name, "b");
+
+    app.handle_selection_mouse_event(mouse(MouseEventKind::Down(MouseButton::Left), 1, 0));
+    app.handle_selection_mouse_event(mouse(MouseEventKind::Drag(MouseButton::Left), 2, 0));
+    app.handle_selection_mouse_event(mouse(MouseEventKind::Drag(MouseButton::Left), 2, 1));
+    app.handle_selection_mouse_event(mouse(MouseEventKind::Up(MouseButton::Left), 2, 1));
+    assert_eq!(
+        app.node_display.grid.floating_nodes[0].location,
+        Location::new(2, 1)
+    );
+    Ok(())
+}
+
+#[test]
+fn clicking_node_name_in_log_centers_it() -> Result<()> {
+    let mut app = App {
+        screen: Rect::new(0, 0, 80, 20),
+        sidebar_state: SidebarState::Shown,
+        ..Default::default()
+    };
+    app.node_display.grid = connected_pair();
+    app.node_display.grid.nodes[1].location = Location::new(20, 10);
+    app.sidebar.width = SIDEBAR_DEFAULT_WIDTH;
+    app.sidebar.log = vec!["Choose b as initator.".to_string()];
+
+    let interior = Block::bordered().inner(sidebar_split(app.screen, app.sidebar.width)[1]);
+    // Below the tabs, on the `b`.
+    let click = MouseEvent {
+        kind: MouseEventKind::Down(MouseButton::Left),
+        column: interior.x + "Choose ".len() as u16,
+        row: interior.y + 1,
+        modifiers: KeyModifiers::NONE,
+    };
+    app.handle_mouse_event(click)?;
+
+    let mut expected = app.node_display.clone();
+    expected.center_on(&Location::new(20, 10), app.grid_area(), false);
+    assert_ne!(expected.offset, (0, 0));
+    assert_eq!(app.node_display.offset, expected.offset);
+    assert_eq!(app.sidebar.log.last().unwrap(), "Centered on b.");
+    assert!(app.jump_to_node("c").is_err());
+    Ok(())
+}
+
+#[test]
+fn fifo_algorithm_renders_solid_channels() -> Result<()> {
+    let mut app = App::default();
+    app.node_display.grid = connected_pair();
+    app.show_channels = true;
+    let area = Rect::new(0, 0, 30, 10);
+
+    app.sidebar.selector_scroll_state = SelectedAlgorithm::ChandyLamport as usize;
+    let mut buf = Buffer::empty(area);
+    (&app).render(area, &mut buf);
+    assert_eq!(buf[(NODE_H_SPACING + NODE_WIDTH, 4)].symbol(), "━");
+
+    app.sidebar.selector_scroll_state = SelectedAlgorithm::ChangRoberts as usize;
+    let mut buf = Buffer::empty(area);
+    (&app).render(area, &mut buf);
+    assert_eq!(buf[(NODE_H_SPACING + NODE_WIDTH, 4)].symbol(), "𜹜");
+
+    Ok(())
+}
+
+#[test]
+fn repeat_runs_last_selected_algorithm() -> Result<()> {
+    let mut app = App::default();
+    app.node_display.grid = NodeGrid::generate_ring(3, &mut StdRng::seed_from_u64(3));
+    app.handle_default_key_event(KeyCode::Char('.').into())?;
+    assert!(app.last_algorithm.is_none());
+
+    app.sidebar.selector_scroll_state = SelectedAlgorithm::ChangRoberts as usize;
+    app.select_algorithm()?;
+    app.sidebar.selector_scroll_state = SelectedAlgorithm::ChandyLamport as usize;
+    app.handle_default_key_event(KeyCode::Char('.').into())?;
+
+    assert_eq!(app.last_algorithm, Some(SelectedAlgorithm::ChangRoberts));
+    let runs = app
+        .sidebar
+        .log
+        .iter()
+        .filter(|l| l.starts_with("Started Chang-Roberts"))
+        .count();
+    assert_eq!(runs, 2);
+    Ok(())
+}
+
+#[test]
+fn sidebar_resizes_within_bounds() -> Result<()> {
+    let mut app = App::default();
+    app.handle_default_key_event(KeyCode::Char('\\').into())?;
+    assert_eq!(app.sidebar.width, SIDEBAR_DEFAULT_WIDTH);
+
+    app.handle_default_key_event(KeyCode::Char('<').into())?;
+    assert_eq!(
+        app.sidebar.width,
+        SIDEBAR_DEFAULT_WIDTH + SIDEBAR_WIDTH_STEP
+    );
+    let area = Rect::new(0, 0, 100, 20);
+    let [grid, sidebar] = sidebar_split(area, app.sidebar.width);
+    assert_eq!(sidebar.width, app.sidebar.width);
+    assert_eq!(grid.width + sidebar.width, area.width);
+
+    for _ in 0..20 {
+        app.handle_default_key_event(KeyCode::Char('<').into())?;
+    }
+    assert_eq!(app.sidebar.width, SIDEBAR_MAX_WIDTH);
+    for _ in 0..20 {
+        app.handle_default_key_event(KeyCode::Char('>').into())?;
+    }
+    assert_eq!(app.sidebar.width, SIDEBAR_MIN_WIDTH);
+
+    app.handle_default_key_event(KeyCode::Char('\\').into())?;
+    app.handle_default_key_event(KeyCode::Char('\\').into())?;
+    assert_eq!(app.sidebar.width, SIDEBAR_MIN_WIDTH);
+    Ok(())
+}
+
+#[test]
+fn replaying_recorded_actions_rebuilds_grid() -> Result<()> {
+    let mut app = App::default();
+    app.handle_default_key_event(KeyCode::Char('R').into())?;
+    for (name, x) in [("a", 0), ("b", 1), ("c", 2)] {
+        app.add_node(name.to_string())?;
+        for _ in 0..x {
+            app.move_node(1, 0);
+        }
+        app.commit_selection()?;
+    }
+    app.pick_node("a".to_string())?;
+    app.connect_selection(&Connection::new("b".to_string(), 2.0), true)?;
+    app.commit_selection()?;
+    app.handle_default_key_event(KeyCode::Char('R').into())?;
+    assert!(app.recording.is_none());
+    assert_eq!(app.state, AppState::Popup(PopupState::Script));
+
+    let script = serde_json::to_string(&app.script)?;
+    let mut replayed = App::default();
+    replayed.replay(&serde_json::from_str::<Vec<Action>>(&script)?)?;
+    assert_eq!(
+        serde_json::to_string(&replayed.node_display.grid)?,
+        serde_json::to_string(&app.node_display.grid)?
+    );
+    assert!(replayed.script.is_empty());
+    Ok(())
+}
+
+#[test]
+fn changes_trigger_backup() -> Result<()> {
+    let dir = env::temp_dir().join(format!("distributed-algorithms-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    let mut app = App {
+        latest_dir: dir.clone(),
+        latest_file: "grid.json".to_string(),
+        ..Default::default()
+    };
+    let backup = dir.join("grid.json.bak");
+    assert_eq!(app.backup_path(), backup);
+
+    app.add_node("a".to_string())?;
+    assert!(!backup.exists());
+
+    app.autosave_every = Some(2);
+    app.move_node(1, 0);
+    assert!(!backup.exists());
+    app.commit_selection()?;
+    assert!(backup.exists());
+    assert_eq!(app.newer_backup(), Some(backup.clone()));
+
+    let restored: NodeGrid = serde_json::from_str(&fs::read_to_string(&backup)?)?;
+    assert_eq!(restored.nodes[0].name, "a");
+
+    // A backup which can't be read is logged, and the popup stays open.
+    fs::write(&backup, "not a grid")?;
+    app.open_popup(PopupState::Restore);
+    app.confirm_restore();
+    fs::remove_dir_all(&dir)?;
+    assert_eq!(app.state, AppState::Popup(PopupState::Restore));
+    let prefix = format!("Could not restore backup {}: ", backup.display());
+    assert!(app.sidebar.log.last().unwrap().starts_with(&prefix));
+    Ok(())
+}
+
+#[test]
+fn hidden_connections_are_not_drawn() -> Result<()> {
+    let mut app = App::default();
+    app.node_display.grid = connected_pair();
+    let area = Rect::new(0, 0, 30, 10);
+    let connection_cell = (NODE_H_SPACING + NODE_WIDTH, 4);
+
+    let mut buf = Buffer::empty(area);
+    (&app).render(area, &mut buf);
+    assert_ne!(buf[connection_cell].symbol(), " ");
+
+    app.handle_default_key_event(KeyCode::Char('h').into())?;
+    let mut hidden = Buffer::empty(area);
+    (&app).render(area, &mut hidden);
+    assert_eq!(hidden[connection_cell].symbol(), " ");
+
+    // Same as drawing the grid without any connections at all.
+    app.hide_connections = false;
+    app.node_display
+        .grid
+        .nodes
+        .iter_mut()
+        .for_each(|n| n.connections.clear());
+    let mut unconnected = Buffer::empty(area);
+    (&app).render(area, &mut unconnected);
+    assert_eq!(hidden, unconnected);
+    Ok(())
+}
+
+#[test]
+fn go_to_centers_coordinate() -> Result<()> {
+    let mut app = App::default();
+    app.node_display.grid = connected_pair();
+    app.node_display.grid.nodes[1].location = Location::new(10, 8);
+    app.screen = Rect::new(0, 0, 40, 20);
+
+    app.go_to("5 4")?;
+    let x = NODE_H_SPACING + 5 * (NODE_H_SPACING + NODE_WIDTH) + NODE_WIDTH / 2;
+    let y = NODE_V_SPACING + 4 * (NODE_V_SPACING + NODE_HEIGHT) + NODE_HEIGHT / 2;
+    assert_eq!(app.node_display.offset, (x - 20, y - 10));
+
+    // Past the last node, the coordinate is clamped.
+    app.go_to("50 50")?;
+    let x = NODE_H_SPACING + 10 * (NODE_H_SPACING + NODE_WIDTH) + NODE_WIDTH / 2;
+    let y = NODE_V_SPACING + 8 * (NODE_V_SPACING + NODE_HEIGHT) + NODE_HEIGHT / 2;
+    assert_eq!(app.node_display.offset, (x - 20, y - 10));
+    let mut buf = Buffer::empty(app.screen);
+    (&app).render(app.screen, &mut buf);
+    assert_eq!(buf[(20, 10)].symbol(), "b");
+
+    assert!(app.go_to("5").is_err());
+    assert!(app.go_to("x 5").is_err());
+    Ok(())
+}
+
+#[test]
+fn session_round_trips() -> Result<()> {
+    let path = env::temp_dir().join(format!(
+        "distributed-algorithms-session-{}.json",
+        std::process::id()
+    ));
+    let mut app = App::default();
+    app.node_display.grid = connected_pair();
+    app.seed_states("5 42")?;
+    app.sidebar.selector_scroll_state = SelectedAlgorithm::LaiYang as usize;
+    app.toggle_sidebar();
+    app.compact = true;
+    app.save_session(&path)?;
+
+    let mut resumed = App::default();
+    resumed.load_session(&path)?;
+    fs::remove_file(&path)?;
+    let names = |app: &App| -> Vec<(String, isize)> {
+        app.node_display
+            .grid
+            .nodes
+            .iter()
+            .map(|n| (n.name.clone(), n.state))
+            .collect()
+    };
+    assert_eq!(names(&resumed), names(&app));
+    assert_eq!(resumed.sidebar.log, app.sidebar.log);
+    assert_eq!(resumed.seed, Some(42));
+    assert_eq!(
+        resumed.highlighted_algorithm(),
+        Some(SelectedAlgorithm::LaiYang)
+    );
+    assert!(resumed.sidebar_state.is_shown() && resumed.compact);
+
+    // Missing fields fall back to their defaults.
+    let session: session::Session = serde_json::from_str(r#"{"log": ["hello"]}"#)?;
+    resumed.resume(session);
+    assert_eq!(resumed.sidebar.log, ["hello"]);
+    assert!(resumed.node_display.grid.nodes.is_empty());
+    assert_eq!(resumed.sidebar.width, SIDEBAR_DEFAULT_WIDTH);
+    Ok(())
+}
+
+#[test]
+fn loading_a_large_grid_zooms_to_fit() -> Result<()> {
+    let path = env::temp_dir().join(format!(
+        "distributed-algorithms-zoom-{}.json",
+        std::process::id()
+    ));
+    let mut spread = connected_pair();
+    spread.nodes[1].location = Location::new(12, 9);
+    fs::write(&path, serde_json::to_string(&spread)?)?;
+
+    let mut app = App {
+        screen: Rect::new(0, 0, 80, 30),
+        ..Default::default()
+    };
+    app.node_display.offset = (5, 5);
+    app.load_grid(&path)?;
+    assert!(app.compact);
+    assert_eq!(app.node_display.offset, (0, 0));
+    let mut buf = Buffer::empty(app.screen);
+    (&app).render(app.screen, &mut buf);
+    let (x, y) = app.node_display.grid.place_compact(&Location::new(12, 9));
+    assert!(x + COMPACT_NODE_WIDTH < app.screen.width && y < app.screen.height);
+    assert_ne!(buf[(x, y)], ratatui::buffer::Cell::EMPTY);
+
+    // A zoom chosen by hand is kept.
+    app.compact = false;
+    app.zoom_pinned = true;
+    app.load_grid(&path)?;
+    assert!(!app.compact);
+    fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+fn grid_argument_is_loaded_at_start() -> Result<()> {
+    let path = env::temp_dir().join(format!(
+        "distributed-algorithms-argument-{}.json",
+        std::process::id()
+    ));
+    fs::write(&path, serde_json::to_string(&connected_pair())?)?;
+
+    let mut app = App::default();
+    app.load_argument(&path.to_string_lossy())?;
+    let names: Vec<&str> = app
+        .node_display
+        .grid
+        .nodes
+        .iter()
+        .map(|n| n.name.as_str())
+        .collect();
+    assert_eq!(names, ["a", "b"]);
+    assert_eq!(app.latest_file, path.file_name().unwrap().to_string_lossy());
+
+    fs::write(&path, "not a grid")?;
+    assert!(
+        App::default()
+            .load_argument(&path.to_string_lossy())
+            .is_err()
+    );
+    fs::remove_file(&path)?;
+    assert!(
+        App::default()
+            .load_argument(&path.to_string_lossy())
+            .is_err()
+    );
+    Ok(())
+}
+
+#[test]
+fn headless_run_writes_report() -> Result<()> {
+    let path = |name: &str| {
+        env::temp_dir().join(format!(
+            "distributed-algorithms-headless-{name}-{}.json",
+            std::process::id()
+        ))
+    };
+    let (grid, out) = (path("grid"), path("out"));
+    fs::write(&grid, serde_json::to_string(&connected_pair())?)?;
+    let args = vec![
+        "--run".to_string(),
+        "chandylamport".to_string(),
+        "--grid".to_string(),
+        grid.to_string_lossy().to_string(),
+        "--out".to_string(),
+        out.to_string_lossy().to_string(),
+        "--frozen".to_string(),
+    ];
+
+    Headless::parse(&args)?.unwrap().run()?;
+    let report: serde_json::Value = serde_json::from_str(&fs::read_to_string(&out)?)?;
+    fs::remove_file(&grid)?;
+    fs::remove_file(&out)?;
+    assert_eq!(report["algorithm"], "ChandyLamport");
+    assert_eq!(report["completed"], true);
+    let log = report["log"].as_array().unwrap();
+    assert!(log.iter().any(|l| l == "Snapshot completed."));
+    assert!(report["results"]["a"].is_string() && report["results"]["b"].is_string());
+    assert!(!report["trace"].as_array().unwrap().is_empty());
+
+    assert!(Headless::parse(&["grid.json".to_string()])?.is_none());
+    assert!(Headless::parse(&args[..4]).is_err());
+    Ok(())
+}
+
+#[test]
+fn headless_run_reports_an_unfit_grid() -> Result<()> {
+    let path = |name: &str| {
+        env::temp_dir().join(format!(
+            "distributed-algorithms-unfit-{name}-{}.json",
+            std::process::id()
+        ))
+    };
+    let (grid, out) = (path("grid"), path("out"));
+    let mut dead_end = connected_pair();
+    dead_end.nodes[1].connections.clear();
+    fs::write(&grid, serde_json::to_string(&dead_end)?)?;
+    for algorithm in ["chandylamport", "laiyang"] {
+        let args = vec![
+            "--run".to_string(),
+            algorithm.to_string(),
+            "--grid".to_string(),
+            grid.to_string_lossy().to_string(),
+            "--out".to_string(),
+            out.to_string_lossy().to_string(),
+        ];
+        Headless::parse(&args)?.unwrap().run()?;
+        let report: serde_json::Value = serde_json::from_str(&fs::read_to_string(&out)?)?;
+        assert_eq!(report["completed"], false);
+        let error = report["error"].as_str().unwrap();
+        assert!(error.ends_with("No outgoing connection from b."), "{error}");
+        assert!(report["trace"].as_array().unwrap().is_empty());
+    }
+    fs::remove_file(&grid)?;
+    fs::remove_file(&out)?;
+    Ok(())
+}
+
+#[test]
+fn unfit_grid_keeps_selector_open() {
+    let mut app = App::default();
+    app.node_display.grid = connected_pair();
+    app.node_display.grid.nodes[1].connections.clear();
+    app.sidebar.selector();
+    app.sidebar.selector_scroll_state = SelectedAlgorithm::ChangRoberts as usize;
+
+    assert!(app.select_algorithm().is_err());
+    assert_eq!(app.sidebar.shown_content, SidebarContent::Selector);
+    assert!(app.last_algorithm.is_none());
+    assert_eq!(
+        app.sidebar.log,
+        ["Cannot run ChangRoberts: No outgoing connection from b. Graph is not a ring."]
+    );
+}
+
+#[test]
+fn floating_node_survives_save_and_load() -> Result<()> {
+    let path = env::temp_dir().join(format!(
+        "distributed-algorithms-floating-{}.json",
+        std::process::id()
+    ));
+    let mut app = App::default();
+    app.node_display.grid = connected_pair();
+    app.node_display.grid.pick("b".to_string())?;
+    app.save_grid(&path, false)?;
+
+    let mut loaded = App::default();
+    loaded.load_grid(&path)?;
+    let grid = &loaded.node_display.grid;
+    assert_eq!(grid.nodes.len(), 1);
+    assert_eq!(grid.floating_nodes[0].name, "b");
+    assert_eq!(grid.floating_nodes[0].connections[0].other, "a");
+    assert_eq!(loaded.state, AppState::Selection);
+    assert_eq!(loaded.sidebar.log, ["Resuming placement of b."]);
+
+    // Grids saved without floating nodes don't have the key at all.
+    app.node_display.grid.commit()?;
+    app.save_grid(&path, false)?;
+    assert!(!fs::read_to_string(&path)?.contains("floating_nodes"));
+    loaded.load_grid(&path)?;
+    assert!(loaded.node_display.grid.floating_nodes.is_empty());
+    assert_eq!(loaded.state, AppState::Default);
+    fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+fn log_is_saved_and_loaded_with_the_grid() -> Result<()> {
+    let path = env::temp_dir().join(format!(
+        "distributed-algorithms-log-{}.json",
+        std::process::id()
+    ));
+    let mut app = App::default();
+    app.node_display.grid = connected_pair();
+    app.sidebar.log = vec!["Node b was chosen as leader.".to_string()];
+    app.save_grid(&path, false)?;
+
+    let mut loaded = App::default();
+    loaded.sidebar.log = vec!["Earlier line.".to_string()];
+    loaded.load_grid(&path)?;
+    assert_eq!(loaded.sidebar.log, app.sidebar.log);
+    assert_eq!(loaded.node_display.grid.nodes.len(), 2);
+
+    // Anonymized grids leave the log out, as it names the nodes.
+    app.save_grid(&path, true)?;
+    assert!(!fs::read_to_string(&path)?.contains("leader"));
+    loaded.load_grid(&path)?;
+    assert_eq!(loaded.sidebar.log, app.sidebar.log);
+    fs::remove_file(&path)?;
+
+    // A path which can't be written is logged, and the popup stays open.
+    let missing = path.join("grid.json");
+    app.open_popup(PopupState::Save);
+    app.textarea = TextArea::from([missing.display().to_string()]);
+    app.confirm_save(true);
+    assert_eq!(app.state, AppState::Popup(PopupState::Save));
+    let prefix = format!("Could not save {}: ", missing.display());
+    assert!(app.sidebar.log.last().unwrap().starts_with(&prefix));
+    Ok(())
+}
+
+#[test]
+fn special_nodes_cycle_to_the_leader() {
+    let mut app = App {
+        screen: Rect::new(0, 0, 20, 10),
+        ..Default::default()
+    };
+    app.node_display.grid = NodeGrid::generate_ring(6, &mut StdRng::seed_from_u64(3));
+    app.handle_default_key_event(KeyCode::Char('x').into())
+        .unwrap();
+    assert_eq!(app.sidebar.log, ["No special nodes in the last run."]);
+
+    let grid = &mut app.node_display.grid;
+    for node in grid.nodes.iter() {
+        grid.display_states
+            .insert(node.name.clone(), nodegrid::NodeDisplayState::Passive);
+    }
+    grid.display_states
+        .insert("n4".to_string(), nodegrid::NodeDisplayState::Leader);
+    assert_eq!(grid.special_nodes(), [("n4".to_string(), "leader")]);
+
+    for _ in 0..2 {
+        app.sidebar.log.clear();
+        app.node_display.offset = (0, 0);
+        app.handle_default_key_event(KeyCode::Char('x').into())
+            .unwrap();
+        assert_eq!(
+            app.sidebar.log,
+            ["Special nodes: n4 (leader).", "Centered on n4."]
+        );
+        let mut expected = app.node_display.clone();
+        let location = expected.grid.nodes[4].location;
+        expected.center_on(&location, app.grid_area(), false);
+        assert_ne!(expected.offset, (0, 0));
+        assert_eq!(app.node_display.offset, expected.offset);
+    }
+}
+
+#[test]
+fn election_comparison_repeats_with_the_same_seed() -> Result<()> {
+    let table = |seed: &str| -> Result<Vec<String>> {
+        let mut app = App {
+            delivery: Some(Delivery::RandomInsert),
+            ..Default::default()
+        };
+        app.compare_elections(seed)?;
+        Ok(app.sidebar.log)
+    };
+    for _ in 0..5 {
+        assert_eq!(table("9 3")?, table("9 3")?);
+    }
+    Ok(())
+}
+
+#[test]
+fn election_comparison_has_a_row_per_election() -> Result<()> {
+    let mut app = App::default();
+    assert!(app.compare_elections("0").is_err());
+    assert!(app.compare_elections("1").is_err());
+    assert!(app.compare_elections("1000000000000").is_err());
+    app.compare_elections("5 5")?;
+    assert_eq!(app.seed, Some(5));
+    assert!(app.node_display.grid.nodes.is_empty());
+
+    let log = &app.sidebar.log;
+    assert_eq!(log[0], "Elections on a ring of 5 nodes with seed 5:");
+    let rows: Vec<Vec<&str>> = log[2..]
+        .iter()
+        .map(|r| r.split_whitespace().collect())
+        .collect();
+    let elections: Vec<SelectedAlgorithm> = SelectedAlgorithm::iter()
+        .filter(|a| a.is_election())
+        .collect();
+    assert_eq!(rows.len(), elections.len());
+    for (row, algorithm) in rows.iter().zip(elections) {
+        assert_eq!(row[0], algorithm.to_string());
+        assert!(row[1].parse::<usize>()? > 0);
+        // n² for Chang-Roberts, 2n(⌊log n⌋+1) for the others.
+        let bound = match algorithm {
+            SelectedAlgorithm::ChangRoberts => "25",
+            _ => "30",
+        };
+        assert_eq!(row[2], bound);
+    }
+    Ok(())
+}
+
+#[test]
+fn stepping_through_a_run_matches_running_it_whole() -> Result<()> {
+    let mut app = App::default();
+    app.node_display.grid = NodeGrid::generate_ring(5, &mut StdRng::seed_from_u64(2));
+    app.run_seed = Some(4);
+    app.sidebar_state = SidebarState::Shown;
+    for algorithm in [
+        SelectedAlgorithm::ChangRoberts,
+        SelectedAlgorithm::ChandyLamport,
+        SelectedAlgorithm::LaiYang,
+    ] {
+        app.sidebar.log.clear();
+        app.run_algorithm(algorithm)?;
+        let whole = std::mem::take(&mut app.sidebar.log);
+        let results = app.node_display.grid.results.clone();
+
+        app.sidebar.selector();
+        app.sidebar.selector_scroll_state = algorithm as usize;
+        app.handle_default_key_event(KeyCode::Char(' ').into())?;
+        assert_eq!(app.state, AppState::Stepping, "{algorithm}");
+        let mut steps = 1;
+        while app.state == AppState::Stepping {
+            app.handle_stepping_key_event(KeyCode::Char(' ').into());
+            steps += 1;
+        }
+        assert!(steps > 5, "{algorithm}");
+        assert_eq!(app.sidebar.log, whole, "{algorithm}");
+        assert_eq!(app.node_display.grid.results, results, "{algorithm}");
+    }
+
+    app.sidebar.selector();
+    app.sidebar.selector_scroll_state = SelectedAlgorithm::Luby as usize;
+    app.handle_default_key_event(KeyCode::Char(' ').into())?;
+    assert_eq!(app.state, AppState::Default);
+    assert_eq!(
+        app.sidebar.log.last().unwrap(),
+        "Luby can't be run step by step."
+    );
+    assert!(
+        app.get_pseudocode()
+            .ends_with("<Space> can't run it step by step.")
+    );
+    app.sidebar.selector_scroll_state = SelectedAlgorithm::Echo as usize;
+    assert!(!app.get_pseudocode().contains("step by step"));
+    Ok(())
+}
+
+#[test]
+fn stepping_title_shows_the_messages_in_transit() -> Result<()> {
+    let mut app = App::default();
+    app.node_display.grid = NodeGrid::generate_ring(5, &mut StdRng::seed_from_u64(2));
+    app.run_seed = Some(4);
+    app.sidebar_state = SidebarState::Shown;
+    app.sidebar.selector();
+    app.sidebar.selector_scroll_state = SelectedAlgorithm::ChangRoberts as usize;
+    app.handle_default_key_event(KeyCode::Char(' ').into())?;
+    app.handle_stepping_key_event(KeyCode::Char(' ').into());
+    assert_eq!(app.state, AppState::Stepping);
+
+    let trace = &app.node_display.grid.trace;
+    let count = |event| trace.iter().filter(|r| r.event == event).count();
+    let in_transit = count(nodegrid::TraceEvent::Sent) - count(nodegrid::TraceEvent::Delivered);
+    assert_eq!(app.stepping.as_ref().unwrap().in_transit(), in_transit);
+
+    app.sidebar_state = SidebarState::Hidden;
+    let area = Rect::new(0, 0, 120, 10);
+    let mut buf = Buffer::empty(area);
+    (&app).render(area, &mut buf);
+    let title: String = (0..area.width).map(|x| buf[(x, 0)].symbol()).collect();
+    assert!(
+        title.contains(&format!("In transit: {in_transit} ")),
+        "{title}"
+    );
+    Ok(())
+}
+
+#[test]
+fn seeding_states_rejects_huge_ranges_and_follows_the_run_seed() -> Result<()> {
+    let mut app = App::default();
+    app.node_display.grid = connected_pair();
+    assert!(app.seed_states("18446744073709551615").is_err());
+    assert!(app.seed_states("9223372036854775808 1").is_err());
+
+    let states = |app: &App| -> Vec<isize> {
+        app.node_display
+            .grid
+            .nodes
+            .iter()
+            .map(|n| n.state)
+            .collect()
+    };
+    app.run_seed = Some(7);
+    app.seed_states("1000")?;
+    let first = states(&app);
+    app.seed_states("1000 7")?;
+    assert_eq!(states(&app), first);
+    assert_eq!(app.seed, Some(7));
+    Ok(())
+}
+
+#[test]
+fn nodes_can_be_inspected_while_stepping() -> Result<()> {
+    let mut app = App::default();
+    app.node_display.grid = NodeGrid::generate_ring(4, &mut StdRng::seed_from_u64(2));
+    app.sidebar_state = SidebarState::Shown;
+    app.sidebar.selector();
+    app.sidebar.selector_scroll_state = SelectedAlgorithm::ChangRoberts as usize;
+    app.handle_default_key_event(KeyCode::Char(' ').into())?;
+    app.handle_stepping_key_event(KeyCode::Char(' ').into());
+    let name = app.node_display.grid.nodes[1].name.clone();
+
+    app.handle_stepping_key_event(KeyCode::Char('i').into());
+    assert_eq!(app.state, AppState::Popup(PopupState::InspectStep));
+    assert!(app.inspect_while_stepping("nowhere").is_err());
+    app.inspect_while_stepping(&name)?;
+    assert_eq!(app.state, AppState::Popup(PopupState::Inspect));
+    assert_eq!(app.textarea.lines()[0], format!("Name: {name}"));
+
+    app.close_inspection();
+    assert_eq!(app.state, AppState::Stepping);
+    assert!(app.stepping.is_some());
+    Ok(())
+}
+
+#[test]
+fn inbox_lists_a_fifo_channel_in_sending_order() -> Result<()> {
+    let mut app = App::default();
+    app.node_display.grid = NodeGrid::generate_bidirectional_ring(4, &mut StdRng::seed_from_u64(2));
+    app.run_seed = Some(3);
+    app.sidebar_state = SidebarState::Shown;
+    app.sidebar.selector();
+    app.sidebar.selector_scroll_state = SelectedAlgorithm::ChandyLamport as usize;
+    app.handle_default_key_event(KeyCode::Char(' ').into())?;
+    assert_eq!(app.state, AppState::Stepping);
+
+    // Nothing is delivered yet, so every channel holds all it was sent.
+    let mut channels: std::collections::BTreeMap<(String, String), Vec<String>> =
+        Default::default();
+    for record in app.node_display.grid.trace.iter() {
+        assert_eq!(record.event, nodegrid::TraceEvent::Sent);
+        channels
+            .entry((record.sender.clone(), record.destination.clone()))
+            .or_default()
+            .push(format!(
+                "<{}> {}->{}",
+                record.kind, record.sender, record.destination
+            ));
+    }
+    let ((sender, destination), sent) = channels
+        .iter()
+        .find(|(_, sent)| sent.len() > 1)
+        .expect("a channel with more than one message");
+    app.inspect_while_stepping(destination)?;
+    assert!(
+        app.textarea
+            .lines()
+            .contains(&format!("    from {sender}: {}", sent.join(", "))),
+        "{:?}",
+        app.textarea.lines()
+    );
+    Ok(())
+}
+
+#[test]
+fn making_a_ring_which_drops_connections_asks_first() {
+    let mut app = App::default();
+    app.node_display.grid = connected_pair();
+    app.node_display.grid.nodes.push(node::Node {
+        name: "c".to_string(),
+        id: 3,
+        connections: vec![Connection::new("a".to_string(), 1.0)],
+        location: Location::new(2, 0),
+        ..Default::default()
+    });
+    let successor = |app: &App| app.node_display.grid.nodes[1].connections[0].other.clone();
+
+    app.apply_fix(Fix::MakeRing);
+    assert_eq!(successor(&app), "a");
+    assert!(app.sidebar.log.last().unwrap().contains("press <r> again"));
+    app.apply_fix(Fix::MakeRing);
+    assert_eq!(successor(&app), "c");
+    assert!(app.node_display.grid.is_ring());
+}
+
+#[test]
+fn failed_import_keeps_the_popup_open() -> Result<()> {
+    let path = env::temp_dir().join(format!(
+        "distributed-algorithms-adjacency-{}.txt",
+        std::process::id()
+    ));
+    let mut app = App::default();
+    app.open_popup(PopupState::ImportAdjacency);
+    app.textarea = TextArea::from([path.display().to_string()]);
+    app.confirm_import_adjacency();
+    assert_eq!(app.state, AppState::Popup(PopupState::ImportAdjacency));
+    let missing = app.sidebar.log.last().unwrap();
+    assert!(missing.starts_with(&format!("Could not import {}: ", path.display())));
+
+    fs::write(&path, "a: b x")?;
+    app.confirm_import_adjacency();
+    assert_eq!(app.state, AppState::Popup(PopupState::ImportAdjacency));
+    assert_eq!(
+        app.sidebar.log.last().unwrap(),
+        &format!(
+            "Could not import {}: Malformed weight \"x\" on line 1.",
+            path.display()
+        )
+    );
+
+    fs::write(&path, "a: b 2.0")?;
+    app.confirm_import_adjacency();
+    fs::remove_file(&path)?;
+    assert_eq!(app.state, AppState::Default);
+    assert_eq!(app.node_display.grid.nodes.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn failed_diff_keeps_the_popup_open() -> Result<()> {
+    let path = env::temp_dir().join(format!(
+        "distributed-algorithms-diff-{}.json",
+        std::process::id()
+    ));
+    let mut app = App::default();
+    app.open_popup(PopupState::Diff);
+    app.textarea = TextArea::from([path.display().to_string()]);
+    app.confirm_diff();
+    assert_eq!(app.state, AppState::Popup(PopupState::Diff));
+    let prefix = format!("Could not compare with {}: ", path.display());
+    assert!(app.sidebar.log.last().unwrap().starts_with(&prefix));
+
+    fs::write(&path, "not a grid")?;
+    app.confirm_diff();
+    assert_eq!(app.state, AppState::Popup(PopupState::Diff));
+    assert!(app.sidebar.log.last().unwrap().starts_with(&prefix));
+
+    fs::write(&path, serde_json::to_string(&connected_pair())?)?;
+    app.confirm_diff();
+    fs::remove_file(&path)?;
+    assert_eq!(app.state, AppState::Default);
+    assert!(app.sidebar.log.contains(&format!(
+        "Changes from the current grid to {}:",
+        path.display()
+    )));
+    Ok(())
+}
+
+#[test]
+fn failed_session_save_keeps_the_popup_open() -> Result<()> {
+    let dir = env::temp_dir().join(format!(
+        "distributed-algorithms-missing-{}",
+        std::process::id()
+    ));
+    let mut app = App::default();
+    app.open_popup(PopupState::SaveSession);
+    app.textarea = TextArea::from([dir.join("session.json").display().to_string()]);
+    app.confirm_save_session();
+    assert_eq!(app.state, AppState::Popup(PopupState::SaveSession));
+    assert!(
+        app.sidebar
+            .log
+            .last()
+            .unwrap()
+            .starts_with("Could not save session: ")
+    );
+
+    fs::create_dir(&dir)?;
+    app.confirm_save_session();
+    fs::remove_dir_all(&dir)?;
+    assert_eq!(app.state, AppState::Default);
+    Ok(())
+}
+
+#[test]
+fn failed_script_export_and_replay_keep_the_popups_open() -> Result<()> {
+    let path = env::temp_dir().join(format!(
+        "distributed-algorithms-script-{}.json",
+        std::process::id()
+    ));
+    let mut app = App::default();
+    app.open_popup(PopupState::Script);
+    let missing = path.join("script.json").display().to_string();
+    app.textarea = TextArea::from([missing]);
+    app.confirm_export_script();
+    assert_eq!(app.state, AppState::Popup(PopupState::Script));
+    assert!(
+        app.sidebar
+            .log
+            .last()
+            .unwrap()
+            .starts_with("Could not export script: ")
+    );
+
+    app.open_popup(PopupState::Load);
+    app.textarea = TextArea::from([path.display().to_string()]);
+    app.confirm_load(true);
+    assert_eq!(app.state, AppState::Popup(PopupState::Load));
+    let prefix = format!("Could not load {}: ", path.display());
+    assert!(app.sidebar.log.last().unwrap().starts_with(&prefix));
+    app.confirm_load(false);
+    assert_eq!(app.state, AppState::Popup(PopupState::Load));
+    assert!(app.sidebar.log.last().unwrap().starts_with(&prefix));
+
+    fs::write(&path, "[]")?;
+    app.confirm_load(true);
+    fs::remove_file(&path)?;
+    assert_eq!(app.state, AppState::Default);
+    Ok(())
+}
+
+#[test]
+fn failed_dump_keeps_the_popup_open() -> Result<()> {
+    let path = env::temp_dir().join(format!(
+        "distributed-algorithms-trace-{}.json",
+        std::process::id()
+    ));
+    let mut app = App::default();
+    app.node_display.grid = connected_pair();
+    app.run_algorithm(SelectedAlgorithm::Echo)?;
+    app.open_popup(PopupState::Dump);
+    let missing = path.join("trace.json");
+    app.textarea = TextArea::from([missing.display().to_string()]);
+    for trace in [true, false] {
+        app.confirm_dump(trace);
+        assert_eq!(app.state, AppState::Popup(PopupState::Dump));
+        let prefix = format!("Could not write {}: ", missing.display());
+        assert!(app.sidebar.log.last().unwrap().starts_with(&prefix));
+    }
+
+    app.textarea = TextArea::from([path.display().to_string()]);
+    app.confirm_dump(true);
+    let records: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+    fs::remove_file(&path)?;
+    assert_eq!(app.state, AppState::Default);
+    assert!(!records.as_array().unwrap().is_empty());
+    Ok(())
+}
+
+#[test]
+fn message_bound_counts_the_included_nodes_only() -> Result<()> {
+    let mut app = App::default();
+    app.node_display.grid = NodeGrid::generate_bidirectional_ring(4, &mut StdRng::seed_from_u64(2));
+    let names: Vec<String> = app.node_display.grid.nodes[..3]
+        .iter()
+        .map(|n| n.name.clone())
+        .collect();
+    app.node_display.grid.included = names.into_iter().collect();
+    app.run_algorithm(SelectedAlgorithm::Echo)?;
+    // Including every node afterwards does not change the grid that was run.
+    app.node_display.grid.included.clear();
+    app.handle_default_key_event(KeyCode::Char('B').into())?;
+    assert_eq!(
+        app.sidebar.log.last().unwrap(),
+        "Echo on 3 nodes: bound 2E = 4 messages, last run sent 4."
+    );
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use std::{fs, io, path::Path};
+
+use anyhow::Result;
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+/// Name of the file the theme is read from, in the directory the app is
+/// started in.
+pub(crate) const THEME_FILE: &str = "theme.json";
+
+/// Colors used to draw the grid and the sidebar. Colors missing from a theme
+/// file keep their default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Theme {
+    pub(crate) node: Color,
+    pub(crate) floating: Color,
+    /// Nodes which ended the last run as leader.
+    pub(crate) leader: Color,
+    /// Nodes which lost the election of the last run.
+    pub(crate) passive: Color,
+    /// Nodes which took part in the snapshot of the last run.
+    pub(crate) recorded: Color,
+    /// Nodes marked as reachable, and highlighted connections.
+    pub(crate) marked: Color,
+    /// The node in the critical section.
+    pub(crate) critical: Color,
+    /// Nodes left out of algorithm runs.
+    pub(crate) excluded: Color,
+    pub(crate) connection: Color,
+    /// Message counts of the connections which carried at least half as many
+    /// messages as the busiest one.
+    pub(crate) busy: Color,
+    pub(crate) sidebar_highlight: Color,
+    pub(crate) background: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            node: Color::Green,
+            floating: Color::Cyan,
+            leader: Color::LightGreen,
+            passive: Color::Blue,
+            recorded: Color::LightBlue,
+            marked: Color::Yellow,
+            critical: Color::Magenta,
+            excluded: Color::DarkGray,
+            connection: Color::Reset,
+            busy: Color::Red,
+            sidebar_highlight: Color::Cyan,
+            background: Color::Reset,
+        }
+    }
+}
+
+/// Themes which can be picked by name instead of listing every color.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum Preset {
+    Default,
+    HighContrast,
+}
+
+/// A theme file holds either the name of a preset or the colors themselves.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ThemeFile {
+    Preset(Preset),
+    Custom(Theme),
+}
+
+impl Theme {
+    /// Bright colors on black, with every kind of node set apart.
+    pub(crate) fn high_contrast() -> Self {
+        Theme {
+            node: Color::White,
+            floating: Color::LightCyan,
+            leader: Color::LightGreen,
+            passive: Color::LightBlue,
+            recorded: Color::LightRed,
+            marked: Color::LightYellow,
+            critical: Color::LightMagenta,
+            excluded: Color::Gray,
+            connection: Color::White,
+            busy: Color::LightRed,
+            sidebar_highlight: Color::LightYellow,
+            background: Color::Black,
+        }
+    }
+
+    /// Reads the theme at `path`, falling back to the default one when there
+    /// is no such file.
+    pub(crate) fn load(path: &Path) -> Result<Self> {
+        let file = match fs::OpenOptions::new().read(true).open(path) {
+            Ok(file) => file,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Theme::default()),
+            Err(error) => return Err(error.into()),
+        };
+        let theme = match serde_json::from_reader(io::BufReader::new(file))? {
+            ThemeFile::Preset(Preset::Default) => Theme::default(),
+            ThemeFile::Preset(Preset::HighContrast) => Theme::high_contrast(),
+            ThemeFile::Custom(theme) => theme,
+        };
+        Ok(theme)
+    }
+}